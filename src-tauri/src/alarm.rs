@@ -0,0 +1,350 @@
+//! 指定した時刻（ローカル時間）に鳴るアラーム。
+//!
+//! アラームは `alarms.json` に保存し、曜日を指定すれば毎週繰り返す。
+//! スケジューラスレッドは短い間隔で壁時計を確認し、前回確認してから現在までの間に
+//! 発火時刻があれば鳴らす。これによりスリープ復帰後や夏時間の切り替え時も取りこぼさない。
+//! 起動直後の確認は各アラームを保存した時刻（鳴ったことがあればその時刻）から始めるので、
+//! アプリを閉じていた間に過ぎた回も拾う。
+//! スリープ中や終了中に複数回分を過ぎていても鳴らすのは 1 回だけで、遅れて鳴らすか捨てるかは
+//! `resync::MissedAlarmPolicy` に従う。
+//! 発火時は `alarm-fired` を emit し、Time Up ウィンドウは `escalation` の段階に沿って表示する。
+
+use chrono::{DateTime, Duration as ChronoDuration, LocalResult, NaiveTime, TimeZone, Weekday};
+use chrono::{Datelike, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::resync::{MissedAlarmPolicy, ResyncState};
+use crate::timer::TimerAlarm;
+
+pub const ALARM_FIRED_EVENT: &str = "alarm-fired";
+pub const ALARMS_CHANGED_EVENT: &str = "alarms-changed";
+
+/// スケジューラが壁時計を確認する間隔
+const SCHEDULER_POLL: Duration = Duration::from_secs(1);
+
+/// 発火時刻をこれ以上過ぎていたら取りこぼしとみなす（スリープ復帰直後など）
+const MISSED_ALARM_GRACE_SECS: i64 = 60;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct WallClockAlarm {
+    pub id: String,
+    pub label: String,
+    /// 0〜23
+    pub hour: u32,
+    /// 0〜59
+    pub minute: u32,
+    /// 繰り返す曜日。空なら次の該当時刻に 1 回だけ鳴る
+    #[serde(default)]
+    pub weekdays: Vec<Weekday>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub alarm: TimerAlarm,
+    /// 最後に鳴った発火時刻（UNIX エポックからのミリ秒）。同じ回を二重に鳴らさないために使う
+    #[serde(default)]
+    pub last_fired_unix_ms: Option<i64>,
+    /// 保存した時刻（UNIX エポックからのミリ秒）。これより前の回は鳴らさず、
+    /// 起動時はここから確認してアプリを閉じていた間の回を拾う
+    #[serde(default)]
+    pub armed_unix_ms: Option<i64>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl WallClockAlarm {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.hour > 23 || self.minute > 59 {
+            return Err(format!(
+                "Invalid alarm time: {:02}:{:02}",
+                self.hour, self.minute
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn repeats(&self) -> bool {
+        !self.weekdays.is_empty()
+    }
+}
+
+/// `after` より後で最初にアラームが鳴る時刻を返す。
+/// 夏時間で存在しない時刻は 1 時間後へずらし、重複する時刻は早い方を採用する
+pub fn next_occurrence<Tz: TimeZone>(
+    alarm: &WallClockAlarm,
+    after: &DateTime<Tz>,
+) -> Option<DateTime<Tz>> {
+    let time = NaiveTime::from_hms_opt(alarm.hour, alarm.minute, 0)?;
+    let tz = after.timezone();
+    let start = after.date_naive();
+    // 夏時間でずれた分も拾えるよう 1 週間 + 1 日先まで調べる
+    (0..=8)
+        .filter_map(|offset| start.checked_add_signed(ChronoDuration::days(offset)))
+        .filter(|date| alarm.weekdays.is_empty() || alarm.weekdays.contains(&date.weekday()))
+        .filter_map(|date| {
+            let naive = date.and_time(time);
+            match tz.from_local_datetime(&naive) {
+                LocalResult::Single(t) => Some(t),
+                LocalResult::Ambiguous(earliest, _) => Some(earliest),
+                LocalResult::None => tz
+                    .from_local_datetime(&(naive + ChronoDuration::hours(1)))
+                    .earliest(),
+            }
+        })
+        .find(|t| t > after)
+}
+
+/// 最後に鳴った時刻と保存した時刻の遅い方。これより前の回は鳴らす対象にしない。
+/// どちらも記録がなければ `None`
+pub fn checked_since<Tz: TimeZone>(alarm: &WallClockAlarm, tz: &Tz) -> Option<DateTime<Tz>> {
+    [alarm.last_fired_unix_ms, alarm.armed_unix_ms]
+        .into_iter()
+        .flatten()
+        .filter_map(|ms| tz.timestamp_millis_opt(ms).single())
+        .max()
+}
+
+/// `last_checked` から `now` までの間に鳴るべき発火時刻を返す。
+/// 複数回分を過ぎていた場合は最後の 1 回だけを返す
+pub fn due_occurrence<Tz: TimeZone>(
+    alarm: &WallClockAlarm,
+    last_checked: &DateTime<Tz>,
+    now: &DateTime<Tz>,
+) -> Option<DateTime<Tz>> {
+    if !alarm.enabled {
+        return None;
+    }
+    // 既に鳴らした回と保存前の回は対象外（時計が巻き戻った場合の二重発火防止も兼ねる）
+    let after = match checked_since(alarm, &last_checked.timezone()) {
+        Some(since) if since > *last_checked => since,
+        _ => last_checked.clone(),
+    };
+    let mut due = next_occurrence(alarm, &after).filter(|t| t <= now)?;
+    while let Some(next) = next_occurrence(alarm, &due).filter(|t| t <= now) {
+        due = next;
+    }
+    Some(due)
+}
+
+/// 保存するアラーム一覧
+#[derive(Debug, Default)]
+pub struct AlarmBook {
+    pub alarms: Vec<WallClockAlarm>,
+}
+
+impl AlarmBook {
+    /// `last_checked` から `now` までに鳴るべきアラームを発火済みにし、鳴らすものを返す。
+    /// `last_checked` が `None` なら起動直後とみなし、各アラームの `checked_since` から確認する
+    /// （記録のないアラームは `now` から）。猶予を超えて遅れた回は `policy` に従って捨てる。
+    /// 2 番目の戻り値は一覧を保存し直す必要があるか
+    pub fn take_due<Tz: TimeZone>(
+        &mut self,
+        last_checked: Option<&DateTime<Tz>>,
+        now: &DateTime<Tz>,
+        policy: MissedAlarmPolicy,
+    ) -> (Vec<WallClockAlarm>, bool) {
+        let mut fired = Vec::new();
+        let mut changed = false;
+        for alarm in &mut self.alarms {
+            let from = match last_checked {
+                Some(checked) => checked.clone(),
+                None => checked_since(alarm, &now.timezone()).unwrap_or_else(|| now.clone()),
+            };
+            let Some(due) = due_occurrence(alarm, &from, now) else {
+                continue;
+            };
+            alarm.last_fired_unix_ms = Some(due.timestamp_millis());
+            if !alarm.repeats() {
+                alarm.enabled = false;
+            }
+            changed = true;
+            let late = (now.clone() - due).num_seconds();
+            if late > MISSED_ALARM_GRACE_SECS && policy == MissedAlarmPolicy::Drop {
+                println!(
+                    "DEBUG: Dropping alarm {} missed by {}s (system asleep or app closed?)",
+                    alarm.id, late
+                );
+                continue;
+            }
+            fired.push(alarm.clone());
+        }
+        (fired, changed)
+    }
+
+    fn next_id(&self) -> String {
+        let n = self
+            .alarms
+            .iter()
+            .filter_map(|a| a.id.strip_prefix("alarm-"))
+            .filter_map(|n| n.parse::<u64>().ok())
+            .max()
+            .unwrap_or(0);
+        format!("alarm-{}", n + 1)
+    }
+}
+
+#[derive(Default)]
+pub struct AlarmState(pub Mutex<AlarmBook>);
+
+fn write_alarms(book: &AlarmBook) -> Result<(), Box<dyn std::error::Error>> {
+    let json = serde_json::to_string_pretty(&book.alarms)?;
+    fs::write(crate::get_alarms_path(), json)?;
+    Ok(())
+}
+
+/// `alarms.json` を読み込んで managed state に反映する
+pub fn load_alarms(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let path = crate::get_alarms_path();
+    if !path.exists() {
+        return Ok(());
+    }
+    let json = fs::read_to_string(path)?;
+    let alarms: Vec<WallClockAlarm> = serde_json::from_str(&json)?;
+    println!("DEBUG: Loaded {} wall-clock alarms", alarms.len());
+    app.state::<AlarmState>()
+        .0
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .alarms = alarms;
+    Ok(())
+}
+
+/// 一覧を保存して `alarms-changed` を emit する
+fn commit_alarms(app: &AppHandle, book: &AlarmBook) {
+    if let Err(e) = write_alarms(book) {
+        println!("DEBUG: Failed to save alarms: {}", e);
+    }
+    if let Err(e) = app.emit(ALARMS_CHANGED_EVENT, &book.alarms) {
+        println!("DEBUG: Failed to emit {}: {}", ALARMS_CHANGED_EVENT, e);
+    }
+}
+
+fn fire_alarm(app: &AppHandle, alarm: &WallClockAlarm) {
+    println!(
+        "DEBUG: Wall-clock alarm fired: {} ({})",
+        alarm.label, alarm.id
+    );
+    if let Err(e) = app.emit(ALARM_FIRED_EVENT, alarm) {
+        println!("DEBUG: Failed to emit {}: {}", ALARM_FIRED_EVENT, e);
+    }
+    crate::escalation::on_alarm_fired(app, alarm);
+}
+
+/// 壁時計を監視してアラームを鳴らすスレッドを起動する。
+/// 最初の確認は待たずに行い、アプリを閉じていた間に過ぎた回を拾う
+pub fn spawn_scheduler(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut last_checked: Option<DateTime<Local>> = None;
+        loop {
+            let now = Local::now();
+            if last_checked.is_some_and(|checked| now < checked) {
+                // 時計が巻き戻った。last_fired で二重発火は防げるので起点だけ合わせる
+                println!("DEBUG: Wall clock moved backwards; rescheduling alarms");
+                last_checked = Some(now);
+                std::thread::sleep(SCHEDULER_POLL);
+                continue;
+            }
+
+            let policy = crate::resync::missed_alarm_policy(&app.state::<ResyncState>());
+            let fired = {
+                let state = app.state::<AlarmState>();
+                let mut book = state.0.lock().unwrap_or_else(|e| e.into_inner());
+                let (fired, changed) = book.take_due(last_checked.as_ref(), &now, policy);
+                if changed {
+                    commit_alarms(&app, &book);
+                }
+                fired
+            };
+            for alarm in &fired {
+                fire_alarm(&app, alarm);
+            }
+            last_checked = Some(now);
+            std::thread::sleep(SCHEDULER_POLL);
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn alarm_list(state: State<'_, AlarmState>) -> Result<Vec<WallClockAlarm>, String> {
+    let book = state
+        .0
+        .lock()
+        .map_err(|e| format!("Alarm state lock poisoned: {}", e))?;
+    Ok(book.alarms.clone())
+}
+
+/// アラームを追加・更新する。ID が空なら新規作成として採番する
+#[tauri::command]
+pub async fn alarm_save(
+    app: AppHandle,
+    state: State<'_, AlarmState>,
+    mut alarm: WallClockAlarm,
+) -> Result<WallClockAlarm, String> {
+    alarm.validate()?;
+    let mut book = state
+        .0
+        .lock()
+        .map_err(|e| format!("Alarm state lock poisoned: {}", e))?;
+    // 時刻を変えたら前回の発火記録は無効にし、保存した時刻から数え直す
+    alarm.last_fired_unix_ms = None;
+    alarm.armed_unix_ms = Some(Local::now().timestamp_millis());
+    if alarm.id.is_empty() {
+        alarm.id = book.next_id();
+        book.alarms.push(alarm.clone());
+    } else {
+        let existing = book
+            .alarms
+            .iter_mut()
+            .find(|a| a.id == alarm.id)
+            .ok_or_else(|| format!("Alarm not found: {}", alarm.id))?;
+        *existing = alarm.clone();
+    }
+    commit_alarms(&app, &book);
+    Ok(alarm)
+}
+
+#[tauri::command]
+pub async fn alarm_delete(
+    app: AppHandle,
+    state: State<'_, AlarmState>,
+    id: String,
+) -> Result<(), String> {
+    let mut book = state
+        .0
+        .lock()
+        .map_err(|e| format!("Alarm state lock poisoned: {}", e))?;
+    let before = book.alarms.len();
+    book.alarms.retain(|a| a.id != id);
+    if book.alarms.len() == before {
+        return Err(format!("Alarm not found: {}", id));
+    }
+    commit_alarms(&app, &book);
+    Ok(())
+}
+
+/// 次に鳴る時刻（UNIX エポックからのミリ秒）。無効なアラームや該当なしは `None`
+#[tauri::command]
+pub async fn alarm_next_fire(
+    state: State<'_, AlarmState>,
+    id: String,
+) -> Result<Option<i64>, String> {
+    let book = state
+        .0
+        .lock()
+        .map_err(|e| format!("Alarm state lock poisoned: {}", e))?;
+    let alarm = book
+        .alarms
+        .iter()
+        .find(|a| a.id == id)
+        .ok_or_else(|| format!("Alarm not found: {}", id))?;
+    if !alarm.enabled {
+        return Ok(None);
+    }
+    Ok(next_occurrence(alarm, &Local::now()).map(|t| t.timestamp_millis()))
+}
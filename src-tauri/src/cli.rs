@@ -0,0 +1,167 @@
+//! `lightning-timer-cli`: 起動中の Lightning Timer をシェルから操作する。
+//!
+//! 例: `make test && lightning-timer-cli start 10m`、`lightning-timer-cli status --json`
+//! アプリが起動していなければ起動してから操作する。
+
+mod cli_args;
+// GUI と共有するモジュールのうち CLI で使わない部分があるため
+#[allow(dead_code)]
+mod control;
+#[allow(dead_code)]
+mod duration;
+
+use cli_args::CliCommand;
+use control::{ControlRequest, ControlResponse};
+use duration::ClockTime;
+use std::process::ExitCode;
+
+const CLI_USAGE: &str = "\
+Usage: lightning-timer-cli <command> [options]
+
+Commands:
+  start [DURATION]   Start the timer, optionally setting it first (5m, 1h30m, 90s, 25:00)
+  pause              Pause the timer
+  reset              Reset the timer
+  status [--json]    Show the timer state
+  preset <NAME>      Start a preset by name or id
+  help               Show this help
+
+Options:
+  --id <TIMER_ID>    Target a named timer instead of the default one
+  --json             Print the timer state as JSON
+
+Starts Lightning Timer if it is not running.";
+
+/// 起動したアプリがソケットを作るまで待つ時間の上限
+const LAUNCH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
+mod client {
+    use std::io::ErrorKind;
+    use std::process::{Command, Stdio};
+    use std::time::{Duration, Instant};
+
+    use crate::control::{self, ControlRequest, ControlResponse, ControlStream};
+
+    #[cfg(target_os = "macos")]
+    const BUNDLE_ID: &str = "com.pemomomo.lightning-timer";
+
+    /// 同じディレクトリの GUI を起動する。macOS ではアプリバンドルも探す
+    fn launch_gui() -> Result<(), String> {
+        let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+        let gui = exe.with_file_name(format!("lightning-timer{}", std::env::consts::EXE_SUFFIX));
+        if gui.exists() {
+            return Command::new(&gui)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .map(|_| ())
+                .map_err(|e| format!("Failed to launch {}: {}", gui.display(), e));
+        }
+        #[cfg(target_os = "macos")]
+        {
+            let status = Command::new("open")
+                .args(["-g", "-b", BUNDLE_ID])
+                .status()
+                .map_err(|e| format!("Failed to launch Lightning Timer: {}", e))?;
+            if status.success() {
+                return Ok(());
+            }
+        }
+        Err("Lightning Timer is not running and could not be found to launch".to_string())
+    }
+
+    /// 起動中のアプリに接続する。いなければ起動して待つ
+    fn connect(timeout: Duration) -> Result<ControlStream, String> {
+        match control::connect() {
+            Ok(stream) => return Ok(stream),
+            Err(e) if matches!(e.kind(), ErrorKind::NotFound | ErrorKind::ConnectionRefused) => {}
+            Err(e) => {
+                return Err(format!(
+                    "Failed to connect to {}: {}",
+                    control::endpoint_path().display(),
+                    e
+                ))
+            }
+        }
+
+        launch_gui()?;
+        let started = Instant::now();
+        while started.elapsed() < timeout {
+            std::thread::sleep(Duration::from_millis(100));
+            if let Ok(stream) = control::connect() {
+                return Ok(stream);
+            }
+        }
+        Err("Timed out waiting for Lightning Timer to start".to_string())
+    }
+
+    pub fn send(request: &ControlRequest, timeout: Duration) -> Result<ControlResponse, String> {
+        control::send_request(connect(timeout)?, request)
+    }
+}
+
+fn send(request: &ControlRequest) -> Result<ControlResponse, String> {
+    client::send(request, LAUNCH_TIMEOUT)
+}
+
+/// "Running 04:59 (Timer)" のような 1 行の表示
+fn describe(timer: &serde_json::Value) -> String {
+    let field = |key: &str| timer.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+    let status = timer.get("status").and_then(|v| v.as_str()).unwrap_or("");
+    let name = timer.get("name").and_then(|v| v.as_str()).unwrap_or("");
+    let display = ClockTime::normalize(field("hours"), field("minutes"), field("seconds"));
+    let (label, display) = match status {
+        "running" => ("Running", display.to_string()),
+        "paused" => ("Paused", display.to_string()),
+        "overtime" => ("Overtime", format!("-{}", display)),
+        "finished" => ("Time up", display.to_string()),
+        _ => ("Idle", display.to_string()),
+    };
+    format!("{} {} ({})", label, display, name)
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (request, json) = match cli_args::parse_cli_args(&args) {
+        Ok(CliCommand::Help) => {
+            println!("{}", CLI_USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Ok(CliCommand::Send { request, json }) => (request, json),
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, CLI_USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let response = match send(&request) {
+        Ok(response) => response,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    match response {
+        ControlResponse {
+            error: Some(error), ..
+        } => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+        ControlResponse {
+            timer: Some(timer), ..
+        } => {
+            if json {
+                println!("{}", timer);
+            } else {
+                println!("{}", describe(&timer));
+            }
+            ExitCode::SUCCESS
+        }
+        ControlResponse { .. } => {
+            eprintln!("error: Empty response from Lightning Timer");
+            ExitCode::FAILURE
+        }
+    }
+}
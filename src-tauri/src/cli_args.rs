@@ -0,0 +1,82 @@
+//! `lightning-timer-cli` の引数の解釈。
+//!
+//! CLI のバイナリが使い、GUI 側では `tests.rs` から確かめるためだけに組み込む。
+
+use crate::control::ControlRequest;
+use crate::duration::parse_duration_spec;
+
+/// 解釈した CLI の引数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliCommand {
+    Help,
+    Send {
+        request: ControlRequest,
+        /// 結果を JSON で出力する
+        json: bool,
+    },
+}
+
+/// `lightning-timer-cli` の引数（プログラム名を除く）を解釈する
+pub fn parse_cli_args(args: &[String]) -> Result<CliCommand, String> {
+    let mut positional = Vec::new();
+    let mut id = None;
+    let mut json = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--id" => {
+                let value = iter.next().ok_or("--id requires a timer id")?;
+                id = Some(value.clone());
+            }
+            "-h" | "--help" => return Ok(CliCommand::Help),
+            flag if flag.starts_with("--") => return Err(format!("Unknown option: {}", flag)),
+            _ => positional.push(arg.as_str()),
+        }
+    }
+
+    let Some((&command, rest)) = positional.split_first() else {
+        return Ok(CliCommand::Help);
+    };
+    let no_args = |request: ControlRequest| {
+        if rest.is_empty() {
+            Ok(request)
+        } else {
+            Err(format!(
+                "Unexpected argument for {}: {}",
+                command,
+                rest.join(" ")
+            ))
+        }
+    };
+    let request = match command {
+        "help" => return Ok(CliCommand::Help),
+        "start" => match rest {
+            [] => ControlRequest::Start {
+                id,
+                duration_secs: None,
+            },
+            // "1h 30m" のように分けて書いても受け付ける
+            spec => ControlRequest::Start {
+                id,
+                duration_secs: Some(parse_duration_spec(&spec.join(" "))?),
+            },
+        },
+        "pause" => no_args(ControlRequest::Pause { id })?,
+        "reset" => no_args(ControlRequest::Reset { id })?,
+        "status" => no_args(ControlRequest::Status { id })?,
+        "preset" => {
+            if rest.is_empty() {
+                return Err("preset requires a preset name".to_string());
+            }
+            if id.is_some() {
+                return Err("--id cannot be used with preset".to_string());
+            }
+            ControlRequest::Preset {
+                key: rest.join(" "),
+            }
+        }
+        other => return Err(format!("Unknown command: {}", other)),
+    };
+    Ok(CliCommand::Send { request, json })
+}
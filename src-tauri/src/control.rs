@@ -0,0 +1,168 @@
+//! 起動中のアプリと `lightning-timer-cli` がやり取りするローカルソケットの取り決め。
+//!
+//! アプリデータディレクトリの `control.sock`（Unix ドメインソケット）に
+//! 1 行 1 JSON のリクエストを書き、1 行の JSON レスポンスを受け取る。
+//! Unix ドメインソケットのない Windows では 127.0.0.1 の TCP で待ち受け、
+//! ポートと接続用のトークンを `control.port` に書き出す。接続したらまずトークンを 1 行送る。
+//! GUI と CLI の両方のバイナリから使うので、tauri には依存させない。
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// アプリデータディレクトリ名（`get_app_data_dir` と CLI で共有する）
+pub const APP_DATA_DIR_NAME: &str = "lightning-timer";
+
+#[cfg(unix)]
+const SOCKET_FILE_NAME: &str = "control.sock";
+
+#[cfg(not(unix))]
+const PORT_FILE_NAME: &str = "control.port";
+
+/// 起動中のアプリへの接続
+#[cfg(unix)]
+pub type ControlStream = std::os::unix::net::UnixStream;
+#[cfg(not(unix))]
+pub type ControlStream = std::net::TcpStream;
+
+/// アプリデータディレクトリ。作成はしない
+pub fn app_data_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(APP_DATA_DIR_NAME)
+}
+
+#[cfg(unix)]
+pub fn socket_path() -> PathBuf {
+    app_data_dir().join(SOCKET_FILE_NAME)
+}
+
+/// TCP で待ち受けるときのポートとトークンを書くファイル
+#[cfg(not(unix))]
+pub fn port_path() -> PathBuf {
+    app_data_dir().join(PORT_FILE_NAME)
+}
+
+/// 接続先の表示（エラーメッセージ用）
+pub fn endpoint_path() -> PathBuf {
+    #[cfg(unix)]
+    return socket_path();
+    #[cfg(not(unix))]
+    return port_path();
+}
+
+/// `control.port` の中身（"<ポート> <トークン>"）。Unix では使わない
+#[cfg_attr(unix, allow(dead_code))]
+pub fn format_endpoint(port: u16, token: &str) -> String {
+    format!("{} {}\n", port, token)
+}
+
+#[cfg_attr(unix, allow(dead_code))]
+pub fn parse_endpoint(text: &str) -> Option<(u16, String)> {
+    let (port, token) = text.trim().split_once(' ')?;
+    let token = token.trim();
+    if token.is_empty() {
+        return None;
+    }
+    Some((port.parse().ok()?, token.to_string()))
+}
+
+/// CLI から送る操作。`id` を省略すると既定タイマーが対象になる
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(
+    tag = "command",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum ControlRequest {
+    /// `duration_secs` があればその時間を設定してから開始する。なければ再開する
+    Start {
+        #[serde(default)]
+        id: Option<String>,
+        #[serde(default)]
+        duration_secs: Option<u64>,
+    },
+    Pause {
+        #[serde(default)]
+        id: Option<String>,
+    },
+    Reset {
+        #[serde(default)]
+        id: Option<String>,
+    },
+    Status {
+        #[serde(default)]
+        id: Option<String>,
+    },
+    /// ID または名前でプリセットを起動する
+    Preset { key: String },
+    /// 2 つ目の起動から、コマンドライン引数を渡して main ウィンドウを前面に出す
+    Activate {
+        #[serde(default)]
+        args: Vec<String>,
+    },
+}
+
+/// 操作の結果。成功時は `timer` にタイマーの状態（`NamedTimerSnapshot` の JSON）が入る
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ControlResponse {
+    #[serde(default)]
+    pub timer: Option<serde_json::Value>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl ControlResponse {
+    pub fn from_result<T: Serialize>(result: Result<T, String>) -> Self {
+        match result.and_then(|timer| serde_json::to_value(timer).map_err(|e| e.to_string())) {
+            Ok(timer) => Self {
+                timer: Some(timer),
+                error: None,
+            },
+            Err(error) => Self {
+                timer: None,
+                error: Some(error),
+            },
+        }
+    }
+}
+
+/// 起動中のアプリのソケットに接続する
+#[cfg(unix)]
+pub fn connect() -> std::io::Result<ControlStream> {
+    std::os::unix::net::UnixStream::connect(socket_path())
+}
+
+/// `control.port` のポートに接続してトークンを送る。
+/// ファイルがなければ（起動していなければ）`NotFound`
+#[cfg(not(unix))]
+pub fn connect() -> std::io::Result<ControlStream> {
+    use std::io::{Error, ErrorKind, Write};
+
+    let text = std::fs::read_to_string(port_path())?;
+    let (port, token) = parse_endpoint(&text)
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "Invalid control port file"))?;
+    let stream = std::net::TcpStream::connect(("127.0.0.1", port))?;
+    (&stream).write_all(format!("{}\n", token).as_bytes())?;
+    Ok(stream)
+}
+
+/// 接続済みのソケットで 1 リクエストを送り、レスポンスを受け取る
+pub fn send_request(
+    stream: ControlStream,
+    request: &ControlRequest,
+) -> Result<ControlResponse, String> {
+    use std::io::{BufRead, BufReader, Write};
+
+    let mut json = serde_json::to_string(request).map_err(|e| e.to_string())?;
+    json.push('\n');
+    (&stream)
+        .write_all(json.as_bytes())
+        .map_err(|e| format!("Failed to send request: {}", e))?;
+
+    let mut line = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut line)
+        .map_err(|e| format!("Failed to read response: {}", e))?;
+    serde_json::from_str(&line).map_err(|e| format!("Invalid response: {}", e))
+}
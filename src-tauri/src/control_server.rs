@@ -0,0 +1,242 @@
+//! `lightning-timer-cli` や 2 つ目の起動からの操作を受け付けるローカルソケット。
+//!
+//! 取り決めは `control` モジュールにある。接続ごとに 1 リクエストを読み、
+//! 1 レスポンスを返して閉じる。Unix ではドメインソケット、Windows では 127.0.0.1 の TCP で待ち受け、
+//! ソケットファイル・ポートファイルは終了時に削除する。
+
+use std::io::{BufRead, Write};
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::control::{ControlRequest, ControlResponse};
+use crate::timer::{self, NamedTimerSnapshot, TimerRegistryState};
+
+fn run_request(app: &AppHandle, request: ControlRequest) -> Result<NamedTimerSnapshot, String> {
+    use tauri::Manager;
+
+    let state = app.state::<TimerRegistryState>();
+    match request {
+        ControlRequest::Start {
+            id: None,
+            duration_secs: Some(secs),
+        } => crate::preset::start_default_timer(app, secs, None),
+        ControlRequest::Start { id, duration_secs } => {
+            timer::update_timer(app, &state, id, |t, now| {
+                if let Some(secs) = duration_secs {
+                    t.engine.set(std::time::Duration::from_secs(secs));
+                }
+                t.engine.start(now);
+            })
+        }
+        ControlRequest::Pause { id } => timer::update_timer(app, &state, id, |t, now| {
+            t.engine.pause(now);
+        }),
+        ControlRequest::Reset { id } => {
+            timer::update_timer(app, &state, id, |t, _| t.engine.reset())
+        }
+        ControlRequest::Status { id } => timer::timer_snapshot(app, id),
+        ControlRequest::Preset { key } => crate::preset::launch_preset(app, &key),
+        ControlRequest::Activate { args } => {
+            crate::instance::activate(app, &args)?;
+            timer::timer_snapshot(app, None)
+        }
+    }
+}
+
+/// リクエストを実行してレスポンスを作る
+pub fn handle_request(app: &AppHandle, request: ControlRequest) -> ControlResponse {
+    println!("DEBUG: Control request: {:?}", request);
+    ControlResponse::from_result(run_request(app, request))
+}
+
+/// 1 接続あたりの読み書きの待ち時間の上限
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 1 行のリクエストを読み、1 行のレスポンスを返す
+fn serve(app: &AppHandle, mut reader: impl BufRead, mut writer: impl Write) -> std::io::Result<()> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let response = match serde_json::from_str::<ControlRequest>(&line) {
+        Ok(request) => handle_request(app, request),
+        Err(e) => ControlResponse {
+            timer: None,
+            error: Some(format!("Invalid request: {}", e)),
+        },
+    };
+    let mut json = serde_json::to_string(&response).map_err(std::io::Error::other)?;
+    json.push('\n');
+    writer.write_all(json.as_bytes())
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::io::BufReader;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use tauri::AppHandle;
+
+    use super::IO_TIMEOUT;
+    use crate::control;
+
+    fn serve(app: &AppHandle, stream: UnixStream) -> std::io::Result<()> {
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+        super::serve(app, BufReader::new(&stream), &stream)
+    }
+
+    pub fn spawn_listener(app: AppHandle) {
+        let path = control::socket_path();
+        if UnixStream::connect(&path).is_ok() {
+            println!(
+                "DEBUG: Control socket already in use by another instance: {}",
+                path.display()
+            );
+            return;
+        }
+        // 前回異常終了したときのソケットファイルが残っていれば作り直す
+        if path.exists() {
+            if let Err(e) = std::fs::remove_file(&path) {
+                println!("DEBUG: Failed to remove stale control socket: {}", e);
+            }
+        }
+        let listener = match UnixListener::bind(&path) {
+            Ok(listener) => listener,
+            Err(e) => {
+                println!("DEBUG: Failed to bind control socket: {}", e);
+                return;
+            }
+        };
+        println!("DEBUG: Control socket listening on {}", path.display());
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        let app = app.clone();
+                        std::thread::spawn(move || {
+                            if let Err(e) = serve(&app, stream) {
+                                println!("DEBUG: Control connection failed: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => println!("DEBUG: Control socket accept failed: {}", e),
+                }
+            }
+        });
+    }
+
+    pub fn remove_endpoint() {
+        let path = control::socket_path();
+        if path.exists() {
+            if let Err(e) = std::fs::remove_file(&path) {
+                println!("DEBUG: Failed to remove control socket: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod tcp {
+    use std::io::{BufRead, BufReader, Error, ErrorKind};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::Arc;
+    use tauri::AppHandle;
+
+    use super::IO_TIMEOUT;
+    use crate::control;
+
+    /// 他のユーザーのプロセスなどから操作されないよう、接続時に確かめる推測できない値
+    /// OS の乱数から 32 バイトを取り、16 進文字列にする
+    fn new_token() -> Result<String, getrandom::Error> {
+        let mut bytes = [0u8; 32];
+        getrandom::fill(&mut bytes)?;
+        Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+
+    fn serve(app: &AppHandle, stream: TcpStream, token: &str) -> std::io::Result<()> {
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+        let mut reader = BufReader::new(&stream);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        if line.trim() != token {
+            return Err(Error::new(
+                ErrorKind::PermissionDenied,
+                "Invalid control token",
+            ));
+        }
+        super::serve(app, reader, &stream)
+    }
+
+    pub fn spawn_listener(app: AppHandle) {
+        // ポートは OS に選ばせ、ファイルで知らせる
+        let listener = match TcpListener::bind(("127.0.0.1", 0)) {
+            Ok(listener) => listener,
+            Err(e) => {
+                println!("DEBUG: Failed to bind control port: {}", e);
+                return;
+            }
+        };
+        let port = match listener.local_addr() {
+            Ok(addr) => addr.port(),
+            Err(e) => {
+                println!("DEBUG: Failed to read control port: {}", e);
+                return;
+            }
+        };
+        let token: Arc<str> = match new_token() {
+            Ok(token) => token.into(),
+            Err(e) => {
+                println!("DEBUG: Failed to generate control token: {}", e);
+                return;
+            }
+        };
+        let path = control::port_path();
+        if let Err(e) = std::fs::write(&path, control::format_endpoint(port, &token)) {
+            println!("DEBUG: Failed to write control port file: {}", e);
+            return;
+        }
+        println!("DEBUG: Control port listening on 127.0.0.1:{}", port);
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        let app = app.clone();
+                        let token = Arc::clone(&token);
+                        std::thread::spawn(move || {
+                            if let Err(e) = serve(&app, stream, &token) {
+                                println!("DEBUG: Control connection failed: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => println!("DEBUG: Control port accept failed: {}", e),
+                }
+            }
+        });
+    }
+
+    pub fn remove_endpoint() {
+        let path = control::port_path();
+        if path.exists() {
+            if let Err(e) = std::fs::remove_file(&path) {
+                println!("DEBUG: Failed to remove control port file: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+use unix as transport;
+
+#[cfg(not(unix))]
+use tcp as transport;
+
+/// 待ち受けを始める
+pub fn spawn_listener(app: AppHandle) {
+    transport::spawn_listener(app);
+}
+
+/// 終了時にソケットファイル・ポートファイルを削除する
+pub fn shutdown() {
+    transport::remove_endpoint();
+}
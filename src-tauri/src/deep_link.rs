@@ -0,0 +1,215 @@
+//! `lightning-timer://` リンク。Wiki やカレンダーの招待、チャットからワンクリックでタイマーを操作する。
+//!
+//! - `lightning-timer://start?d=5m&label=Standup` … 時間を設定して開始（`label` は名前付きタイマー。なければ作る）
+//! - `lightning-timer://start` … 既定タイマーを開始・再開
+//! - `lightning-timer://pause`、`lightning-timer://reset`（`label` で名前付きタイマーを指定できる）
+//! - `lightning-timer://preset?name=tea` … プリセットを起動
+//!
+//! Windows・Linux ではリンクがコマンドライン引数として渡され、起動済みなら `instance` が
+//! 制御ソケット（Windows では制御ポート）で先に起動したプロセスへ転送する。
+//! macOS では `RunEvent::Opened` で届く。どちらも `open` に集約する。
+
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Url};
+
+use crate::duration::parse_duration_spec;
+use crate::timer::{self, NamedTimerSnapshot, TimerRegistryState};
+
+pub const DEEP_LINK_SCHEME: &str = "lightning-timer";
+
+/// 受け付けるリンクの長さの上限
+const MAX_LINK_LEN: usize = 2048;
+/// `label` の長さの上限（文字数）
+const MAX_LABEL_CHARS: usize = 64;
+
+/// 解釈したリンク
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeepLink {
+    /// `duration_secs` があれば設定してから開始する。なければ再開する
+    Start {
+        duration_secs: Option<u64>,
+        label: Option<String>,
+    },
+    Pause {
+        label: Option<String>,
+    },
+    Reset {
+        label: Option<String>,
+    },
+    /// ID または名前でプリセットを起動する
+    Preset {
+        name: String,
+    },
+}
+
+/// `lightning-timer:` で始まる引数か（大文字小文字を区別しない）
+pub fn is_deep_link(arg: &str) -> bool {
+    arg.split_once(':')
+        .is_some_and(|(scheme, _)| scheme.eq_ignore_ascii_case(DEEP_LINK_SCHEME))
+}
+
+fn parse_label(value: &str) -> Result<String, String> {
+    let label = value.trim();
+    if label.is_empty() {
+        return Err("\"label\" must not be empty".to_string());
+    }
+    if label.chars().count() > MAX_LABEL_CHARS {
+        return Err(format!(
+            "\"label\" must be at most {} characters",
+            MAX_LABEL_CHARS
+        ));
+    }
+    if label.chars().any(char::is_control) {
+        return Err("\"label\" must not contain control characters".to_string());
+    }
+    Ok(label.to_string())
+}
+
+/// リンクを解釈・検証する。未知の操作・パラメータや重複、操作に合わないパラメータは誤りにする
+pub fn parse_deep_link(link: &str) -> Result<DeepLink, String> {
+    let link = link.trim();
+    if link.len() > MAX_LINK_LEN {
+        return Err(format!("Link is longer than {} bytes", MAX_LINK_LEN));
+    }
+    let url = Url::parse(link).map_err(|e| format!("Invalid link: {}", e))?;
+    if url.scheme() != DEEP_LINK_SCHEME {
+        return Err(format!(
+            "Unsupported scheme: {} (expected {}://)",
+            url.scheme(),
+            DEEP_LINK_SCHEME
+        ));
+    }
+    if !url.username().is_empty() || url.password().is_some() || url.port().is_some() {
+        return Err("Invalid link: unexpected user or port".to_string());
+    }
+    // `lightning-timer://start` が基本形。末尾の `/` と `lightning-timer:start` も受け付ける
+    let action = match url.host_str() {
+        Some(host) if url.path().is_empty() || url.path() == "/" => host.to_string(),
+        Some(_) => return Err(format!("Unexpected path: {}", url.path())),
+        None => url.path().trim_end_matches('/').to_string(),
+    };
+    if action.is_empty() {
+        return Err("Missing action (start, pause, reset or preset)".to_string());
+    }
+
+    let mut duration_secs = None;
+    let mut label = None;
+    let mut name = None;
+    for (key, value) in url.query_pairs() {
+        let slot = match key.as_ref() {
+            "d" | "duration" => {
+                if duration_secs.is_some() {
+                    return Err("Duplicate parameter: d".to_string());
+                }
+                duration_secs =
+                    Some(parse_duration_spec(&value).map_err(|e| format!("\"d\": {}", e))?);
+                continue;
+            }
+            "label" => &mut label,
+            "name" => &mut name,
+            other => return Err(format!("Unknown parameter: {}", other)),
+        };
+        if slot.is_some() {
+            return Err(format!("Duplicate parameter: {}", key));
+        }
+        *slot = Some(value.into_owned());
+    }
+    let label = label.as_deref().map(parse_label).transpose()?;
+
+    let action = action.to_ascii_lowercase();
+    let unexpected = |param: &str| format!("\"{}\" does not take \"{}\"", action, param);
+    match action.as_str() {
+        "start" => {
+            if name.is_some() {
+                return Err(unexpected("name"));
+            }
+            Ok(DeepLink::Start {
+                duration_secs,
+                label,
+            })
+        }
+        "pause" | "reset" => {
+            if duration_secs.is_some() {
+                return Err(unexpected("d"));
+            }
+            if name.is_some() {
+                return Err(unexpected("name"));
+            }
+            Ok(if action == "pause" {
+                DeepLink::Pause { label }
+            } else {
+                DeepLink::Reset { label }
+            })
+        }
+        "preset" => {
+            if duration_secs.is_some() {
+                return Err(unexpected("d"));
+            }
+            if label.is_some() {
+                return Err(unexpected("label"));
+            }
+            let name = name
+                .map(|n| n.trim().to_string())
+                .filter(|n| !n.is_empty())
+                .ok_or_else(|| "\"preset\" requires \"name\"".to_string())?;
+            Ok(DeepLink::Preset { name })
+        }
+        other => Err(format!("Unknown action: {}", other)),
+    }
+}
+
+/// `label` の名前付きタイマーを操作する。`label` がなければ既定タイマー
+fn update_labeled<F>(
+    app: &AppHandle,
+    label: Option<&str>,
+    create: Option<Duration>,
+    f: F,
+) -> Result<NamedTimerSnapshot, String>
+where
+    F: FnOnce(&mut timer::NamedTimer, std::time::Instant),
+{
+    let id = label
+        .map(|label| timer::timer_id_by_name(app, label, create))
+        .transpose()?;
+    timer::update_timer(app, &app.state::<TimerRegistryState>(), id, f)
+}
+
+/// リンクを解釈してタイマーを操作する
+pub fn open(app: &AppHandle, link: &str) -> Result<NamedTimerSnapshot, String> {
+    println!("DEBUG: Opening deep link: {}", link);
+    match parse_deep_link(link)? {
+        DeepLink::Start {
+            duration_secs: Some(secs),
+            label: None,
+        } => crate::preset::start_default_timer(app, secs, None),
+        DeepLink::Start {
+            duration_secs,
+            label,
+        } => {
+            let duration = duration_secs.map(Duration::from_secs);
+            update_labeled(app, label.as_deref(), duration, |t, now| {
+                if let Some(duration) = duration {
+                    t.engine.set(duration);
+                }
+                t.engine.start(now);
+            })
+        }
+        DeepLink::Pause { label } => update_labeled(app, label.as_deref(), None, |t, now| {
+            t.engine.pause(now);
+        }),
+        DeepLink::Reset { label } => {
+            update_labeled(app, label.as_deref(), None, |t, _| t.engine.reset())
+        }
+        DeepLink::Preset { name } => crate::preset::launch_preset(app, &name),
+    }
+}
+
+/// macOS で OS から渡されたリンクを開く
+#[cfg(target_os = "macos")]
+pub fn open_urls(app: &AppHandle, urls: &[Url]) {
+    for url in urls {
+        if let Err(e) = open(app, url.as_str()) {
+            println!("DEBUG: Failed to open deep link {}: {}", url, e);
+        }
+    }
+}
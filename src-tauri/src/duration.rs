@@ -0,0 +1,127 @@
+//! 時・分・秒で扱うタイマーの時間表現。
+//!
+//! 入力の桁あふれ（0:90 → 1:30 など）を正規化し、
+//! 1 時間以上なら `H:MM:SS`、未満なら `MM:SS` で表示する。
+//! CLI やディープリンクの "5m" "1h30m" "25:00" のような指定は `parse_duration_spec` で読む。
+
+use serde::Serialize;
+use std::fmt;
+
+/// 設定できる最大の時間（99:59:59）
+pub const MAX_DURATION_SECS: u64 = 99 * 3600 + 59 * 60 + 59;
+
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockTime {
+    pub hours: u64,
+    /// 0〜59
+    pub minutes: u64,
+    /// 0〜59
+    pub seconds: u64,
+}
+
+impl ClockTime {
+    /// 秒数から時・分・秒に分解する（上限で丸める）
+    pub fn from_secs(total_secs: u64) -> Self {
+        let total = total_secs.min(MAX_DURATION_SECS);
+        Self {
+            hours: total / 3600,
+            minutes: (total % 3600) / 60,
+            seconds: total % 60,
+        }
+    }
+
+    /// 桁あふれを含む入力を正規化する（例: 0 時間 0 分 90 秒 → 0:01:30）
+    pub fn normalize(hours: u64, minutes: u64, seconds: u64) -> Self {
+        let total = hours
+            .saturating_mul(3600)
+            .saturating_add(minutes.saturating_mul(60))
+            .saturating_add(seconds);
+        Self::from_secs(total)
+    }
+
+    pub fn total_secs(&self) -> u64 {
+        self.hours * 3600 + self.minutes * 60 + self.seconds
+    }
+}
+
+impl fmt::Display for ClockTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.hours > 0 {
+            write!(f, "{}:{:02}:{:02}", self.hours, self.minutes, self.seconds)
+        } else {
+            write!(f, "{:02}:{:02}", self.minutes, self.seconds)
+        }
+    }
+}
+
+/// "5m" "1h30m" "90s" "25:00" "1:05:00" のような時間指定を秒数にする。
+/// 単位のない数字だけの指定は分として扱う（"25" → 25 分）。
+/// `:` 区切りの桁あふれは正規化する（"0:90" → 1:30）
+pub fn parse_duration_spec(spec: &str) -> Result<u64, String> {
+    let spec = spec.trim().to_ascii_lowercase();
+    let invalid = || format!("Invalid duration: {:?} (e.g. 5m, 1h30m, 90s, 25:00)", spec);
+    if spec.is_empty() {
+        return Err(invalid());
+    }
+
+    let total = if spec.contains(':') {
+        // [H:]MM:SS
+        let parts = spec
+            .split(':')
+            .map(|part| {
+                (!part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+                    .then(|| part.parse::<u64>().ok())
+                    .flatten()
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(invalid)?;
+        let (hours, minutes, seconds) = match parts[..] {
+            [minutes, seconds] => (0, minutes, seconds),
+            [hours, minutes, seconds] => (hours, minutes, seconds),
+            _ => return Err(invalid()),
+        };
+        hours
+            .saturating_mul(3600)
+            .saturating_add(minutes.saturating_mul(60))
+            .saturating_add(seconds)
+    } else if spec.bytes().all(|b| b.is_ascii_digit()) {
+        spec.parse::<u64>()
+            .map_err(|_| invalid())?
+            .saturating_mul(60)
+    } else {
+        // 数字と単位の組を順に読む（"1h30m"、"1h 30m"）
+        let mut total: u64 = 0;
+        let mut seen = String::new();
+        let mut rest = spec.as_str();
+        while !rest.is_empty() {
+            let digits = rest.bytes().take_while(|b| b.is_ascii_digit()).count();
+            let value: u64 = rest[..digits].parse().map_err(|_| invalid())?;
+            // "2 min" のように数字と単位の間に空白があってもよい
+            rest = rest[digits..].trim_start();
+            let unit_len = rest.bytes().take_while(|b| b.is_ascii_alphabetic()).count();
+            let unit = &rest[..unit_len];
+            let (key, scale) = match unit {
+                "h" | "hr" | "hrs" | "hour" | "hours" => ('h', 3600),
+                "m" | "min" | "mins" | "minute" | "minutes" => ('m', 60),
+                "s" | "sec" | "secs" | "second" | "seconds" => ('s', 1),
+                _ => return Err(invalid()),
+            };
+            if seen.contains(key) {
+                return Err(invalid());
+            }
+            seen.push(key);
+            total = total.saturating_add(value.saturating_mul(scale));
+            rest = rest[unit_len..].trim_start();
+        }
+        total
+    };
+
+    if total == 0 || total > MAX_DURATION_SECS {
+        return Err(format!(
+            "Duration must be between 1 second and {}",
+            ClockTime::from_secs(MAX_DURATION_SECS)
+        ));
+    }
+    Ok(total)
+}
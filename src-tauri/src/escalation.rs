@@ -0,0 +1,314 @@
+//! 時間切れの知らせ方を段階的に強める。
+//!
+//! まずレイヤーオーバーレイの "TIME UP" を点滅させ、時間切れから一定時間たっても
+//! 止められなければデスクトップ通知、さらにフルスクリーンの Time Up ウィンドウを出す。
+//! どの段階も設定で省ける（ゆるい締切ではフルスクリーンにしない、など）。
+//! 待ち時間はどれも時間切れの時点から数える。
+//!
+//! タイマーを開始・リセットしたり Time Up を閉じたりすると残りの段階は取り消す。
+//! スヌーズはタイマーを設定した時間で開始し直す（時刻指定アラームなら同じ名前のタイマーを作る）。
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::alarm::WallClockAlarm;
+use crate::duration::ClockTime;
+use crate::timer::{self, NamedTimerSnapshot, TimerRegistryState, TimerStatus, DEFAULT_TIMER_ID};
+
+/// 各段階の待ち時間の上限
+pub const MAX_ESCALATION_DELAY_SECS: u64 = 60 * 60;
+/// スヌーズの長さの上限
+pub const MAX_SNOOZE_SECS: u64 = 24 * 60 * 60;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EscalationPolicy {
+    /// 時間切れと同時にレイヤーの "TIME UP" を点滅させる（オフなら点滅せずに表示する）
+    pub layer_flash: bool,
+    pub notification: bool,
+    pub notification_delay_secs: u64,
+    /// フルスクリーンの Time Up ウィンドウ（設定の showTimeUpWindow）
+    pub timeup_window: bool,
+    pub timeup_window_delay_secs: u64,
+    /// スヌーズでタイマーを開始し直す長さ
+    pub snooze_secs: u64,
+}
+
+/// 既定ではこれまでどおりすぐにフルスクリーンにする
+impl Default for EscalationPolicy {
+    fn default() -> Self {
+        Self {
+            layer_flash: true,
+            notification: false,
+            notification_delay_secs: 30,
+            timeup_window: true,
+            timeup_window_delay_secs: 0,
+            snooze_secs: 5 * 60,
+        }
+    }
+}
+
+impl EscalationPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.notification_delay_secs > MAX_ESCALATION_DELAY_SECS
+            || self.timeup_window_delay_secs > MAX_ESCALATION_DELAY_SECS
+        {
+            return Err(format!(
+                "Escalation delays must be at most {} seconds",
+                MAX_ESCALATION_DELAY_SECS
+            ));
+        }
+        if self.snooze_secs == 0 || self.snooze_secs > MAX_SNOOZE_SECS {
+            return Err(format!(
+                "Snooze must be between 1 and {} seconds",
+                MAX_SNOOZE_SECS
+            ));
+        }
+        Ok(())
+    }
+
+    /// 実行する段階を待ち時間の順に並べる。`allow_timeup_window` はタイマー・アラームごとの
+    /// Time Up ウィンドウの設定（ポモドーロの休憩明けなどでは出さない）
+    pub fn plan(&self, allow_timeup_window: bool) -> Vec<(Duration, EscalationStep)> {
+        let mut steps = Vec::new();
+        if self.layer_flash {
+            steps.push((Duration::ZERO, EscalationStep::LayerFlash));
+        }
+        if self.notification {
+            steps.push((
+                Duration::from_secs(self.notification_delay_secs),
+                EscalationStep::Notification,
+            ));
+        }
+        if self.timeup_window && allow_timeup_window {
+            steps.push((
+                Duration::from_secs(self.timeup_window_delay_secs),
+                EscalationStep::TimeupWindow,
+            ));
+        }
+        // 同じ待ち時間なら上の順（安定ソート）
+        steps.sort_by_key(|(delay, _)| *delay);
+        steps
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EscalationStep {
+    LayerFlash,
+    Notification,
+    TimeupWindow,
+}
+
+/// 何の時間切れか
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AlertSource {
+    Timer {
+        id: String,
+        name: String,
+    },
+    /// 時刻指定アラーム。スヌーズでは `label` の名前付きタイマーを使う
+    Alarm {
+        label: String,
+    },
+}
+
+impl AlertSource {
+    /// Time Up ウィンドウ・通知に出す名前。既定タイマーなら `None`
+    pub fn display_name(&self) -> Option<String> {
+        match self {
+            AlertSource::Timer { id, .. } if id == DEFAULT_TIMER_ID => None,
+            AlertSource::Timer { name, .. } => Some(name.clone()),
+            AlertSource::Alarm { label } => Some(label.clone()),
+        }
+    }
+}
+
+/// 進行中の段階と設定。`generation` は取り消し・やり直しで増やし、古いスレッドを止める
+#[derive(Default)]
+pub struct EscalationStore {
+    pub policy: EscalationPolicy,
+    pub active: Option<AlertSource>,
+    pub generation: u64,
+}
+
+#[derive(Default)]
+pub struct EscalationState(pub Mutex<EscalationStore>);
+
+fn lock_store(app: &AppHandle) -> std::sync::MutexGuard<'_, EscalationStore> {
+    app.state::<EscalationState>()
+        .inner()
+        .0
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// レイヤーの "TIME UP" を点滅させるか
+pub fn layer_flash_enabled(app: &AppHandle) -> bool {
+    lock_store(app).policy.layer_flash
+}
+
+/// 段階をまだ実行してよいか。取り消された・タイマーが動き出した場合は `false`
+fn still_active(app: &AppHandle, source: &AlertSource, generation: u64) -> bool {
+    if lock_store(app).generation != generation {
+        return false;
+    }
+    match source {
+        AlertSource::Timer { id, .. } => timer::timer_snapshot(app, Some(id.clone()))
+            .map(|s| {
+                matches!(
+                    s.timer.status,
+                    TimerStatus::Finished | TimerStatus::Overtime
+                )
+            })
+            .unwrap_or(false),
+        AlertSource::Alarm { .. } => true,
+    }
+}
+
+fn fire(app: &AppHandle, source: &AlertSource, step: EscalationStep) {
+    println!("DEBUG: Escalation step {:?} for {:?}", step, source);
+    let name = source.display_name();
+    match step {
+        EscalationStep::LayerFlash => {
+            if let Err(e) =
+                crate::render_layer_timer(app, ClockTime::from_secs(0), true, false, name)
+            {
+                println!("DEBUG: Failed to flash layer: {}", e);
+            }
+        }
+        EscalationStep::Notification => {
+            let body = match &name {
+                Some(name) => format!("{} has finished.", name),
+                None => "The time has come.".to_string(),
+            };
+            if let Err(e) = app
+                .notification()
+                .builder()
+                .title("Time Up!!")
+                .body(body)
+                .show()
+            {
+                println!("DEBUG: Failed to show notification: {}", e);
+            }
+        }
+        EscalationStep::TimeupWindow => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = crate::show_timeup_window(app, name).await {
+                    println!("DEBUG: Failed to show Time Up window: {}", e);
+                }
+            });
+        }
+    }
+}
+
+/// 段階を順に実行する。待ち時間 0 の段階はすぐに実行し、残りはスレッドで待つ
+fn start(app: &AppHandle, source: AlertSource, allow_timeup_window: bool) {
+    let (plan, generation) = {
+        let mut store = lock_store(app);
+        store.generation += 1;
+        store.active = Some(source.clone());
+        (store.policy.plan(allow_timeup_window), store.generation)
+    };
+    let (now, later): (Vec<_>, Vec<_>) = plan.into_iter().partition(|(delay, _)| delay.is_zero());
+    for (_, step) in now {
+        fire(app, &source, step);
+    }
+    if later.is_empty() {
+        return;
+    }
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let started = std::time::Instant::now();
+        for (delay, step) in later {
+            std::thread::sleep(delay.saturating_sub(started.elapsed()));
+            if !still_active(&app, &source, generation) {
+                return;
+            }
+            fire(&app, &source, step);
+        }
+    });
+}
+
+/// タイマーが時間切れになったとき（取りこぼしを鳴らさない設定のときは呼ばれない）
+pub fn on_timer_finished(app: &AppHandle, snapshot: &NamedTimerSnapshot) {
+    start(
+        app,
+        AlertSource::Timer {
+            id: snapshot.id.clone(),
+            name: snapshot.name.clone(),
+        },
+        snapshot.alarm.show_timeup_window,
+    );
+}
+
+/// 時刻指定アラームが鳴ったとき
+pub fn on_alarm_fired(app: &AppHandle, alarm: &WallClockAlarm) {
+    start(
+        app,
+        AlertSource::Alarm {
+            label: alarm.label.clone(),
+        },
+        alarm.alarm.show_timeup_window,
+    );
+}
+
+/// 残りの段階を取り消す（Time Up を閉じたとき）
+pub fn cancel(app: &AppHandle) {
+    let mut store = lock_store(app);
+    if store.active.take().is_some() {
+        store.generation += 1;
+        println!("DEBUG: Escalation cancelled");
+    }
+}
+
+/// 直前の時間切れのタイマーを設定した時間で開始し直し、Time Up を閉じる
+pub fn snooze(app: &AppHandle) -> Result<NamedTimerSnapshot, String> {
+    let (source, snooze) = {
+        let store = lock_store(app);
+        let source = store
+            .active
+            .clone()
+            .ok_or_else(|| "Nothing to snooze".to_string())?;
+        (source, Duration::from_secs(store.policy.snooze_secs))
+    };
+    let id = match &source {
+        AlertSource::Timer { id, .. } => id.clone(),
+        AlertSource::Alarm { label } => timer::timer_id_by_name(app, label, Some(snooze))?,
+    };
+    let snapshot = timer::update_timer(
+        app,
+        &app.state::<TimerRegistryState>(),
+        Some(id),
+        |t, now| {
+            t.engine.set(snooze);
+            t.engine.start(now);
+        },
+    )?;
+    println!("DEBUG: Snoozed {:?} for {:?}", source, snooze);
+    crate::dismiss_timeup(app);
+    Ok(snapshot)
+}
+
+#[tauri::command]
+pub async fn escalation_set_policy(
+    state: State<'_, EscalationState>,
+    policy: EscalationPolicy,
+) -> Result<(), String> {
+    policy.validate()?;
+    let mut store = state
+        .0
+        .lock()
+        .map_err(|e| format!("Escalation state lock poisoned: {}", e))?;
+    println!("DEBUG: Escalation policy set to {:?}", policy);
+    store.policy = policy;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn escalation_snooze(app: AppHandle) -> Result<NamedTimerSnapshot, String> {
+    snooze(&app)
+}
@@ -0,0 +1,548 @@
+//! 壁掛けディスプレイや OBS のブラウザソースにタイマーを映すための WebSocket 配信。
+//!
+//! 既定では無効で、設定画面から有効にすると 127.0.0.1 だけで待ち受ける。
+//! 待ち受けポートは `event_stream.port` にテキストで書き出す。
+//! 接続直後に全タイマーの状態（`timers`）を送り、以降はタイマーの変化を
+//! `tick` / `stateChange` / `finished` / `overtime` として JSON で送る。
+//!
+//! クライアントからは `{"action":"start","id":"timer-2"}` のような制御メッセージを送れる。
+//! `action` は `get` / `start` / `pause` / `reset` / `set`（`hours` / `minutes` / `seconds` を伴う）で、
+//! 結果は `result` または `error` として返す。操作は HTTP API と同じ処理で行う。
+//! ブラウザソースから接続できるよう `Origin` 付きの接続も受け付けるが、
+//! 任意のページから操作されないよう、その接続では `get` 以外の制御メッセージを拒否する。
+//! DNS リバインディング対策として、`Host` がループバック以外の接続はハンドシェイクで拒否する。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+use tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tungstenite::{Message, WebSocket};
+
+use crate::http_api::{is_loopback_host, run_route, ApiRoute, SetTimerBody};
+use crate::timer::{
+    NamedTimerSnapshot, TimerRegistryState, TimerStatus, TIMER_FINISHED_EVENT, TIMER_TICK_EVENT,
+};
+
+/// ポート未指定時に最初に試すポート。HTTP API の既定ポートの隣
+pub const DEFAULT_EVENT_STREAM_PORT: u16 = 21421;
+
+/// 受付・送受信ループが停止要求を確認する間隔
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// WebSocket ハンドシェイクの待ち時間の上限
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EventStreamConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 優先して使うポート。使用中なら空いているポートにずらす
+    #[serde(default = "default_port")]
+    pub port: u16,
+}
+
+fn default_port() -> u16 {
+    DEFAULT_EVENT_STREAM_PORT
+}
+
+impl Default for EventStreamConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_EVENT_STREAM_PORT,
+        }
+    }
+}
+
+/// 設定画面に返す配信の状態
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EventStreamStatus {
+    pub config: EventStreamConfig,
+    /// 実際に待ち受けているポート。停止中は `None`
+    pub port: Option<u16>,
+    pub error: Option<String>,
+}
+
+/// クライアントへ送るメッセージ
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum StreamMessage {
+    /// 接続直後に送る全タイマーの状態
+    Timers {
+        timers: Vec<NamedTimerSnapshot>,
+    },
+    Tick {
+        timer: NamedTimerSnapshot,
+    },
+    StateChange {
+        from: Option<TimerStatus>,
+        to: TimerStatus,
+        timer: NamedTimerSnapshot,
+    },
+    Finished {
+        timer: NamedTimerSnapshot,
+    },
+    /// 0 を過ぎて超過計測に入った
+    Overtime {
+        timer: NamedTimerSnapshot,
+    },
+    /// 制御メッセージの実行結果
+    Result {
+        timer: NamedTimerSnapshot,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// クライアントから受け取る制御メッセージ
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "action", rename_all = "camelCase")]
+pub enum ControlMessage {
+    Get {
+        #[serde(default)]
+        id: Option<String>,
+    },
+    Start {
+        #[serde(default)]
+        id: Option<String>,
+    },
+    Pause {
+        #[serde(default)]
+        id: Option<String>,
+    },
+    Reset {
+        #[serde(default)]
+        id: Option<String>,
+    },
+    Set {
+        #[serde(default)]
+        id: Option<String>,
+        #[serde(default)]
+        hours: u32,
+        #[serde(default)]
+        minutes: u32,
+        #[serde(default)]
+        seconds: u32,
+    },
+}
+
+impl ControlMessage {
+    /// タイマーを変えないメッセージか
+    pub fn is_read_only(&self) -> bool {
+        matches!(self, ControlMessage::Get { .. })
+    }
+
+    /// HTTP API の操作に置き換える
+    pub fn into_route(self) -> (ApiRoute, Option<String>, Option<SetTimerBody>) {
+        match self {
+            ControlMessage::Get { id } => (ApiRoute::GetTimer, id, None),
+            ControlMessage::Start { id } => (ApiRoute::Start, id, None),
+            ControlMessage::Pause { id } => (ApiRoute::Pause, id, None),
+            ControlMessage::Reset { id } => (ApiRoute::Reset, id, None),
+            ControlMessage::Set {
+                id,
+                hours,
+                minutes,
+                seconds,
+            } => (
+                ApiRoute::Set,
+                id,
+                Some(SetTimerBody {
+                    hours,
+                    minutes,
+                    seconds,
+                }),
+            ),
+        }
+    }
+}
+
+/// 接続元に許す操作
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClientAccess {
+    /// ローカルのツールなど。制御メッセージを受け付ける
+    Control,
+    /// `Origin` 付き（Web ページ・ブラウザソース）。配信と `get` だけ
+    ReadOnly,
+}
+
+/// ハンドシェイクの `Host` と `Origin` から接続元に許す操作を決める。
+/// `Host` がループバックでなければ（DNS リバインディングなど）拒否する
+pub fn client_access(host: Option<&str>, origin: Option<&str>) -> Result<ClientAccess, String> {
+    match host {
+        Some(host) if is_loopback_host(host) => {}
+        Some(host) => return Err(format!("Host not allowed: {}", host)),
+        None => return Err("Missing Host header".to_string()),
+    }
+    Ok(match origin {
+        Some(_) => ClientAccess::ReadOnly,
+        None => ClientAccess::Control,
+    })
+}
+
+/// 制御メッセージを読み、接続元に許された操作か確かめる
+pub fn parse_control(text: &str, access: ClientAccess) -> Result<ControlMessage, String> {
+    let control = serde_json::from_str::<ControlMessage>(text)
+        .map_err(|e| format!("Invalid control message: {}", e))?;
+    if access == ClientAccess::ReadOnly && !control.is_read_only() {
+        return Err("Control messages from web pages are not allowed".to_string());
+    }
+    Ok(control)
+}
+
+/// タイマーのイベントと直前の状態から、クライアントへ送るメッセージを決める
+pub fn stream_messages(
+    previous: Option<TimerStatus>,
+    event: &str,
+    snapshot: &NamedTimerSnapshot,
+) -> Vec<StreamMessage> {
+    if event == TIMER_FINISHED_EVENT {
+        return vec![StreamMessage::Finished {
+            timer: snapshot.clone(),
+        }];
+    }
+    if event != TIMER_TICK_EVENT {
+        return Vec::new();
+    }
+
+    let status = snapshot.timer.status;
+    let mut messages = vec![StreamMessage::Tick {
+        timer: snapshot.clone(),
+    }];
+    if previous != Some(status) {
+        messages.push(StreamMessage::StateChange {
+            from: previous,
+            to: status,
+            timer: snapshot.clone(),
+        });
+        // 一時停止からの再開ではなく、カウントダウンから超過計測に入ったとき
+        let counting_down = matches!(
+            previous,
+            Some(TimerStatus::Running) | Some(TimerStatus::Finished)
+        );
+        if status == TimerStatus::Overtime && counting_down {
+            messages.push(StreamMessage::Overtime {
+                timer: snapshot.clone(),
+            });
+        }
+    }
+    messages
+}
+
+#[derive(Default)]
+pub struct EventStream {
+    stop: Option<Arc<AtomicBool>>,
+    clients: Vec<Sender<String>>,
+    /// `stateChange` を判定するための各タイマーの直前の状態
+    statuses: HashMap<String, TimerStatus>,
+    status: EventStreamStatus,
+}
+
+#[derive(Default)]
+pub struct EventStreamState(pub Mutex<EventStream>);
+
+fn load_event_stream_config() -> EventStreamConfig {
+    let path = crate::get_event_stream_config_path();
+    if !path.exists() {
+        return EventStreamConfig::default();
+    }
+    match fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+    {
+        Ok(config) => config,
+        Err(e) => {
+            println!(
+                "DEBUG: Failed to load event stream config, using defaults: {}",
+                e
+            );
+            EventStreamConfig::default()
+        }
+    }
+}
+
+fn save_event_stream_config(config: &EventStreamConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let json = serde_json::to_string_pretty(config)?;
+    fs::write(crate::get_event_stream_config_path(), json)?;
+    Ok(())
+}
+
+fn to_json(message: &StreamMessage) -> Option<String> {
+    match serde_json::to_string(message) {
+        Ok(json) => Some(json),
+        Err(e) => {
+            println!("DEBUG: Failed to serialize stream message: {}", e);
+            None
+        }
+    }
+}
+
+/// タイマーのイベントを接続中のクライアントへ送る。`timer::emit_snapshot` から呼ぶ
+pub fn publish(app: &AppHandle, event: &str, snapshot: &NamedTimerSnapshot) {
+    let state = app.state::<EventStreamState>();
+    let mut stream = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    if stream.stop.is_none() {
+        return;
+    }
+    let previous = stream.statuses.get(&snapshot.id).copied();
+    if event == TIMER_TICK_EVENT {
+        stream
+            .statuses
+            .insert(snapshot.id.clone(), snapshot.timer.status);
+    }
+    if stream.clients.is_empty() {
+        return;
+    }
+    for message in stream_messages(previous, event, snapshot) {
+        if let Some(json) = to_json(&message) {
+            // 切断済みのクライアントは送信に失敗するので取り除く
+            stream
+                .clients
+                .retain(|client| client.send(json.clone()).is_ok());
+        }
+    }
+}
+
+fn handle_control(app: &AppHandle, text: &str, access: ClientAccess) -> StreamMessage {
+    let result = parse_control(text, access).and_then(|control| {
+        let (route, id, set) = control.into_route();
+        run_route(app, route, id, set).map_err(|e| e.message)
+    });
+    match result {
+        Ok(timer) => StreamMessage::Result { timer },
+        Err(message) => StreamMessage::Error { message },
+    }
+}
+
+fn is_timeout(e: &tungstenite::Error) -> bool {
+    matches!(e, tungstenite::Error::Io(e)
+        if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut))
+}
+
+/// 1 クライアントとの送受信。停止要求か切断まで続ける
+fn serve_client(
+    app: &AppHandle,
+    mut socket: WebSocket<TcpStream>,
+    access: ClientAccess,
+    outgoing: Receiver<String>,
+    stop: &AtomicBool,
+) -> tungstenite::Result<()> {
+    let timers = {
+        let state = app.state::<TimerRegistryState>();
+        let registry = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        registry.snapshots(Instant::now())
+    };
+    if let Some(json) = to_json(&StreamMessage::Timers { timers }) {
+        socket.send(Message::text(json))?;
+    }
+
+    while !stop.load(Ordering::Relaxed) {
+        while let Ok(json) = outgoing.try_recv() {
+            socket.send(Message::text(json))?;
+        }
+        match socket.read() {
+            Ok(Message::Text(text)) => {
+                if let Some(json) = to_json(&handle_control(app, text.as_str(), access)) {
+                    socket.send(Message::text(json))?;
+                }
+            }
+            Ok(Message::Close(_)) => break,
+            // ping への pong は tungstenite が返す
+            Ok(_) => {}
+            Err(e) if is_timeout(&e) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    socket.close(None)?;
+    socket.flush()
+}
+
+fn accept_client(app: &AppHandle, stream: TcpStream, stop: Arc<AtomicBool>) {
+    let peer = stream.peer_addr().ok();
+    // 受付側の非ブロッキング設定を引き継ぐ OS があるので戻しておく
+    let prepared = stream
+        .set_nonblocking(false)
+        .and_then(|_| stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT)));
+    if let Err(e) = prepared {
+        println!("DEBUG: Failed to prepare event stream socket: {}", e);
+        return;
+    }
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let mut access = ClientAccess::ReadOnly;
+        // 拒否の応答の型は tungstenite が決めている
+        #[allow(clippy::result_large_err)]
+        let check = |request: &Request, response: Response| {
+            let header = |name| {
+                request
+                    .headers()
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+            };
+            match client_access(header("Host"), header("Origin")) {
+                Ok(allowed) => {
+                    access = allowed;
+                    Ok(response)
+                }
+                Err(message) => {
+                    let mut rejection = ErrorResponse::new(Some(message));
+                    *rejection.status_mut() = tungstenite::http::StatusCode::FORBIDDEN;
+                    Err(rejection)
+                }
+            }
+        };
+        let socket = match tungstenite::accept_hdr(stream, check) {
+            Ok(socket) => socket,
+            Err(e) => {
+                println!("DEBUG: Event stream handshake failed: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = socket.get_ref().set_read_timeout(Some(POLL_INTERVAL)) {
+            println!("DEBUG: Failed to set event stream read timeout: {}", e);
+            return;
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        {
+            let state = app.state::<EventStreamState>();
+            let mut stream = state.0.lock().unwrap_or_else(|e| e.into_inner());
+            stream.clients.push(sender);
+        }
+        println!(
+            "DEBUG: Event stream client connected: {:?} ({:?})",
+            peer, access
+        );
+        match serve_client(&app, socket, access, receiver, &stop) {
+            Ok(())
+            | Err(tungstenite::Error::ConnectionClosed)
+            | Err(tungstenite::Error::AlreadyClosed) => {}
+            Err(e) => println!("DEBUG: Event stream client error: {}", e),
+        }
+        println!("DEBUG: Event stream client disconnected: {:?}", peer);
+    });
+}
+
+/// 優先ポートが使えなければ次のポートから順に待ち受けを試す
+fn bind_listener(preferred: u16) -> Result<(TcpListener, u16), String> {
+    crate::bind_from_port(preferred, |port| TcpListener::bind(("127.0.0.1", port)))
+        .and_then(|(listener, port)| {
+            // 停止要求を確認できるよう受付は非ブロッキングにする
+            listener
+                .set_nonblocking(true)
+                .map(|_| (listener, port))
+                .map_err(|e| e.to_string())
+        })
+        .map_err(|e| format!("Failed to start event stream: {}", e))
+}
+
+fn stop_server(stream: &mut EventStream) {
+    if let Some(stop) = stream.stop.take() {
+        stop.store(true, Ordering::Relaxed);
+        println!("DEBUG: Event stream stopped");
+    }
+    stream.clients.clear();
+    stream.statuses.clear();
+    stream.status.port = None;
+    let path = crate::get_event_stream_port_path();
+    if path.exists() {
+        if let Err(e) = fs::remove_file(&path) {
+            println!("DEBUG: Failed to remove event stream port file: {}", e);
+        }
+    }
+}
+
+/// 設定どおりに配信を止めるか（再）起動し、結果の状態を返す
+fn apply_config(app: &AppHandle, config: EventStreamConfig) -> EventStreamStatus {
+    let state = app.state::<EventStreamState>();
+    let mut stream = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    stop_server(&mut stream);
+    stream.status = EventStreamStatus {
+        config: config.clone(),
+        ..Default::default()
+    };
+    if !config.enabled {
+        return stream.status.clone();
+    }
+
+    match bind_listener(config.port) {
+        Ok((listener, port)) => {
+            if let Err(e) = fs::write(crate::get_event_stream_port_path(), port.to_string()) {
+                println!("DEBUG: Failed to write event stream port file: {}", e);
+            }
+            println!("DEBUG: Event stream listening on ws://127.0.0.1:{}", port);
+            let stop = Arc::new(AtomicBool::new(false));
+            let worker_stop = Arc::clone(&stop);
+            let app = app.clone();
+            std::thread::spawn(move || {
+                while !worker_stop.load(Ordering::Relaxed) {
+                    match listener.accept() {
+                        Ok((client, _)) => accept_client(&app, client, Arc::clone(&worker_stop)),
+                        Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                            std::thread::sleep(POLL_INTERVAL)
+                        }
+                        Err(e) => {
+                            println!("DEBUG: Event stream accept failed: {}", e);
+                            std::thread::sleep(POLL_INTERVAL);
+                        }
+                    }
+                }
+            });
+            stream.stop = Some(stop);
+            stream.status.port = Some(port);
+        }
+        Err(e) => {
+            println!("DEBUG: {}", e);
+            stream.status.error = Some(e);
+        }
+    }
+    stream.status.clone()
+}
+
+/// 起動時に保存済みの設定を反映する。
+/// 無効でも前回異常終了したときのポートファイルは削除する
+pub fn start_if_enabled(app: &AppHandle) {
+    apply_config(app, load_event_stream_config());
+}
+
+/// 終了時に配信を止め、ポートファイルを削除する
+pub fn stop(app: &AppHandle) {
+    let state = app.state::<EventStreamState>();
+    let mut stream = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    stop_server(&mut stream);
+}
+
+#[tauri::command]
+pub async fn event_stream_get_status(
+    state: State<'_, EventStreamState>,
+) -> Result<EventStreamStatus, String> {
+    let stream = state
+        .0
+        .lock()
+        .map_err(|e| format!("Event stream state lock poisoned: {}", e))?;
+    Ok(stream.status.clone())
+}
+
+/// 設定を保存して配信を起動・停止し、結果の状態を返す
+#[tauri::command]
+pub async fn event_stream_set_config(
+    app: AppHandle,
+    config: EventStreamConfig,
+) -> Result<EventStreamStatus, String> {
+    if config.port == 0 {
+        return Err("Event stream port must be between 1 and 65535".to_string());
+    }
+    save_event_stream_config(&config)
+        .map_err(|e| format!("Failed to save event stream config: {}", e))?;
+    Ok(apply_config(&app, config))
+}
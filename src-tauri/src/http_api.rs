@@ -0,0 +1,410 @@
+//! ビルドスクリプトや Stream Deck からタイマーを操作するためのローカル HTTP API。
+//!
+//! 既定では無効で、設定画面から有効にすると 127.0.0.1 だけで待ち受ける。
+//! 待ち受けポートは `http_api.port` にテキストで書き出すので、
+//! `curl "http://127.0.0.1:$(cat http_api.port)/timer"` のように使える。
+//!
+//! - `GET  /timer`        タイマーの状態
+//! - `POST /timer/start`  開始・再開
+//! - `POST /timer/pause`  一時停止
+//! - `POST /timer/reset`  リセット
+//! - `POST /timer/set`    時間を設定（本文は `{"hours":0,"minutes":5,"seconds":0}`）
+//!
+//! どのエンドポイントも `?id=<タイマー ID>` で対象を指定でき、省略すると既定タイマーになる。
+//! パスの各セグメントと `id` の値はパーセントデコードしてから解釈する（`%2F` はセグメントを区切らない）。
+//! ブラウザ上のページから勝手に操作されないよう、`Origin` ヘッダー付きのリクエストは拒否する。
+//! DNS リバインディングで読まれないよう、`Host` がループバック以外のリクエストも拒否する。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::timer::{self, NamedTimerSnapshot, TimerRegistryState};
+
+/// ポート未指定時に最初に試すポート。使用中ならここから順に空きを探す
+pub const DEFAULT_HTTP_API_PORT: u16 = 21420;
+
+/// `POST /timer/set` の本文の上限
+const MAX_BODY_BYTES: u64 = 4096;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpApiConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 優先して使うポート。使用中なら空いているポートにずらす
+    #[serde(default = "default_port")]
+    pub port: u16,
+}
+
+fn default_port() -> u16 {
+    DEFAULT_HTTP_API_PORT
+}
+
+impl Default for HttpApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_HTTP_API_PORT,
+        }
+    }
+}
+
+/// 設定画面に返すサーバーの状態
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpApiStatus {
+    pub config: HttpApiConfig,
+    /// 実際に待ち受けているポート。停止中は `None`
+    pub port: Option<u16>,
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+pub struct HttpApi {
+    server: Option<Arc<Server>>,
+    status: HttpApiStatus,
+}
+
+#[derive(Default)]
+pub struct HttpApiState(pub Mutex<HttpApi>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiRoute {
+    GetTimer,
+    Start,
+    Pause,
+    Reset,
+    Set,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiRequest {
+    pub route: ApiRoute,
+    /// `?id=` で指定したタイマー。省略時は既定タイマー
+    pub timer_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiError {
+    pub status: u16,
+    pub message: String,
+}
+
+impl ApiError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+/// `POST /timer/set` の本文。省略した項目は 0 として扱う
+#[derive(Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct SetTimerBody {
+    pub hours: u32,
+    pub minutes: u32,
+    pub seconds: u32,
+}
+
+/// `Host` ヘッダーがループバック（`127.0.0.1` / `localhost` / `[::1]`、ポート付き可）か
+pub fn is_loopback_host(host: &str) -> bool {
+    let host = host.trim();
+    let name = match host.strip_prefix('[') {
+        Some(rest) => rest.split_once(']').map(|(name, _)| name).unwrap_or(rest),
+        None => host.split_once(':').map(|(name, _)| name).unwrap_or(host),
+    };
+    name.eq_ignore_ascii_case("localhost")
+        || name
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// `%XX` をデコードする。不正なエスケープや UTF-8 でない結果はエラー
+pub fn percent_decode(value: &str) -> Result<String, ApiError> {
+    let invalid = || ApiError::new(400, format!("Invalid percent-encoding: {}", value));
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = bytes.get(i + 1..i + 3).ok_or_else(invalid)?;
+            let hex = std::str::from_utf8(hex).map_err(|_| invalid())?;
+            decoded.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| invalid())
+}
+
+/// メソッドと URL から操作を決める
+pub fn parse_request(method: &str, url: &str) -> Result<ApiRequest, ApiError> {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let segments = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(percent_decode)
+        .collect::<Result<Vec<_>, _>>()?;
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+    let path = format!("/{}", segments.join("/"));
+    let (route, expected_method) = match segments[..] {
+        ["timer"] => (ApiRoute::GetTimer, "GET"),
+        ["timer", "start"] => (ApiRoute::Start, "POST"),
+        ["timer", "pause"] => (ApiRoute::Pause, "POST"),
+        ["timer", "reset"] => (ApiRoute::Reset, "POST"),
+        ["timer", "set"] => (ApiRoute::Set, "POST"),
+        _ => return Err(ApiError::new(404, format!("Not found: {}", path))),
+    };
+    if !method.eq_ignore_ascii_case(expected_method) {
+        return Err(ApiError::new(
+            405,
+            format!("{} requires {}", path, expected_method),
+        ));
+    }
+
+    let timer_id = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == "id")
+        .map(|(_, value)| percent_decode(&value.replace('+', " ")))
+        .transpose()?
+        .filter(|value| !value.is_empty());
+    Ok(ApiRequest { route, timer_id })
+}
+
+/// `POST /timer/set` の本文を解析する
+pub fn parse_set_body(body: &str) -> Result<SetTimerBody, ApiError> {
+    serde_json::from_str(body).map_err(|e| ApiError::new(400, format!("Invalid body: {}", e)))
+}
+
+fn load_http_api_config() -> HttpApiConfig {
+    let path = crate::get_http_api_config_path();
+    if !path.exists() {
+        return HttpApiConfig::default();
+    }
+    match fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+    {
+        Ok(config) => config,
+        Err(e) => {
+            println!(
+                "DEBUG: Failed to load HTTP API config, using defaults: {}",
+                e
+            );
+            HttpApiConfig::default()
+        }
+    }
+}
+
+fn save_http_api_config(config: &HttpApiConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let json = serde_json::to_string_pretty(config)?;
+    fs::write(crate::get_http_api_config_path(), json)?;
+    Ok(())
+}
+
+/// 操作を実行する。`ApiRoute::Set` には `set` が必要。
+/// WebSocket の制御メッセージからも使う
+pub(crate) fn run_route(
+    app: &AppHandle,
+    route: ApiRoute,
+    id: Option<String>,
+    set: Option<SetTimerBody>,
+) -> Result<NamedTimerSnapshot, ApiError> {
+    let state = app.state::<TimerRegistryState>();
+    let result = match route {
+        ApiRoute::GetTimer => timer::timer_snapshot(app, id),
+        ApiRoute::Start => timer::update_timer(app, &state, id, |t, now| {
+            t.engine.start(now);
+        }),
+        ApiRoute::Pause => timer::update_timer(app, &state, id, |t, now| {
+            t.engine.pause(now);
+        }),
+        ApiRoute::Reset => timer::update_timer(app, &state, id, |t, _| t.engine.reset()),
+        ApiRoute::Set => {
+            let body = set.ok_or_else(|| ApiError::new(400, "Missing time to set"))?;
+            let total = timer::clock_total_secs(Some(body.hours), body.minutes, body.seconds);
+            timer::update_timer(app, &state, id, |t, _| {
+                t.engine.set(Duration::from_secs(total))
+            })
+        }
+    };
+    result.map_err(|e| {
+        let status = if e.starts_with("Timer not found") {
+            404
+        } else {
+            400
+        };
+        ApiError::new(status, e)
+    })
+}
+
+fn json_response(status: u16, json: String) -> Response<std::io::Cursor<Vec<u8>>> {
+    let header = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+        .expect("static header is valid");
+    Response::from_string(json)
+        .with_status_code(status)
+        .with_header(header)
+}
+
+fn handle_request(app: &AppHandle, mut request: Request) {
+    let method = request.method().clone();
+    let url = request.url().to_string();
+    let has_origin = request.headers().iter().any(|h| h.field.equiv("Origin"));
+    let loopback_host = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Host"))
+        .is_some_and(|h| is_loopback_host(h.value.as_str()));
+
+    let result = if has_origin {
+        Err(ApiError::new(
+            403,
+            "Requests from web pages are not allowed",
+        ))
+    } else if !loopback_host {
+        Err(ApiError::new(403, "Host not allowed"))
+    } else {
+        parse_request(method.as_str(), &url).and_then(|parsed| {
+            let mut body = String::new();
+            if method == Method::Post {
+                request
+                    .as_reader()
+                    .take(MAX_BODY_BYTES)
+                    .read_to_string(&mut body)
+                    .map_err(|e| ApiError::new(400, format!("Failed to read body: {}", e)))?;
+            }
+            let set = match parsed.route {
+                ApiRoute::Set => Some(parse_set_body(&body)?),
+                _ => None,
+            };
+            run_route(app, parsed.route, parsed.timer_id, set)
+        })
+    };
+
+    let response = match result {
+        Ok(snapshot) => match serde_json::to_string(&snapshot) {
+            Ok(json) => json_response(200, json),
+            Err(e) => json_response(
+                500,
+                serde_json::json!({ "error": e.to_string() }).to_string(),
+            ),
+        },
+        Err(e) => {
+            println!(
+                "DEBUG: HTTP API {} {} -> {}: {}",
+                method, url, e.status, e.message
+            );
+            json_response(
+                e.status,
+                serde_json::json!({ "error": e.message }).to_string(),
+            )
+        }
+    };
+    if let Err(e) = request.respond(response) {
+        println!("DEBUG: Failed to send HTTP API response: {}", e);
+    }
+}
+
+/// 優先ポートが使えなければ次のポートから順に待ち受けを試す
+fn bind_server(preferred: u16) -> Result<(Server, u16), String> {
+    crate::bind_from_port(preferred, |port| Server::http(("127.0.0.1", port)))
+        .map_err(|e| format!("Failed to start HTTP API: {}", e))
+}
+
+fn stop_server(api: &mut HttpApi) {
+    if let Some(server) = api.server.take() {
+        server.unblock();
+        println!("DEBUG: HTTP API stopped");
+    }
+    api.status.port = None;
+    let path = crate::get_http_api_port_path();
+    if path.exists() {
+        if let Err(e) = fs::remove_file(&path) {
+            println!("DEBUG: Failed to remove HTTP API port file: {}", e);
+        }
+    }
+}
+
+/// 設定どおりにサーバーを止めるか（再）起動し、結果の状態を返す
+fn apply_config(app: &AppHandle, config: HttpApiConfig) -> HttpApiStatus {
+    let state = app.state::<HttpApiState>();
+    let mut api = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    stop_server(&mut api);
+    api.status = HttpApiStatus {
+        config: config.clone(),
+        port: None,
+        error: None,
+    };
+    if !config.enabled {
+        return api.status.clone();
+    }
+
+    match bind_server(config.port) {
+        Ok((server, port)) => {
+            if let Err(e) = fs::write(crate::get_http_api_port_path(), port.to_string()) {
+                println!("DEBUG: Failed to write HTTP API port file: {}", e);
+            }
+            println!("DEBUG: HTTP API listening on 127.0.0.1:{}", port);
+            let server = Arc::new(server);
+            let worker = Arc::clone(&server);
+            let app = app.clone();
+            std::thread::spawn(move || {
+                for request in worker.incoming_requests() {
+                    handle_request(&app, request);
+                }
+            });
+            api.server = Some(server);
+            api.status.port = Some(port);
+        }
+        Err(e) => {
+            println!("DEBUG: {}", e);
+            api.status.error = Some(e);
+        }
+    }
+    api.status.clone()
+}
+
+/// 起動時に、保存済みの設定で有効になっていればサーバーを起動する
+/// 無効でも前回異常終了したときのポートファイルは削除する
+pub fn start_if_enabled(app: &AppHandle) {
+    apply_config(app, load_http_api_config());
+}
+
+/// 終了時にサーバーを止め、ポートファイルを削除する
+pub fn stop(app: &AppHandle) {
+    let state = app.state::<HttpApiState>();
+    let mut api = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    stop_server(&mut api);
+}
+
+#[tauri::command]
+pub async fn http_api_get_status(state: State<'_, HttpApiState>) -> Result<HttpApiStatus, String> {
+    let api = state
+        .0
+        .lock()
+        .map_err(|e| format!("HTTP API state lock poisoned: {}", e))?;
+    Ok(api.status.clone())
+}
+
+/// 設定を保存してサーバーを起動・停止し、結果の状態を返す
+#[tauri::command]
+pub async fn http_api_set_config(
+    app: AppHandle,
+    config: HttpApiConfig,
+) -> Result<HttpApiStatus, String> {
+    if config.port == 0 {
+        return Err("HTTP API port must be between 1 and 65535".to_string());
+    }
+    save_http_api_config(&config).map_err(|e| format!("Failed to save HTTP API config: {}", e))?;
+    Ok(apply_config(&app, config))
+}
@@ -0,0 +1,104 @@
+//! 多重起動の防止。
+//!
+//! アプリデータディレクトリの `instance.lock` を排他ロックできたプロセスだけが起動を続ける。
+//! ロックは OS がプロセス終了時に解放するので、異常終了してもファイルが残るだけで次の起動を妨げない。
+//! 2 つ目の起動は制御ソケット（Windows では制御ポート）経由でコマンドライン引数
+//! （`--start 25m` など）を先に起動したプロセスへ渡し、その main ウィンドウを前面に出させてから終了する。
+
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::Path;
+use tauri::{AppHandle, Manager};
+
+/// 先に起動したプロセスのソケットが用意できるまで待つ時間の上限
+const FORWARD_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// プロセスが生きている間保持するロック
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
+}
+
+/// `path` を排他ロックする。ほかのプロセスが保持していれば `None`
+pub fn acquire_at(path: &Path) -> std::io::Result<Option<InstanceLock>> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)?;
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => return Ok(None),
+        Err(TryLockError::Error(e)) => return Err(e),
+    }
+    // 調査用にプロセス ID を書いておく
+    file.set_len(0)?;
+    writeln!(file, "{}", std::process::id())?;
+    Ok(Some(InstanceLock { _file: file }))
+}
+
+/// アプリのロックを取る。すでに起動していれば `None`
+pub fn acquire() -> std::io::Result<Option<InstanceLock>> {
+    acquire_at(&crate::get_instance_lock_path())
+}
+
+/// 先に起動したプロセスへ引数を渡し、main ウィンドウを前面に出させる
+pub fn forward_to_primary(args: Vec<String>) -> Result<(), String> {
+    use crate::control::{self, ControlRequest};
+    use std::time::{Duration, Instant};
+
+    // 先に起動したプロセスがまだソケットを作っていない場合に備えて少し待つ
+    let started = Instant::now();
+    let stream = loop {
+        match control::connect() {
+            Ok(stream) => break stream,
+            Err(e) if started.elapsed() >= FORWARD_TIMEOUT => {
+                return Err(format!(
+                    "Failed to reach the running instance at {}: {}",
+                    control::endpoint_path().display(),
+                    e
+                ));
+            }
+            Err(_) => std::thread::sleep(Duration::from_millis(100)),
+        }
+    };
+    #[cfg(windows)]
+    allow_primary_to_focus();
+    let response = control::send_request(stream, &ControlRequest::Activate { args })?;
+    match response.error {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// Windows では前面に出す権利はユーザーが起動したこのプロセスにあるので、先に起動したプロセスに譲る
+#[cfg(windows)]
+fn allow_primary_to_focus() {
+    const ASFW_ANY: u32 = u32::MAX;
+    #[link(name = "user32")]
+    extern "system" {
+        fn AllowSetForegroundWindow(process_id: u32) -> i32;
+    }
+    // SAFETY: 引数は値だけで、失敗しても前面に出せないだけ
+    if unsafe { AllowSetForegroundWindow(ASFW_ANY) } == 0 {
+        println!("DEBUG: AllowSetForegroundWindow failed");
+    }
+}
+
+/// 2 つ目の起動から渡された引数を反映し、main ウィンドウを前面に出す
+/// （`--minimized` のときは出さない）
+pub fn activate(app: &AppHandle, args: &[String]) -> Result<(), String> {
+    println!("DEBUG: Activated by another launch with args: {:?}", args);
+    let options = crate::launch::apply_forwarded(app, args)?;
+    if options.minimized {
+        return Ok(());
+    }
+    if let Some(window) = app.get_webview_window("main") {
+        if let Err(e) = window.unminimize() {
+            println!("DEBUG: Failed to unminimize main window: {}", e);
+        }
+        crate::force_focus_window(&window);
+    }
+    Ok(())
+}
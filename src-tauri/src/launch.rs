@@ -0,0 +1,284 @@
+//! GUI のコマンドライン引数。ランチャーや自動起動から設定済みの状態で開くために使う。
+//!
+//! `--config <path>` には同じ項目を JSON で書いておける
+//! （例: `{"start": "25m", "layer": true, "alwaysOnTop": true}`）。コマンドラインの指定が優先する。
+//! 2 つ目の起動は解釈・検証した結果を `to_args` で引数に戻して先に起動したプロセスへ渡すので、
+//! 相対パスの `--config` もその場で読み込まれる。
+//! Windows・Linux で OS から渡される `lightning-timer://` リンクもここで受け取る。
+//! 起動済みのときはリンクも `to_args` に含めて転送するので、どの OS でも先に起動したプロセスで開く。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::duration::{parse_duration_spec, ClockTime};
+
+pub const LAUNCH_OPTIONS_EVENT: &str = "launch-options";
+
+pub const LAUNCH_USAGE: &str = "\
+Usage: lightning-timer [options] [lightning-timer://LINK]
+
+Options:
+  --start <DURATION>   Start the timer (5m, 1h30m, 90s, 25:00)
+  --preset <NAME>      Start a preset by name or id
+  --layer              Open straight into the layer overlay
+  --minimized          Start with the main window minimized
+  --always-on-top      Keep the main window on top for this session
+  --config <PATH>      Read these options from a JSON file (flags override it)
+  -h, --help           Show this help";
+
+/// 解釈した起動オプション
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LaunchOptions {
+    pub start_secs: Option<u64>,
+    pub preset: Option<String>,
+    pub layer: bool,
+    pub minimized: bool,
+    pub always_on_top: bool,
+    pub config: Option<PathBuf>,
+    /// 検証済みの `lightning-timer://` リンク
+    pub deep_link: Option<String>,
+}
+
+/// `--config` で読み込む JSON
+#[derive(Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct LaunchConfig {
+    pub start: Option<String>,
+    pub preset: Option<String>,
+    pub layer: bool,
+    pub minimized: bool,
+    pub always_on_top: bool,
+}
+
+/// 解釈の結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LaunchCommand {
+    Help,
+    Run(LaunchOptions),
+}
+
+/// フロントエンドで反映するオプション（`launch-options` のペイロード）
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FrontendLaunchOptions {
+    pub layer: bool,
+    pub always_on_top: bool,
+}
+
+/// 起動時にフロントエンドが受け取るまで保持するオプション
+#[derive(Default)]
+pub struct LaunchState(pub Mutex<Option<FrontendLaunchOptions>>);
+
+/// GUI の引数（プログラム名を除く）を解釈する。`--config` の読み込みはしない
+pub fn parse_launch_args(args: &[String]) -> Result<LaunchCommand, String> {
+    let mut options = LaunchOptions::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        // `--start=25m` の形も受け付ける
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = |name: &str| -> Result<String, String> {
+            inline_value
+                .clone()
+                .or_else(|| iter.next().cloned())
+                .filter(|v| !v.trim().is_empty())
+                .ok_or_else(|| format!("{} requires a value", name))
+        };
+        match flag {
+            "-h" | "--help" => return Ok(LaunchCommand::Help),
+            "--start" => {
+                let spec = value(flag)?;
+                options.start_secs =
+                    Some(parse_duration_spec(&spec).map_err(|e| format!("--start: {}", e))?);
+            }
+            "--preset" => options.preset = Some(value(flag)?.trim().to_string()),
+            "--config" => options.config = Some(PathBuf::from(value(flag)?)),
+            "--layer" | "--minimized" | "--always-on-top" if inline_value.is_some() => {
+                return Err(format!("{} does not take a value", flag));
+            }
+            "--layer" => options.layer = true,
+            "--minimized" => options.minimized = true,
+            "--always-on-top" => options.always_on_top = true,
+            // 古い macOS が Finder からの起動時に付けるプロセス番号
+            psn if psn.starts_with("-psn_") => {}
+            link if crate::deep_link::is_deep_link(link) => {
+                if options.deep_link.is_some() {
+                    return Err("Only one link can be opened at a time".to_string());
+                }
+                crate::deep_link::parse_deep_link(link)?;
+                options.deep_link = Some(link.to_string());
+            }
+            other if other.starts_with('-') => return Err(format!("Unknown option: {}", other)),
+            other => return Err(format!("Unexpected argument: {}", other)),
+        }
+    }
+    options.validate()?;
+    Ok(LaunchCommand::Run(options))
+}
+
+impl LaunchOptions {
+    fn validate(&self) -> Result<(), String> {
+        if self.start_secs.is_some() && self.preset.is_some() {
+            return Err("--start and --preset cannot be used together".to_string());
+        }
+        if self.deep_link.is_some() && (self.start_secs.is_some() || self.preset.is_some()) {
+            return Err("A link cannot be combined with --start or --preset".to_string());
+        }
+        Ok(())
+    }
+
+    /// `--config` の内容を、コマンドラインで指定されていない項目に反映する
+    pub fn merge_config(&mut self, config: LaunchConfig) -> Result<(), String> {
+        if self.start_secs.is_none() && self.preset.is_none() && self.deep_link.is_none() {
+            if let Some(spec) = &config.start {
+                self.start_secs =
+                    Some(parse_duration_spec(spec).map_err(|e| format!("\"start\": {}", e))?);
+            }
+            self.preset = config.preset.map(|p| p.trim().to_string());
+        }
+        self.layer |= config.layer;
+        self.minimized |= config.minimized;
+        self.always_on_top |= config.always_on_top;
+        self.config = None;
+        self.validate()
+    }
+
+    /// `--config` を読み込んで反映する
+    pub fn load_config(&mut self) -> Result<(), String> {
+        let Some(path) = self.config.clone() else {
+            return Ok(());
+        };
+        let config = read_config(&path)?;
+        self.merge_config(config)
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// 先に起動したプロセスへ渡す引数に戻す（`--config` は反映済みなので含めない）
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(secs) = self.start_secs {
+            args.push("--start".to_string());
+            args.push(ClockTime::from_secs(secs).to_string());
+        }
+        if let Some(preset) = &self.preset {
+            args.push("--preset".to_string());
+            args.push(preset.clone());
+        }
+        for (enabled, flag) in [
+            (self.layer, "--layer"),
+            (self.minimized, "--minimized"),
+            (self.always_on_top, "--always-on-top"),
+        ] {
+            if enabled {
+                args.push(flag.to_string());
+            }
+        }
+        if let Some(link) = &self.deep_link {
+            args.push(link.clone());
+        }
+        args
+    }
+
+    pub fn frontend(&self) -> FrontendLaunchOptions {
+        FrontendLaunchOptions {
+            layer: self.layer,
+            always_on_top: self.always_on_top,
+        }
+    }
+}
+
+fn read_config(path: &Path) -> Result<LaunchConfig, String> {
+    let json = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&json).map_err(|e| format!("Invalid config {}: {}", path.display(), e))
+}
+
+/// 起動時の引数を解釈する。`--help` や誤りはここで表示して終了コードを返す
+pub fn options_from_env() -> Result<LaunchOptions, i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = parse_launch_args(&args).and_then(|command| match command {
+        LaunchCommand::Help => Ok(None),
+        LaunchCommand::Run(mut options) => options.load_config().map(|_| Some(options)),
+    });
+    match result {
+        Ok(Some(options)) => Ok(options),
+        Ok(None) => {
+            println!("{}", LAUNCH_USAGE);
+            Err(0)
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, LAUNCH_USAGE);
+            Err(2)
+        }
+    }
+}
+
+/// タイマーの開始（リンクを含む）・ウィンドウの最小化を行う。レイヤー表示と最前面表示はフロントエンドが行う
+fn apply_backend(app: &AppHandle, options: &LaunchOptions) {
+    let result = match (&options.preset, options.start_secs, &options.deep_link) {
+        (Some(preset), _, _) => crate::preset::launch_preset(app, preset).map(|_| ()),
+        (None, Some(secs), _) => crate::preset::start_default_timer(app, secs, None).map(|_| ()),
+        (None, None, Some(link)) => crate::deep_link::open(app, link).map(|_| ()),
+        (None, None, None) => Ok(()),
+    };
+    if let Err(e) = result {
+        println!("DEBUG: Failed to apply launch options: {}", e);
+    }
+    if options.minimized {
+        if let Some(window) = app.get_webview_window("main") {
+            if let Err(e) = window.minimize() {
+                println!("DEBUG: Failed to minimize main window: {}", e);
+            }
+        }
+    }
+}
+
+/// 起動時のオプションを反映する。フロントエンド向けの分は `launch_take_options` で渡す
+pub fn apply_on_startup(app: &AppHandle, options: &LaunchOptions) {
+    println!("DEBUG: Launch options: {:?}", options);
+    apply_backend(app, options);
+    *app.state::<LaunchState>()
+        .0
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = Some(options.frontend());
+}
+
+/// 2 つ目の起動から渡された引数を反映する。フロントエンドには `launch-options` で通知する
+pub fn apply_forwarded(app: &AppHandle, args: &[String]) -> Result<LaunchOptions, String> {
+    let options = match parse_launch_args(args)? {
+        LaunchCommand::Help => LaunchOptions::default(),
+        LaunchCommand::Run(mut options) => {
+            options.load_config()?;
+            options
+        }
+    };
+    apply_backend(app, &options);
+    let frontend = options.frontend();
+    if frontend != FrontendLaunchOptions::default() {
+        if let Err(e) = app.emit_to(
+            tauri::EventTarget::webview_window("main"),
+            LAUNCH_OPTIONS_EVENT,
+            &frontend,
+        ) {
+            println!("DEBUG: Failed to emit {}: {}", LAUNCH_OPTIONS_EVENT, e);
+        }
+    }
+    Ok(options)
+}
+
+/// 起動時のオプションのうちフロントエンドで反映する分を 1 度だけ返す
+#[tauri::command]
+pub async fn launch_take_options(
+    state: State<'_, LaunchState>,
+) -> Result<Option<FrontendLaunchOptions>, String> {
+    let mut pending = state
+        .0
+        .lock()
+        .map_err(|e| format!("Launch state lock poisoned: {}", e))?;
+    Ok(pending.take())
+}
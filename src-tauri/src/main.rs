@@ -1,9 +1,8 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use std::fs;
-#[cfg(not(debug_assertions))]
 use std::net::{SocketAddr, TcpListener};
 use tauri::{AppHandle, Emitter, Manager, WindowEvent};
 use tauri_plugin_store::Builder as StoreBuilder;
@@ -13,19 +12,114 @@ use objc2::MainThreadMarker;
 #[cfg(target_os = "macos")]
 use objc2_app_kit::NSApplication;
 
-#[derive(Serialize, Deserialize, Default, Debug)]
-pub struct WindowState {
-    x: Option<i32>,
-    y: Option<i32>,
-    width: Option<u32>,
-    height: Option<u32>,
+mod alarm;
+// CLI の引数解釈は lightning-timer-cli と共有し、ここではテストからだけ使う
+#[cfg(test)]
+mod cli_args;
+mod control;
+mod control_server;
+mod deep_link;
+mod duration;
+mod escalation;
+mod event_stream;
+mod http_api;
+mod instance;
+mod launch;
+mod pomodoro;
+mod preset;
+mod resync;
+mod sequence;
+mod session;
+mod shortcut;
+mod stopwatch;
+mod timer;
+mod timeup;
+mod tray;
+mod window_state;
+
+use alarm::AlarmState;
+use duration::ClockTime;
+use escalation::EscalationState;
+use event_stream::EventStreamState;
+use http_api::HttpApiState;
+use launch::LaunchState;
+use pomodoro::PomodoroState;
+use resync::ResyncState;
+use sequence::SequenceState;
+use shortcut::ShortcutRegistryState;
+use stopwatch::StopwatchState;
+use timer::{TimerRegistryState, TimerRestoreState};
+use timeup::{timeup_label, TimeupGeometry, TimeupScreenState};
+use tray::TrayState;
+use window_state::WindowStatesState;
+
+/// アプリのデータディレクトリ（存在しなければ作成する）
+pub fn get_app_data_dir() -> std::path::PathBuf {
+    let path = control::app_data_dir();
+    fs::create_dir_all(&path).ok();
+    path
 }
 
+/// ウィンドウごとの位置・サイズ（ラベルがキー）
 pub fn get_window_state_path() -> std::path::PathBuf {
-    let mut path = dirs::data_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
-    path.push("lightning-timer");
+    get_app_data_dir().join("window_states.json")
+}
+
+/// 以前の main ウィンドウだけの状態。読み込み時に `window_states.json` へ移行する
+pub fn get_legacy_window_state_path() -> std::path::PathBuf {
+    get_app_data_dir().join("window_state.json")
+}
+
+pub fn get_timer_state_path() -> std::path::PathBuf {
+    get_app_data_dir().join("timer_state.json")
+}
+
+pub fn get_pomodoro_config_path() -> std::path::PathBuf {
+    get_app_data_dir().join("pomodoro.json")
+}
+
+pub fn get_alarms_path() -> std::path::PathBuf {
+    get_app_data_dir().join("alarms.json")
+}
+
+pub fn get_presets_path() -> std::path::PathBuf {
+    get_app_data_dir().join("presets.json")
+}
+
+pub fn get_shortcuts_path() -> std::path::PathBuf {
+    get_app_data_dir().join("shortcuts.json")
+}
+
+pub fn get_http_api_config_path() -> std::path::PathBuf {
+    get_app_data_dir().join("http_api.json")
+}
+
+/// HTTP API が実際に待ち受けているポート番号を書き出すファイル
+pub fn get_http_api_port_path() -> std::path::PathBuf {
+    get_app_data_dir().join("http_api.port")
+}
+
+pub fn get_event_stream_config_path() -> std::path::PathBuf {
+    get_app_data_dir().join("event_stream.json")
+}
+
+/// WebSocket 配信が実際に待ち受けているポート番号を書き出すファイル
+pub fn get_event_stream_port_path() -> std::path::PathBuf {
+    get_app_data_dir().join("event_stream.port")
+}
+
+/// 多重起動を防ぐためのロックファイル
+pub fn get_instance_lock_path() -> std::path::PathBuf {
+    get_app_data_dir().join("instance.lock")
+}
+
+pub fn get_sessions_path() -> std::path::PathBuf {
+    get_app_data_dir().join("sessions.json")
+}
+
+pub fn get_sequences_dir() -> std::path::PathBuf {
+    let path = get_app_data_dir().join("sequences");
     fs::create_dir_all(&path).ok();
-    path.push("window_state.json");
     path
 }
 
@@ -40,52 +134,46 @@ pub fn find_available_port() -> Result<u16, Box<dyn std::error::Error>> {
     // 製品ビルド時は20000番台から順番に空いているポートを探す
     #[cfg(not(debug_assertions))]
     {
-        for port in 20000..=65535 {
-            let addr = SocketAddr::from(([127, 0, 0, 1], port));
-            if let Ok(_listener) = TcpListener::bind(&addr) {
-                println!("DEBUG: Found available port: {}", port);
-                return Ok(port);
-            }
-        }
-        Err("No available port found in range 20000-65535".into())
+        find_free_port_from(20000)
     }
 }
 
-fn save_window_state(window: &tauri::WebviewWindow) -> Result<(), Box<dyn std::error::Error>> {
-    let state = WindowState {
-        x: window.outer_position().ok().map(|p| p.x),
-        y: window.outer_position().ok().map(|p| p.y),
-        width: None,  // サイズは固定なので保存しない
-        height: None, // サイズは固定なので保存しない
-    };
-
-    let json = serde_json::to_string_pretty(&state)?;
-    fs::write(get_window_state_path(), json)?;
-    println!("DEBUG: Window state saved: {:?}", state);
-    Ok(())
+/// `preferred` から順に試すポート。65535 の次は 1024 に戻り、`preferred` の手前で終わる
+pub fn port_candidates(preferred: u16) -> impl Iterator<Item = u16> {
+    (preferred..=u16::MAX).chain(1024..preferred)
 }
 
-fn restore_window_state(window: &tauri::WebviewWindow) -> Result<(), Box<dyn std::error::Error>> {
-    let path = get_window_state_path();
-    if !path.exists() {
-        println!("DEBUG: No saved window state found");
-        return Ok(());
+/// `preferred` から順に `bind` を試し、最初に待ち受けられたものとそのポートを返す。
+/// 空きを調べてから改めて待ち受けると間に他のプロセスに取られることがあるので、候補ごとに直接待ち受ける
+pub fn bind_from_port<T, E: std::fmt::Display>(
+    preferred: u16,
+    mut bind: impl FnMut(u16) -> Result<T, E>,
+) -> Result<(T, u16), String> {
+    let mut first_error = None;
+    for port in port_candidates(preferred) {
+        match bind(port) {
+            Ok(bound) => return Ok((bound, port)),
+            Err(e) => {
+                first_error.get_or_insert_with(|| e.to_string());
+            }
+        }
     }
+    Err(format!(
+        "No available port: {}",
+        first_error.unwrap_or_default()
+    ))
+}
 
-    let json = fs::read_to_string(&path)?;
-    let state: WindowState = serde_json::from_str(&json)?;
-
-    if let Some(x) = state.x {
-        if let Some(y) = state.y {
-            window.set_position(tauri::Position::Physical(tauri::PhysicalPosition { x, y }))?;
-            println!("DEBUG: Window position restored: ({}, {})", x, y);
+/// 127.0.0.1 で `start` 以降の空いているポートを順番に探す
+pub fn find_free_port_from(start: u16) -> Result<u16, Box<dyn std::error::Error>> {
+    for port in start..=65535 {
+        let addr = SocketAddr::from(([127, 0, 0, 1], port));
+        if let Ok(_listener) = TcpListener::bind(addr) {
+            println!("DEBUG: Found available port: {}", port);
+            return Ok(port);
         }
     }
-
-    // サイズは固定なので復元しない
-    println!("DEBUG: Window size is fixed, not restoring");
-
-    Ok(())
+    Err(format!("No available port found in range {}-65535", start).into())
 }
 
 /// macOSでネイティブAPIを使ってウィンドウとWKWebViewにフォーカスを強制的に戻すヘルパー関数
@@ -128,11 +216,23 @@ async fn open_devtools(app: AppHandle) -> Result<(), String> {
 }
 
 #[tauri::command]
-async fn save_timer_state_on_exit() -> Result<(), String> {
+async fn save_timer_state_on_exit(app: AppHandle) -> Result<(), String> {
     println!("DEBUG: Save timer state on exit command called");
+    timer::save_timer_state(&app);
     Ok(())
 }
 
+/// 終了時の後始末。どの経路で終了しても `RunEvent::Exit` から 1 回だけ呼ぶ
+fn shutdown(app: &AppHandle) {
+    println!("DEBUG: Shutting down");
+    timer::save_timer_state(app);
+    window_state::save(app, &window_state::TRACKED_WINDOWS);
+    // 終了後にクライアントが古いポート・ソケットへ接続しないよう削除する
+    http_api::stop(app);
+    event_stream::stop(app);
+    control_server::shutdown();
+}
+
 #[tauri::command]
 async fn exit_app(app: AppHandle) -> Result<(), String> {
     println!("DEBUG: Exit app command called");
@@ -152,12 +252,7 @@ async fn start_drag(window: tauri::WebviewWindow) -> Result<(), String> {
 
 #[tauri::command]
 async fn save_window_position(app: AppHandle) -> Result<(), String> {
-    if let Some(window) = app.get_webview_window("main") {
-        if let Err(e) = save_window_state(&window) {
-            println!("DEBUG: Failed to save window position: {}", e);
-            return Err(format!("Failed to save window position: {}", e));
-        }
-    }
+    window_state::save(&app, &["main"]);
     Ok(())
 }
 
@@ -243,116 +338,140 @@ async fn get_available_port() -> Result<u16, String> {
     }
 }
 
-#[tauri::command]
-async fn show_timeup_window(app: AppHandle) -> Result<(), String> {
-    println!("DEBUG: show_timeup_window called");
-
-    // 既存のウィンドウがあれば表示する、なければ新規作成
-    if let Some(existing_window) = app.get_webview_window("timeup") {
-        println!("DEBUG: Showing existing Time Up window");
-
-        // ウィンドウ表示前にisClosingフラグをリセット
-        let _ = existing_window.eval(
-            "
-            console.log('Resetting isClosing flag...');
-            if (typeof isClosing !== 'undefined') {
-                isClosing = false;
-                console.log('isClosing flag reset to false');
-            } else {
-                console.log('isClosing variable not found');
-            }
-        ",
-        );
+/// Time Up ウィンドウの再表示を知らせるイベント（閉じる処理中のフラグもページ側で戻す）
+pub const TIMEUP_SHOW_EVENT: &str = "timeup-show";
 
-        // ウィンドウをプライマリモニターの左上角に移動
-        if let Err(e) =
-            existing_window.set_position(tauri::Position::Logical(tauri::LogicalPosition {
-                x: 0.0,
-                y: 0.0,
-            }))
-        {
-            println!("DEBUG: Failed to set window position: {}", e);
-        }
+/// Time Up ウィンドウに表示する内容。`timeup-show` のペイロード
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeupInfo {
+    /// 終了したタイマーの名前。既定タイマーなら `None`
+    pub timer_name: Option<String>,
+}
 
-        if let Err(e) = existing_window.show() {
-            println!("DEBUG: Failed to show existing window: {}", e);
-            return Err(format!("Failed to show existing Time Up window: {}", e));
-        }
-        if let Err(e) = existing_window.set_focus() {
-            println!("DEBUG: Failed to focus existing window: {}", e);
-            return Err(format!("Failed to focus existing Time Up window: {}", e));
-        }
-        println!("DEBUG: Existing Time Up window shown and focused");
-        return Ok(());
-    }
+/// 表示中の Time Up の内容。新しく作ったウィンドウは読み込み後に `timeup_current` で取得する
+#[derive(Default)]
+pub struct TimeupState(pub std::sync::Mutex<TimeupInfo>);
 
-    // 新しいウィンドウを作成
-    println!("DEBUG: Creating new Time Up window");
-
-    // 画面サイズを取得（プライマリモニターの論理サイズを使用）
-    let screen_size = if let Some(main_window) = app.get_webview_window("main") {
-        if let Ok(monitor) = main_window.primary_monitor() {
-            if let Some(monitor) = monitor {
-                let size = monitor.size();
-                let scale_factor = monitor.scale_factor();
-                // 論理サイズを計算（物理サイズ / スケールファクター）
-                let logical_width = size.width as f64 / scale_factor;
-                let logical_height = size.height as f64 / scale_factor;
-                println!(
-                    "DEBUG: Physical size: {}x{}, Scale factor: {}, Logical size: {}x{}",
-                    size.width, size.height, scale_factor, logical_width, logical_height
-                );
-                (logical_width, logical_height)
-            } else {
-                println!("DEBUG: No primary monitor found, using default size");
-                (1920.0, 1080.0)
+#[tauri::command]
+async fn timeup_current(state: tauri::State<'_, TimeupState>) -> Result<TimeupInfo, String> {
+    let info = state
+        .0
+        .lock()
+        .map_err(|e| format!("Time Up state lock poisoned: {}", e))?;
+    Ok(info.clone())
+}
+
+/// Time Up ウィンドウを 1 枚、モニターを覆うように表示する。なければ作る
+fn show_timeup_on(
+    app: &AppHandle,
+    label: &str,
+    geometry: &TimeupGeometry,
+    info: &TimeupInfo,
+) -> Result<tauri::WebviewWindow, String> {
+    let window = match app.get_webview_window(label) {
+        Some(window) => {
+            println!("DEBUG: Showing existing Time Up window {}", label);
+            if let Err(e) = app.emit_to(
+                tauri::EventTarget::webview_window(label),
+                TIMEUP_SHOW_EVENT,
+                info,
+            ) {
+                println!("DEBUG: Failed to emit {}: {}", TIMEUP_SHOW_EVENT, e);
             }
-        } else {
-            println!("DEBUG: Failed to get primary monitor, using default size");
-            (1920.0, 1080.0)
+            window
+        }
+        None => {
+            println!("DEBUG: Creating new Time Up window {}", label);
+            tauri::WebviewWindowBuilder::new(
+                app,
+                label,
+                tauri::WebviewUrl::App("timeup.html".into()),
+            )
+            .title("Time Up!!")
+            .inner_size(geometry.width, geometry.height)
+            .resizable(false)
+            .decorations(false) // ウィンドウバー非表示
+            .always_on_top(true)
+            .visible(false) // モニターへ動かしてから表示する
+            .build()
+            .map_err(|e| {
+                println!("DEBUG: Failed to create Time Up window: {}", e);
+                format!("Failed to create Time Up window: {}", e)
+            })?
         }
-    } else {
-        println!("DEBUG: No main window found, using default size");
-        (1920.0, 1080.0)
     };
 
-    // 新しいTime Upウィンドウを作成（プライマリモニターの画面サイズに合わせて表示）
-    match tauri::WebviewWindowBuilder::new(
-        &app,
-        "timeup",
-        tauri::WebviewUrl::App("timeup.html".into()),
-    )
-    .title("Time Up!!")
-    .inner_size(screen_size.0, screen_size.1) // プライマリモニターの画面サイズに合わせる
-    .position(0.0, 0.0) // プライマリモニターの左上角に配置
-    .resizable(false)
-    .decorations(false) // ウィンドウバー非表示
-    .always_on_top(true)
-    .visible(true) // 明示的に可視化
-    .build()
-    {
-        Ok(_window) => {
-            println!("DEBUG: Time Up window created with screen size");
-
-            // ウィンドウを確実に表示
-            if let Some(window) = app.get_webview_window("timeup") {
-                if let Err(e) = window.show() {
-                    println!("DEBUG: Failed to show window: {}", e);
-                    return Err(format!("Failed to show Time Up window: {}", e));
-                }
-                if let Err(e) = window.set_focus() {
-                    println!("DEBUG: Failed to focus window: {}", e);
-                    return Err(format!("Failed to focus Time Up window: {}", e));
-                }
-                println!("DEBUG: Time Up window shown and focused");
+    // モニターの左上へ物理座標で動かし、そのモニターの倍率で画面全体の大きさにする
+    if let Err(e) = window.set_position(tauri::PhysicalPosition {
+        x: geometry.x,
+        y: geometry.y,
+    }) {
+        println!("DEBUG: Failed to set window position: {}", e);
+    }
+    let size = tauri::LogicalSize::new(geometry.width, geometry.height)
+        .to_physical::<u32>(geometry.scale_factor);
+    if let Err(e) = window.set_size(size) {
+        println!("DEBUG: Failed to set window size: {}", e);
+    }
+    window.show().map_err(|e| {
+        println!("DEBUG: Failed to show window: {}", e);
+        format!("Failed to show Time Up window: {}", e)
+    })?;
+    Ok(window)
+}
+
+#[tauri::command]
+async fn show_timeup_window(app: AppHandle, timer_name: Option<String>) -> Result<(), String> {
+    println!("DEBUG: show_timeup_window called (timer: {:?})", timer_name);
+
+    // どのタイマーが終了したかを表示するための名前
+    let info = TimeupInfo { timer_name };
+    *app.state::<TimeupState>()
+        .0
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = info.clone();
+
+    // 設定に応じたモニターごとに 1 枚。モニターが取れなければ以前どおり左上に 1920x1080
+    let mut geometries: Vec<TimeupGeometry> = timeup::target_monitors(&app)
+        .iter()
+        .map(TimeupGeometry::covering)
+        .collect();
+    if geometries.is_empty() {
+        println!("DEBUG: No monitor found, using default size");
+        geometries.push(TimeupGeometry {
+            x: 0,
+            y: 0,
+            width: 1920.0,
+            height: 1080.0,
+            scale_factor: 1.0,
+        });
+    }
+    println!("DEBUG: Time Up windows: {:?}", geometries);
+
+    let mut first = None;
+    for (index, geometry) in geometries.iter().enumerate() {
+        let window = show_timeup_on(&app, &timeup_label(index), geometry, &info)?;
+        first.get_or_insert(window);
+    }
+    // 前回より表示先が減ったときの残り
+    for window in timeup::timeup_windows(&app) {
+        let index = (0..geometries.len()).find(|&i| timeup_label(i) == window.label());
+        if index.is_none() {
+            if let Err(e) = window.hide() {
+                println!("DEBUG: Failed to hide Time Up window: {}", e);
             }
         }
-        Err(e) => {
-            println!("DEBUG: Failed to create Time Up window: {}", e);
-            return Err(format!("Failed to create Time Up window: {}", e));
-        }
     }
 
+    // Esc で閉じられるよう 1 枚目にフォーカスする
+    if let Some(window) = first {
+        window.set_focus().map_err(|e| {
+            println!("DEBUG: Failed to focus window: {}", e);
+            format!("Failed to focus Time Up window: {}", e)
+        })?;
+    }
+    println!("DEBUG: Time Up window shown and focused");
     Ok(())
 }
 
@@ -415,15 +534,68 @@ fn apply_macos_overlay_behavior(window: &tauri::WebviewWindow, panel_nonactivati
     });
 }
 
-/// フォントサイズ（rem）に基づいてレイヤーウィンドウの適切なサイズ（論理ピクセル）を計算する。
-/// "TIME UP"（7文字モノスペース + letter-spacing 0.05em）が収まるサイズを返す。
-fn layer_size_for_font(font_size: f64) -> (f64, f64) {
+/// レイヤーに表示する文字数の下限（"TIME UP" の 7 文字）
+const LAYER_MIN_CHARS: usize = 7;
+
+/// フォントサイズ（rem）と表示文字数に基づいてレイヤーウィンドウの適切なサイズ（論理ピクセル）を計算する。
+/// 1 文字あたりモノスペース + letter-spacing 0.05em 程度の幅に余白を足し、
+/// 最低でも "TIME UP" が収まるサイズを返す。"1:30:00" や "-1:00:00" など長い表示では横に広げる。
+fn layer_size_for_font(font_size: f64, chars: usize) -> (f64, f64) {
     let font_px = font_size * 16.0;
-    let width = (font_px * 5.5).max(320.0);
+    let chars = chars.max(LAYER_MIN_CHARS) as f64;
+    let width = (font_px * (0.65 * chars + 1.0)).max(320.0);
     let height = (font_px * 1.5).max(120.0);
     (width, height)
 }
 
+/// レイヤーウィンドウのサイズ計算に使う現在のフォントサイズと表示文字数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayerLayout {
+    pub font_size: f64,
+    pub chars: usize,
+}
+
+impl Default for LayerLayout {
+    fn default() -> Self {
+        Self {
+            font_size: 6.0,
+            chars: LAYER_MIN_CHARS,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct LayerLayoutState(pub std::sync::Mutex<LayerLayout>);
+
+/// フォントサイズまたは文字数が変わったときだけレイヤーウィンドウをリサイズする
+fn update_layer_layout(app: &AppHandle, font_size: Option<f64>, chars: Option<usize>) {
+    let (changed, layout) = {
+        let state = app.state::<LayerLayoutState>();
+        let mut layout = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        let next = LayerLayout {
+            font_size: font_size.unwrap_or(layout.font_size),
+            chars: chars.unwrap_or(layout.chars).max(LAYER_MIN_CHARS),
+        };
+        let changed = *layout != next;
+        *layout = next;
+        (changed, next)
+    };
+    if !changed && font_size.is_none() {
+        return;
+    }
+    if let Some(layer) = app.get_webview_window("layer") {
+        let (new_w, new_h) = layer_size_for_font(layout.font_size, layout.chars);
+        if let Err(e) = layer.set_size(tauri::Size::Logical(tauri::LogicalSize {
+            width: new_w,
+            height: new_h,
+        })) {
+            println!("DEBUG: Failed to resize layer window: {}", e);
+        }
+        // リサイズ後にレイヤー位置を再同期
+        sync_layer_to_ctrl(app);
+    }
+}
+
 /// レイヤーディスプレイウィンドウの既定位置とサイズ（論理ピクセル・グローバル座標）を計算する。
 /// 複数モニター環境では main ウィンドウが乗っているモニターを優先する
 fn layer_default_geometry(app: &AppHandle) -> (f64, f64, f64, f64) {
@@ -499,20 +671,26 @@ async fn show_layer_window(app: AppHandle) -> Result<(), String> {
             .map_err(|e| format!("Failed to show layer: {}", e))?;
         w
     } else {
-        tauri::WebviewWindowBuilder::new(&app, "layer", tauri::WebviewUrl::App("layer.html".into()))
-            .title("Lightning Timer Overlay")
-            .inner_size(layer_w, layer_h)
-            .position(default_x, default_y)
-            .resizable(false)
-            .decorations(false)
-            .transparent(true)
-            .shadow(false)
-            .always_on_top(true)
-            .skip_taskbar(true)
-            .focused(false)
-            .visible(true)
-            .build()
-            .map_err(|e| format!("Failed to create layer window: {}", e))?
+        let w = tauri::WebviewWindowBuilder::new(
+            &app,
+            "layer",
+            tauri::WebviewUrl::App("layer.html".into()),
+        )
+        .title("Lightning Timer Overlay")
+        .inner_size(layer_w, layer_h)
+        .position(default_x, default_y)
+        .resizable(false)
+        .decorations(false)
+        .transparent(true)
+        .shadow(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .focused(false)
+        .visible(true)
+        .build()
+        .map_err(|e| format!("Failed to create layer window: {}", e))?;
+        window_state::restore(&w);
+        w
     };
 
     // クリックスルー有効化
@@ -529,7 +707,7 @@ async fn show_layer_window(app: AppHandle) -> Result<(), String> {
             .map_err(|e| format!("Failed to show layer_ctrl: {}", e))?;
         w
     } else {
-        tauri::WebviewWindowBuilder::new(
+        let w = tauri::WebviewWindowBuilder::new(
             &app,
             "layer_ctrl",
             tauri::WebviewUrl::App("layer_ctrl.html".into()),
@@ -547,7 +725,9 @@ async fn show_layer_window(app: AppHandle) -> Result<(), String> {
         .skip_taskbar(true)
         .visible(true)
         .build()
-        .map_err(|e| format!("Failed to create layer_ctrl window: {}", e))?
+        .map_err(|e| format!("Failed to create layer_ctrl window: {}", e))?;
+        window_state::restore(&w);
+        w
     };
 
     #[cfg(target_os = "macos")]
@@ -559,6 +739,80 @@ async fn show_layer_window(app: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// レイヤーの表示テキストの更新イベント
+pub const LAYER_TIMER_EVENT: &str = "layer-timer-update";
+/// レイヤーの文字色・影・フォントサイズの更新イベント
+pub const LAYER_STYLE_EVENT: &str = "layer-style-update";
+
+/// レイヤーの表示テキストの見た目
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum LayerTextStyle {
+    Normal,
+    /// "TIME UP" を点滅させる
+    Timeup,
+    /// 超過時間を色を変えて表示する
+    Overtime,
+}
+
+/// `layer-timer-update` のペイロード。ページは `textContent` に入れるだけでスクリプトとしては扱わない
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LayerText {
+    pub content: String,
+    pub style: LayerTextStyle,
+    pub label: String,
+}
+
+impl LayerText {
+    /// 残り時間・TIME UP・超過時間（"-01:23"）の表示を作る
+    pub fn for_timer(
+        display: ClockTime,
+        show_time_up: bool,
+        overtime: bool,
+        label: Option<String>,
+    ) -> Self {
+        let (content, style) = if overtime {
+            (format!("-{}", display), LayerTextStyle::Overtime)
+        } else if show_time_up {
+            ("TIME UP".to_string(), LayerTextStyle::Timeup)
+        } else {
+            (display.to_string(), LayerTextStyle::Normal)
+        };
+        Self {
+            content,
+            style,
+            label: label.unwrap_or_default(),
+        }
+    }
+}
+
+/// `layer-style-update` のペイロード。値は検証・変換済みの CSS の値
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LayerStyle {
+    pub color: String,
+    pub shadow: String,
+    /// rem 単位
+    pub font_size: f64,
+}
+
+impl LayerStyle {
+    /// 色は16進カラー、影は `light` / それ以外（dark）、フォントサイズは 1〜20rem に収める
+    pub fn sanitized(color: &str, shadow: &str, font_size: f64) -> Self {
+        let shadow = if shadow == "light" {
+            "0 0 8px rgba(255,255,255,0.95), 0 0 16px rgba(255,255,255,0.8), 0 2px 4px rgba(255,255,255,1)"
+        } else {
+            "0 0 8px rgba(0,0,0,0.9), 0 0 16px rgba(0,0,0,0.7), 0 2px 4px rgba(0,0,0,1)"
+        };
+        Self {
+            color: sanitize_hex_color(color),
+            shadow: shadow.to_string(),
+            font_size: font_size.clamp(1.0, 20.0),
+        }
+    }
+}
+
 /// 16進カラー文字列のサニタイズ。妥当でなければ既定値を返す
 fn sanitize_hex_color(color: &str) -> String {
     let trimmed = color.trim();
@@ -572,33 +826,83 @@ fn sanitize_hex_color(color: &str) -> String {
     }
 }
 
+/// レイヤーの表示テキストとラベルを書き換える
+fn set_layer_text(app: &AppHandle, text: LayerText) -> Result<(), String> {
+    if app.get_webview_window("layer").is_some() {
+        if let Err(e) = app.emit_to(
+            tauri::EventTarget::webview_window("layer"),
+            LAYER_TIMER_EVENT,
+            &text,
+        ) {
+            println!("DEBUG: Failed to emit {}: {}", LAYER_TIMER_EVENT, e);
+            return Err(format!("Failed to update layer timer: {}", e));
+        }
+        // 文字数が変わったらウィンドウ幅を合わせる
+        update_layer_layout(app, None, Some(text.content.chars().count()));
+    }
+    Ok(())
+}
+
+/// レイヤーの残り時間表示を更新する。
+/// ラベル未指定でポモドーロやシーケンスの実行中ならフェーズ名・セグメント名を併記する。
+/// 超過計測中（`overtime`）は `display` を超過時間として "-01:23" の形で表示する。
+/// ストップウォッチをレイヤーに表示している間は何もしない。
+pub fn render_layer_timer(
+    app: &AppHandle,
+    display: ClockTime,
+    show_time_up: bool,
+    overtime: bool,
+    label: Option<String>,
+) -> Result<(), String> {
+    if stopwatch::shows_on_layer(app) {
+        return Ok(());
+    }
+    let label = label
+        .or_else(|| pomodoro::active_layer_label(app))
+        .or_else(|| sequence::active_layer_label(app));
+    let mut text = LayerText::for_timer(display, show_time_up, overtime, label);
+    // 点滅させない設定なら "TIME UP" をそのまま表示する
+    if text.style == LayerTextStyle::Timeup && !escalation::layer_flash_enabled(app) {
+        text.style = LayerTextStyle::Normal;
+    }
+    set_layer_text(app, text)
+}
+
+/// レイヤーに経過時間（ストップウォッチ）を表示する
+pub fn render_layer_elapsed(app: &AppHandle, elapsed: std::time::Duration) -> Result<(), String> {
+    set_layer_text(
+        app,
+        LayerText {
+            content: stopwatch::format_elapsed(elapsed),
+            style: LayerTextStyle::Normal,
+            label: "STOPWATCH".to_string(),
+        },
+    )
+}
+
 #[tauri::command]
 async fn update_layer_timer(
     app: AppHandle,
+    hours: Option<u32>,
     minutes: u32,
     seconds: u32,
     show_time_up: bool,
+    overtime: Option<bool>,
+    label: Option<String>,
 ) -> Result<(), String> {
-    if let Some(layer) = app.get_webview_window("layer") {
-        // min/max で値を妥当な範囲に丸める
-        let m = minutes.min(99);
-        let s = seconds.min(99);
-        let content = if show_time_up {
-            "TIME UP".to_string()
-        } else {
-            format!("{:02}:{:02}", m, s)
-        };
-        let class_op = if show_time_up { "add" } else { "remove" };
-        let script = format!(
-            "(function(){{var el=document.getElementById('time');if(!el)return;el.textContent='{}';el.classList.{}('timeup');}})();",
-            content, class_op
-        );
-        if let Err(e) = layer.eval(&script) {
-            println!("DEBUG: Failed to eval layer timer: {}", e);
-            return Err(format!("Failed to update layer timer: {}", e));
-        }
-    }
-    Ok(())
+    // 桁あふれは時・分・秒に正規化する
+    let display = ClockTime::normalize(
+        u64::from(hours.unwrap_or(0)),
+        u64::from(minutes),
+        u64::from(seconds),
+    );
+    render_layer_timer(
+        &app,
+        display,
+        show_time_up,
+        overtime.unwrap_or(false),
+        label,
+    )
 }
 
 #[tauri::command]
@@ -608,37 +912,23 @@ async fn update_layer_style(
     shadow: String,
     font_size: f64,
 ) -> Result<(), String> {
-    let safe_color = sanitize_hex_color(&color);
-    let shadow_value = if shadow == "light" {
-        "0 0 8px rgba(255,255,255,0.95), 0 0 16px rgba(255,255,255,0.8), 0 2px 4px rgba(255,255,255,1)"
-    } else {
-        "0 0 8px rgba(0,0,0,0.9), 0 0 16px rgba(0,0,0,0.7), 0 2px 4px rgba(0,0,0,1)"
-    };
-    let safe_font_size = font_size.clamp(1.0, 20.0);
+    let style = LayerStyle::sanitized(&color, &shadow, font_size);
 
-    if let Some(layer) = app.get_webview_window("layer") {
+    if app.get_webview_window("layer").is_some() {
         // フォントサイズに応じてウィンドウサイズを調整
-        let (new_w, new_h) = layer_size_for_font(safe_font_size);
-        if let Err(e) = layer.set_size(tauri::Size::Logical(tauri::LogicalSize {
-            width: new_w,
-            height: new_h,
-        })) {
-            println!("DEBUG: Failed to resize layer window: {}", e);
-        }
-        // リサイズ後にレイヤー位置を再同期
-        sync_layer_to_ctrl(&app);
-
-        let script = format!(
-            "(function(){{var r=document.documentElement;r.style.setProperty('--layer-color','{}');r.style.setProperty('--layer-shadow','{}');r.style.setProperty('--layer-font-size','{}rem');console.log('[layer] style set via eval',r.style.getPropertyValue('--layer-color'),r.style.getPropertyValue('--layer-font-size'));}})();",
-            safe_color, shadow_value, safe_font_size
-        );
-        if let Err(e) = layer.eval(&script) {
-            println!("DEBUG: Failed to eval layer style: {}", e);
+        update_layer_layout(&app, Some(style.font_size), None);
+
+        if let Err(e) = app.emit_to(
+            tauri::EventTarget::webview_window("layer"),
+            LAYER_STYLE_EVENT,
+            &style,
+        ) {
+            println!("DEBUG: Failed to emit {}: {}", LAYER_STYLE_EVENT, e);
             return Err(format!("Failed to update layer style: {}", e));
         }
         println!(
-            "DEBUG: Layer style updated color={} shadow={} fontSize={}rem size={}x{}",
-            safe_color, shadow, safe_font_size, new_w, new_h
+            "DEBUG: Layer style updated color={} shadow={} fontSize={}rem",
+            style.color, shadow, style.font_size
         );
     }
     Ok(())
@@ -647,6 +937,7 @@ async fn update_layer_style(
 #[tauri::command]
 async fn hide_layer_window(app: AppHandle) -> Result<(), String> {
     println!("DEBUG: hide_layer_window called");
+    window_state::save(&app, &["layer", "layer_ctrl"]);
     if let Some(w) = app.get_webview_window("layer") {
         match w.hide() {
             Ok(()) => println!("DEBUG: layer window hidden"),
@@ -681,6 +972,7 @@ async fn exit_layer_mode(app: AppHandle) -> Result<(), String> {
     ) {
         println!("DEBUG: Failed to emit layer-exit-requested: {}", e);
     }
+    window_state::save(&app, &["layer", "layer_ctrl"]);
     if let Some(w) = app.get_webview_window("layer") {
         if let Err(e) = w.hide() {
             println!("DEBUG: Failed to hide layer: {}", e);
@@ -694,6 +986,33 @@ async fn exit_layer_mode(app: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// レイヤー表示を切り替え、main ウィンドウの状態も合わせる（トレイ・ショートカット用）
+fn toggle_layer_window(app: &AppHandle) {
+    let visible = app
+        .get_webview_window("layer")
+        .and_then(|w| w.is_visible().ok())
+        .unwrap_or(false);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = if visible {
+            exit_layer_mode(app.clone()).await
+        } else {
+            show_layer_window(app.clone()).await.map(|()| {
+                if let Err(e) = app.emit_to(
+                    tauri::EventTarget::webview_window("main"),
+                    "layer-enter-requested",
+                    (),
+                ) {
+                    println!("DEBUG: Failed to emit layer-enter-requested: {}", e);
+                }
+            })
+        };
+        if let Err(e) = result {
+            println!("DEBUG: Failed to toggle layer: {}", e);
+        }
+    });
+}
+
 /// main ウィンドウが乗っているモニターの中心座標 (論理ピクセル) を計算する
 fn center_on_main_monitor(app: &AppHandle, width: f64, height: f64) -> (f64, f64) {
     if let Some(main) = app.get_webview_window("main") {
@@ -752,6 +1071,7 @@ async fn show_settings_window(app: AppHandle) -> Result<(), String> {
     .visible(true)
     .build()
     .map_err(|e| format!("Failed to create settings window: {}", e))?;
+    window_state::restore(&window);
 
     let _ = window.set_focus();
     Ok(())
@@ -760,6 +1080,7 @@ async fn show_settings_window(app: AppHandle) -> Result<(), String> {
 #[tauri::command]
 async fn hide_settings_window(app: AppHandle) -> Result<(), String> {
     println!("DEBUG: hide_settings_window called");
+    window_state::save(&app, &["settings"]);
     if let Some(w) = app.get_webview_window("settings") {
         let _ = w.hide();
     }
@@ -769,45 +1090,216 @@ async fn hide_settings_window(app: AppHandle) -> Result<(), String> {
 #[tauri::command]
 async fn hide_timeup_window(app: AppHandle) -> Result<(), String> {
     println!("DEBUG: hide_timeup_window command called");
-    if let Some(window) = app.get_webview_window("timeup") {
-        println!("DEBUG: Found timeup window, attempting to hide");
-        // ウィンドウを閉じる代わりに非表示にする
-        if window.label() == "timeup" {
-            // ウィンドウを非表示にする
-            window.hide().map_err(|e| {
-                println!("DEBUG: Failed to hide window: {}", e);
-                format!("Failed to hide Time Up window: {}", e)
-            })?;
-            println!("DEBUG: Time Up window hidden successfully");
-        } else {
-            println!("DEBUG: Window label mismatch, not hiding");
-        }
-    } else {
-        println!("DEBUG: Time Up window not found");
+    escalation::cancel(&app);
+    // ウィンドウを閉じる代わりに非表示にする（モニターごとのウィンドウすべて）
+    for window in timeup::timeup_windows(&app) {
+        window.hide().map_err(|e| {
+            println!("DEBUG: Failed to hide window: {}", e);
+            format!("Failed to hide Time Up window: {}", e)
+        })?;
+        println!(
+            "DEBUG: Time Up window {} hidden successfully",
+            window.label()
+        );
     }
     Ok(())
 }
 
+/// main ウィンドウ以外（ショートカットなど）から Time Up を閉じる。
+/// main 側でもアラーム音と TimeUP 表示を止めるよう `timeup-dismissed` を送る
+fn dismiss_timeup(app: &AppHandle) {
+    escalation::cancel(app);
+    for window in timeup::timeup_windows(app) {
+        if let Err(e) = window.hide() {
+            println!("DEBUG: Failed to hide Time Up window: {}", e);
+        }
+    }
+    if let Err(e) = app.emit_to(
+        tauri::EventTarget::webview_window("main"),
+        "timeup-dismissed",
+        (),
+    ) {
+        println!("DEBUG: Failed to emit timeup-dismissed: {}", e);
+    }
+}
+
 fn main() {
+    // 引数の誤りは起動前に知らせる
+    let launch_options = match launch::options_from_env() {
+        Ok(options) => options,
+        Err(code) => std::process::exit(code),
+    };
+
+    // 2 つ目の起動なら引数を先に起動したプロセスへ渡して終了する。
+    // ロックはプロセスが終わるまで保持する
+    let _instance_lock = match instance::acquire() {
+        Ok(Some(lock)) => Some(lock),
+        Ok(None) => {
+            let args = launch_options.to_args();
+            println!("DEBUG: Already running, forwarding args: {:?}", args);
+            if let Err(e) = instance::forward_to_primary(args) {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Err(e) => {
+            println!("DEBUG: Failed to acquire instance lock, continuing: {}", e);
+            None
+        }
+    };
+
     tauri::Builder::default()
         .plugin(StoreBuilder::default().build())
-        .setup(|app| {
+        .plugin(tauri_plugin_notification::init())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(shortcut::handle_shortcut)
+                .build(),
+        )
+        .manage(TimerRegistryState::default())
+        .manage(TimerRestoreState::default())
+        .manage(PomodoroState::default())
+        .manage(SequenceState::default())
+        .manage(StopwatchState::default())
+        .manage(LayerLayoutState::default())
+        .manage(TimeupState::default())
+        .manage(WindowStatesState::default())
+        .manage(AlarmState::default())
+        .manage(ResyncState::default())
+        .manage(TimeupScreenState::default())
+        .manage(EscalationState::default())
+        .manage(TrayState::default())
+        .manage(ShortcutRegistryState::default())
+        .manage(HttpApiState::default())
+        .manage(EventStreamState::default())
+        .manage(LaunchState::default())
+        .setup(move |app| {
+            if let Err(e) = resync::load_policy(app.handle()) {
+                println!("DEBUG: Failed to load missed alarm policy: {}", e);
+            }
+            // 前回終了時のタイマー状態を復元
+            if let Err(e) = timer::restore_timer_state(app.handle()) {
+                println!("DEBUG: Failed to restore timer state: {}", e);
+            }
+            // カウントダウンはバックエンドのティッカーが駆動する
+            timer::spawn_ticker(app.handle().clone());
+            stopwatch::spawn_ticker(app.handle().clone());
+            if let Err(e) = alarm::load_alarms(app.handle()) {
+                println!("DEBUG: Failed to load alarms: {}", e);
+            }
+            alarm::spawn_scheduler(app.handle().clone());
+            // main ウィンドウを隠していても操作できるようトレイを常駐させる
+            if let Err(e) = tray::create_tray(app.handle()) {
+                println!("DEBUG: Failed to create tray icon: {}", e);
+            }
+            shortcut::register_saved_shortcuts(app.handle());
+            // 外部ツール向けの HTTP API（設定で有効にした場合のみ）
+            http_api::start_if_enabled(app.handle());
+            event_stream::start_if_enabled(app.handle());
+            // lightning-timer-cli からの操作を受け付ける
+            control_server::spawn_listener(app.handle().clone());
+
+            window_state::load(app.handle());
             if let Some(window) = app.get_webview_window("main") {
-                // ウィンドウ状態を復元
-                if let Err(e) = restore_window_state(&window) {
-                    println!("DEBUG: Failed to restore window state: {}", e);
-                }
+                // ウィンドウ状態を復元（ほかのウィンドウは作成時に復元する）
+                window_state::restore(&window);
                 // ウィンドウはtauri.conf.jsonで自動的に表示されるため、手動でshow()は不要
             }
+            // モニターの抜き差しでその構成の配置に切り替える
+            window_state::spawn_monitor_watch(app.handle().clone());
+            // --start / --preset / --minimized など（レイヤー表示と最前面表示はフロントエンドが反映）
+            launch::apply_on_startup(app.handle(), &launch_options);
             Ok(())
         })
-               .invoke_handler(tauri::generate_handler![open_devtools, save_timer_state_on_exit, exit_app, start_drag, save_window_position, set_window_size, set_window_resizable, focus_window, get_available_port, show_timeup_window, hide_timeup_window, show_layer_window, hide_layer_window, update_layer_style, update_layer_timer, exit_layer_mode, show_settings_window, hide_settings_window])
+        .invoke_handler(tauri::generate_handler![
+            open_devtools,
+            save_timer_state_on_exit,
+            exit_app,
+            start_drag,
+            save_window_position,
+            set_window_size,
+            set_window_resizable,
+            focus_window,
+            get_available_port,
+            show_timeup_window,
+            timeup_current,
+            hide_timeup_window,
+            show_layer_window,
+            hide_layer_window,
+            update_layer_style,
+            update_layer_timer,
+            exit_layer_mode,
+            show_settings_window,
+            hide_settings_window,
+            timer::timer_set,
+            timer::timer_start,
+            timer::timer_pause,
+            timer::timer_resume,
+            timer::timer_reset,
+            timer::timer_get_state,
+            timer::timer_list,
+            timer::timer_create,
+            timer::timer_remove,
+            timer::timer_rename,
+            timer::timer_set_alarm,
+            timer::timer_set_overtime,
+            timer::timer_restore_report,
+            pomodoro::pomodoro_get_config,
+            pomodoro::pomodoro_set_config,
+            pomodoro::pomodoro_start,
+            pomodoro::pomodoro_skip,
+            pomodoro::pomodoro_stop,
+            pomodoro::pomodoro_get_state,
+            sequence::sequence_list,
+            sequence::sequence_save,
+            sequence::sequence_delete,
+            sequence::sequence_start,
+            sequence::sequence_next,
+            sequence::sequence_previous,
+            sequence::sequence_stop,
+            sequence::sequence_get_state,
+            stopwatch::stopwatch_start,
+            stopwatch::stopwatch_pause,
+            stopwatch::stopwatch_reset,
+            stopwatch::stopwatch_lap,
+            stopwatch::stopwatch_get_state,
+            stopwatch::stopwatch_set_layer,
+            session::session_list,
+            session::session_clear,
+            alarm::alarm_list,
+            alarm::alarm_save,
+            alarm::alarm_delete,
+            alarm::alarm_next_fire,
+            preset::preset_list,
+            preset::preset_save,
+            preset::preset_delete,
+            preset::preset_reorder,
+            preset::preset_launch,
+            shortcut::shortcut_get_config,
+            shortcut::shortcut_set_config,
+            shortcut::shortcut_conflicts,
+            launch::launch_take_options,
+            window_state::window_set_display_mode,
+            window_state::window_saved_display_mode,
+            http_api::http_api_get_status,
+            http_api::http_api_set_config,
+            event_stream::event_stream_get_status,
+            event_stream::event_stream_set_config,
+            resync::resync_get_policy,
+            resync::resync_set_policy,
+            timeup::timeup_set_screen,
+            timeup::timeup_list_monitors,
+            escalation::escalation_set_policy,
+            escalation::escalation_snooze
+        ])
         .on_window_event(|window, event| {
             match event {
                 WindowEvent::CloseRequested { api, .. } => {
                     // settings ウィンドウは破棄せず非表示にして使い回す
                     if window.label() == "settings" {
                         api.prevent_close();
+                        window_state::save(window.app_handle(), &["settings"]);
                         let _ = window.hide();
                         return;
                     }
@@ -815,31 +1307,29 @@ fn main() {
                     if window.label() != "main" {
                         return;
                     }
-                    // タイマー状態保存を促す
-                    if let Some(main_window) = window.app_handle().get_webview_window("main") {
-                        let _ = main_window.eval("if (window.__TAURI__) { window.__TAURI__.core.invoke('save_timer_state_on_exit'); }");
-                    }
-
-                    // ウィンドウが閉じられる前に状態を保存
-                    if let Some(main_window) = window.app_handle().get_webview_window("main") {
-                        if let Err(e) = save_window_state(&main_window) {
-                            println!("DEBUG: Failed to save window state: {}", e);
-                        }
-                    }
                     // メインウィンドウが閉じられた際にアプリケーション全体を終了
-                    std::process::exit(0);
+                    // （状態の保存などは `RunEvent::Exit` で行う）
+                    window.app_handle().exit(0);
                 }
-                WindowEvent::Moved(_) => {
+                WindowEvent::Moved(_) | WindowEvent::Resized(_) => {
                     // 操作ハンドルが動いたらディスプレイも追従させる
-                    if window.label() == "layer_ctrl" {
+                    if window.label() == "layer_ctrl" && matches!(event, WindowEvent::Moved(_)) {
                         sync_layer_to_ctrl(window.app_handle());
                     }
+                    window_state::remember(window.app_handle(), window.label());
                 }
                 _ => {}
             }
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| match event {
+            tauri::RunEvent::Exit => shutdown(app),
+            // macOS では lightning-timer:// リンクが引数ではなくイベントで届く
+            #[cfg(target_os = "macos")]
+            tauri::RunEvent::Opened { urls } => deep_link::open_urls(app, &urls),
+            _ => {}
+        });
 }
 
 #[cfg(test)]
@@ -0,0 +1,368 @@
+//! ポモドーロ（作業 / 短い休憩 / 長い休憩）のサイクルモード。
+//!
+//! 既定タイマーを使ってバックエンド側でフェーズを進めるため、
+//! main ウィンドウが非表示でもサイクルが継続する。
+//! フェーズが切り替わるたびに `pomodoro-phase-changed` を emit し、
+//! レイヤー表示にはフェーズ名（"BREAK" など）を併記する。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::duration::ClockTime;
+use crate::timer::{self, NamedTimerSnapshot, TimerAlarm, TimerRegistryState, DEFAULT_TIMER_ID};
+
+pub const POMODORO_PHASE_CHANGED_EVENT: &str = "pomodoro-phase-changed";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PomodoroConfig {
+    pub work_secs: u64,
+    pub short_break_secs: u64,
+    pub long_break_secs: u64,
+    /// 長い休憩までの作業回数
+    pub rounds_before_long_break: u32,
+    /// フェーズ終了時に次のフェーズを自動で開始するか
+    pub auto_advance: bool,
+    /// 休憩 → 作業の切り替わりではフルスクリーンの Time Up ウィンドウを出さない
+    pub skip_timeup_on_break_end: bool,
+}
+
+impl Default for PomodoroConfig {
+    fn default() -> Self {
+        Self {
+            work_secs: 25 * 60,
+            short_break_secs: 5 * 60,
+            long_break_secs: 15 * 60,
+            rounds_before_long_break: 4,
+            auto_advance: true,
+            skip_timeup_on_break_end: true,
+        }
+    }
+}
+
+impl PomodoroConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.work_secs == 0 || self.short_break_secs == 0 || self.long_break_secs == 0 {
+            return Err("Pomodoro phase lengths must be greater than zero".to_string());
+        }
+        if self.rounds_before_long_break == 0 {
+            return Err("Rounds before a long break must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum PomodoroPhase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl PomodoroPhase {
+    pub fn is_break(self) -> bool {
+        self != PomodoroPhase::Work
+    }
+
+    /// レイヤー表示に併記するラベル
+    pub fn layer_label(self) -> &'static str {
+        match self {
+            PomodoroPhase::Work => "WORK",
+            PomodoroPhase::ShortBreak => "BREAK",
+            PomodoroPhase::LongBreak => "LONG BREAK",
+        }
+    }
+}
+
+/// サイクルの進行状態
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PomodoroCycle {
+    pub config: PomodoroConfig,
+    pub phase: PomodoroPhase,
+    /// 現在（または直前）の作業が何回目か（1 始まり）
+    pub round: u32,
+    /// 開始前の既定タイマーのアラーム設定。停止時に戻す
+    #[serde(skip)]
+    pub saved_alarm: TimerAlarm,
+}
+
+impl PomodoroCycle {
+    pub fn new(config: PomodoroConfig) -> Self {
+        Self {
+            config,
+            phase: PomodoroPhase::Work,
+            round: 1,
+            saved_alarm: TimerAlarm::default(),
+        }
+    }
+
+    /// 開始前の既定タイマーのアラーム設定を覚えておく
+    pub fn saving_alarm(mut self, alarm: &TimerAlarm) -> Self {
+        self.saved_alarm = alarm.clone();
+        self
+    }
+
+    /// このフェーズで使うアラーム設定。変えるのは Time Up ウィンドウだけで、
+    /// 音や超過計測は利用者の設定のまま
+    pub fn phase_alarm(&self, current: &TimerAlarm) -> TimerAlarm {
+        TimerAlarm {
+            show_timeup_window: self.saved_alarm.show_timeup_window && self.shows_timeup_at_end(),
+            ..current.clone()
+        }
+    }
+
+    /// 停止時のアラーム設定。サイクルが変えた Time Up ウィンドウを開始前の値に戻す
+    /// （実行中に設定画面で変えた音や超過計測はそのまま残す）
+    pub fn restored_alarm(&self, current: &TimerAlarm) -> TimerAlarm {
+        TimerAlarm {
+            show_timeup_window: self.saved_alarm.show_timeup_window,
+            ..current.clone()
+        }
+    }
+
+    pub fn phase_duration(&self) -> Duration {
+        Duration::from_secs(match self.phase {
+            PomodoroPhase::Work => self.config.work_secs,
+            PomodoroPhase::ShortBreak => self.config.short_break_secs,
+            PomodoroPhase::LongBreak => self.config.long_break_secs,
+        })
+    }
+
+    /// 次のフェーズへ進める
+    pub fn advance(&mut self) {
+        match self.phase {
+            PomodoroPhase::Work => {
+                self.phase = if self
+                    .round
+                    .is_multiple_of(self.config.rounds_before_long_break)
+                {
+                    PomodoroPhase::LongBreak
+                } else {
+                    PomodoroPhase::ShortBreak
+                };
+            }
+            PomodoroPhase::ShortBreak | PomodoroPhase::LongBreak => {
+                self.phase = PomodoroPhase::Work;
+                self.round += 1;
+            }
+        }
+    }
+
+    /// 現在のフェーズが終わったときに Time Up ウィンドウを表示するか
+    pub fn shows_timeup_at_end(&self) -> bool {
+        !(self.phase.is_break() && self.config.skip_timeup_on_break_end)
+    }
+}
+
+/// 実行中のサイクル（停止中は `None`）
+#[derive(Default)]
+pub struct PomodoroState(pub Mutex<Option<PomodoroCycle>>);
+
+pub fn load_pomodoro_config() -> PomodoroConfig {
+    fs::read_to_string(crate::get_pomodoro_config_path())
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_pomodoro_config(config: &PomodoroConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let json = serde_json::to_string_pretty(config)?;
+    fs::write(crate::get_pomodoro_config_path(), json)?;
+    Ok(())
+}
+
+/// 実行中ならレイヤー表示用のフェーズ名を返す
+pub fn active_layer_label(app: &AppHandle) -> Option<String> {
+    let state = app.state::<PomodoroState>();
+    let cycle = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    cycle.as_ref().map(|c| c.phase.layer_label().to_string())
+}
+
+/// 既定タイマーのアラーム設定を開始前に戻す。`reset` なら時間もリセットする
+fn restore_default_timer(
+    app: &AppHandle,
+    cycle: &PomodoroCycle,
+    reset: bool,
+) -> Result<(), String> {
+    let registry = app.state::<TimerRegistryState>();
+    timer::update_timer(
+        app,
+        &registry,
+        Some(DEFAULT_TIMER_ID.to_string()),
+        |t, _| {
+            t.alarm = cycle.restored_alarm(&t.alarm);
+            if reset {
+                t.engine.reset();
+            }
+        },
+    )?;
+    if let Err(e) = app.emit(POMODORO_PHASE_CHANGED_EVENT, Option::<PomodoroCycle>::None) {
+        println!(
+            "DEBUG: Failed to emit {}: {}",
+            POMODORO_PHASE_CHANGED_EVENT, e
+        );
+    }
+    Ok(())
+}
+
+/// 実行中のサイクルを破棄する（プリセット起動時など）。既定タイマーの時間はそのままで、
+/// アラーム設定だけ開始前に戻す
+pub fn clear(app: &AppHandle) {
+    let state = app.state::<PomodoroState>();
+    let previous = state.0.lock().unwrap_or_else(|e| e.into_inner()).take();
+    if let Some(cycle) = previous {
+        if let Err(e) = restore_default_timer(app, &cycle, false) {
+            println!("DEBUG: Failed to restore timer after pomodoro: {}", e);
+        }
+    }
+}
+
+/// 既定タイマーを現在のフェーズに合わせて設定し、必要なら開始する
+fn apply_phase(app: &AppHandle, cycle: &PomodoroCycle, start: bool) -> Result<(), String> {
+    let duration = cycle.phase_duration();
+    let registry = app.state::<TimerRegistryState>();
+    timer::update_timer(
+        app,
+        &registry,
+        Some(DEFAULT_TIMER_ID.to_string()),
+        |t, now| {
+            t.alarm = cycle.phase_alarm(&t.alarm);
+            t.engine.set(duration);
+            if start {
+                t.engine.start(now);
+            }
+        },
+    )?;
+
+    if let Err(e) = app.emit(POMODORO_PHASE_CHANGED_EVENT, cycle) {
+        println!(
+            "DEBUG: Failed to emit {}: {}",
+            POMODORO_PHASE_CHANGED_EVENT, e
+        );
+    }
+    if let Err(e) = crate::render_layer_timer(
+        app,
+        ClockTime::from_secs(duration.as_secs()),
+        false,
+        false,
+        Some(cycle.phase.layer_label().to_string()),
+    ) {
+        println!("DEBUG: Failed to update layer for pomodoro: {}", e);
+    }
+    Ok(())
+}
+
+/// ティッカーから既定タイマーの終了時に呼ばれ、次のフェーズへ進める
+pub fn on_timer_finished(app: &AppHandle, snapshot: &NamedTimerSnapshot) {
+    if snapshot.id != DEFAULT_TIMER_ID {
+        return;
+    }
+    let next = {
+        let state = app.state::<PomodoroState>();
+        let mut cycle = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        match cycle.as_mut() {
+            Some(cycle) => {
+                cycle.advance();
+                cycle.clone()
+            }
+            None => return,
+        }
+    };
+    println!(
+        "DEBUG: Pomodoro advanced to {:?} (round {})",
+        next.phase, next.round
+    );
+    if let Err(e) = apply_phase(app, &next, next.config.auto_advance) {
+        println!("DEBUG: Failed to advance pomodoro: {}", e);
+    }
+}
+
+#[tauri::command]
+pub async fn pomodoro_get_config() -> Result<PomodoroConfig, String> {
+    Ok(load_pomodoro_config())
+}
+
+#[tauri::command]
+pub async fn pomodoro_set_config(config: PomodoroConfig) -> Result<(), String> {
+    config.validate()?;
+    save_pomodoro_config(&config).map_err(|e| format!("Failed to save pomodoro config: {}", e))
+}
+
+/// 保存済み設定（または引数の設定）で作業フェーズから開始する
+#[tauri::command]
+pub async fn pomodoro_start(
+    app: AppHandle,
+    state: State<'_, PomodoroState>,
+    config: Option<PomodoroConfig>,
+) -> Result<PomodoroCycle, String> {
+    let config = config.unwrap_or_else(load_pomodoro_config);
+    config.validate()?;
+    // 既定タイマーを奪い合わないようシーケンスは止める
+    crate::sequence::clear(&app);
+    let alarm = timer::timer_snapshot(&app, None)?.alarm;
+    let cycle = PomodoroCycle::new(config).saving_alarm(&alarm);
+    *state
+        .0
+        .lock()
+        .map_err(|e| format!("Pomodoro state lock poisoned: {}", e))? = Some(cycle.clone());
+    apply_phase(&app, &cycle, true)?;
+    Ok(cycle)
+}
+
+/// 現在のフェーズを打ち切って次のフェーズへ進む
+#[tauri::command]
+pub async fn pomodoro_skip(
+    app: AppHandle,
+    state: State<'_, PomodoroState>,
+) -> Result<PomodoroCycle, String> {
+    let next = {
+        let mut cycle = state
+            .0
+            .lock()
+            .map_err(|e| format!("Pomodoro state lock poisoned: {}", e))?;
+        let cycle = cycle.as_mut().ok_or("Pomodoro is not running")?;
+        cycle.advance();
+        cycle.clone()
+    };
+    apply_phase(&app, &next, next.config.auto_advance)?;
+    Ok(next)
+}
+
+/// サイクルを終了し、既定タイマーをリセットしてアラーム設定を開始前に戻す
+#[tauri::command]
+pub async fn pomodoro_stop(app: AppHandle, state: State<'_, PomodoroState>) -> Result<(), String> {
+    let previous = state
+        .0
+        .lock()
+        .map_err(|e| format!("Pomodoro state lock poisoned: {}", e))?
+        .take();
+    match previous {
+        Some(cycle) => restore_default_timer(&app, &cycle, true),
+        None => {
+            let registry = app.state::<TimerRegistryState>();
+            timer::update_timer(
+                &app,
+                &registry,
+                Some(DEFAULT_TIMER_ID.to_string()),
+                |t, _| t.engine.reset(),
+            )?;
+            Ok(())
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn pomodoro_get_state(
+    state: State<'_, PomodoroState>,
+) -> Result<Option<PomodoroCycle>, String> {
+    let cycle = state
+        .0
+        .lock()
+        .map_err(|e| format!("Pomodoro state lock poisoned: {}", e))?;
+    Ok(cycle.clone())
+}
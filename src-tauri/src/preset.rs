@@ -0,0 +1,237 @@
+//! よく使う時間を名前付きで保存するプリセット（例: "Tea 3:00"）。
+//!
+//! プリセットはアプリデータディレクトリの `presets.json` に並び順どおり保存する。
+//! 起動すると既定タイマーにその時間を設定して開始する。
+//! トレイメニュー・グローバルショートカット（プリセットごとの `shortcut`）・CLI からは `launch_preset` を呼ぶ。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::duration::{ClockTime, MAX_DURATION_SECS};
+use crate::timer::{self, NamedTimerSnapshot, TimerAlarm, TimerRegistryState, DEFAULT_TIMER_ID};
+
+pub const PRESETS_CHANGED_EVENT: &str = "presets-changed";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Preset {
+    /// 空なら保存時に採番する
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub duration_secs: u64,
+    /// 起動時に適用するアラーム。`None` なら既定タイマーの現在の設定を使う
+    #[serde(default)]
+    pub alarm: Option<TimerAlarm>,
+    /// 起動するグローバルショートカット（例: "CommandOrControl+Alt+1"）。`None` や空文字列なら割り当てない
+    #[serde(default)]
+    pub shortcut: Option<String>,
+}
+
+impl Preset {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Preset name must not be empty".to_string());
+        }
+        if self.duration_secs == 0 || self.duration_secs > MAX_DURATION_SECS {
+            return Err(format!(
+                "Preset duration must be between 1 and {} seconds",
+                MAX_DURATION_SECS
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 並び順を保持したプリセット一覧
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct PresetLibrary {
+    pub presets: Vec<Preset>,
+}
+
+impl PresetLibrary {
+    fn next_id(&self) -> String {
+        let n = self
+            .presets
+            .iter()
+            .filter_map(|p| p.id.strip_prefix("preset-"))
+            .filter_map(|n| n.parse::<u64>().ok())
+            .max()
+            .unwrap_or(0);
+        format!("preset-{}", n + 1)
+    }
+
+    /// ID または名前（大文字小文字を区別しない）で探す。CLI の `preset tea` などに使う
+    pub fn find(&self, key: &str) -> Option<&Preset> {
+        let key = key.trim();
+        self.presets.iter().find(|p| p.id == key).or_else(|| {
+            self.presets
+                .iter()
+                .find(|p| p.name.trim().eq_ignore_ascii_case(key))
+        })
+    }
+
+    /// 追加または更新する。ID が空なら末尾に新規追加する
+    pub fn save(&mut self, mut preset: Preset) -> Result<Preset, String> {
+        preset.validate()?;
+        preset.name = preset.name.trim().to_string();
+        if preset.id.is_empty() {
+            preset.id = self.next_id();
+            self.presets.push(preset.clone());
+        } else {
+            let existing = self
+                .presets
+                .iter_mut()
+                .find(|p| p.id == preset.id)
+                .ok_or_else(|| format!("Preset not found: {}", preset.id))?;
+            *existing = preset.clone();
+        }
+        Ok(preset)
+    }
+
+    pub fn remove(&mut self, id: &str) -> Result<(), String> {
+        let before = self.presets.len();
+        self.presets.retain(|p| p.id != id);
+        if self.presets.len() == before {
+            return Err(format!("Preset not found: {}", id));
+        }
+        Ok(())
+    }
+
+    /// 指定した ID の順に並べ替える。既存の ID をちょうど 1 回ずつ含む必要がある
+    pub fn reorder(&mut self, ids: &[String]) -> Result<(), String> {
+        if ids.len() != self.presets.len() {
+            return Err(format!(
+                "Expected {} preset ids, got {}",
+                self.presets.len(),
+                ids.len()
+            ));
+        }
+        for (i, id) in ids.iter().enumerate() {
+            if ids[..i].contains(id) || !self.presets.iter().any(|p| &p.id == id) {
+                return Err(format!("Unknown or duplicate preset id: {}", id));
+            }
+        }
+        self.presets
+            .sort_by_key(|p| ids.iter().position(|id| *id == p.id));
+        Ok(())
+    }
+}
+
+pub fn load_presets() -> Result<PresetLibrary, Box<dyn std::error::Error>> {
+    let path = crate::get_presets_path();
+    if !path.exists() {
+        return Ok(PresetLibrary::default());
+    }
+    let json = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+fn write_presets(library: &PresetLibrary) -> Result<(), Box<dyn std::error::Error>> {
+    let json = serde_json::to_string_pretty(library)?;
+    fs::write(crate::get_presets_path(), json)?;
+    Ok(())
+}
+
+/// 一覧を保存して `presets-changed` を emit する
+fn commit_presets(app: &AppHandle, library: &PresetLibrary) -> Result<(), String> {
+    write_presets(library).map_err(|e| format!("Failed to save presets: {}", e))?;
+    if let Err(e) = app.emit(PRESETS_CHANGED_EVENT, &library.presets) {
+        println!("DEBUG: Failed to emit {}: {}", PRESETS_CHANGED_EVENT, e);
+    }
+    crate::tray::refresh_menu(app);
+    crate::shortcut::register_saved_shortcuts(app);
+    Ok(())
+}
+
+/// 一覧を読み込んで変更し、保存する
+fn modify_presets<T>(
+    app: &AppHandle,
+    f: impl FnOnce(&mut PresetLibrary) -> Result<T, String>,
+) -> Result<T, String> {
+    let mut library = load_presets().map_err(|e| format!("Failed to load presets: {}", e))?;
+    let result = f(&mut library)?;
+    commit_presets(app, &library)?;
+    Ok(result)
+}
+
+/// プリセットを ID または名前で探し、既定タイマーに設定して開始する
+pub fn launch_preset(app: &AppHandle, key: &str) -> Result<NamedTimerSnapshot, String> {
+    let library = load_presets().map_err(|e| format!("Failed to load presets: {}", e))?;
+    let preset = library
+        .find(key)
+        .cloned()
+        .ok_or_else(|| format!("Preset not found: {}", key))?;
+    println!(
+        "DEBUG: Launching preset {} ({}s)",
+        preset.name, preset.duration_secs
+    );
+
+    start_default_timer(app, preset.duration_secs, preset.alarm.as_ref())
+}
+
+/// 既定タイマーに時間を設定して開始する。ポモドーロやシーケンスの実行中なら止める。
+/// プリセット・CLI の `start 5m` から使う
+pub fn start_default_timer(
+    app: &AppHandle,
+    duration_secs: u64,
+    alarm: Option<&TimerAlarm>,
+) -> Result<NamedTimerSnapshot, String> {
+    crate::pomodoro::clear(app);
+    crate::sequence::clear(app);
+    let registry = app.state::<TimerRegistryState>();
+    let snapshot = timer::update_timer(
+        app,
+        &registry,
+        Some(DEFAULT_TIMER_ID.to_string()),
+        |t, now| {
+            if let Some(alarm) = alarm {
+                t.alarm = alarm.clone();
+            }
+            t.engine.set(Duration::from_secs(duration_secs));
+            t.engine.start(now);
+        },
+    )?;
+
+    let display = ClockTime::from_secs(duration_secs);
+    if let Err(e) = crate::render_layer_timer(app, display, false, false, None) {
+        println!("DEBUG: Failed to update layer for timer start: {}", e);
+    }
+    Ok(snapshot)
+}
+
+#[tauri::command]
+pub async fn preset_list() -> Result<Vec<Preset>, String> {
+    load_presets()
+        .map(|library| library.presets)
+        .map_err(|e| format!("Failed to load presets: {}", e))
+}
+
+/// プリセットを追加・更新する。ID が空なら新規作成として末尾に追加する
+#[tauri::command]
+pub async fn preset_save(app: AppHandle, preset: Preset) -> Result<Preset, String> {
+    modify_presets(&app, |library| library.save(preset))
+}
+
+#[tauri::command]
+pub async fn preset_delete(app: AppHandle, id: String) -> Result<(), String> {
+    modify_presets(&app, |library| library.remove(&id))
+}
+
+/// 並び順を ID の配列で指定する
+#[tauri::command]
+pub async fn preset_reorder(app: AppHandle, ids: Vec<String>) -> Result<Vec<Preset>, String> {
+    modify_presets(&app, |library| {
+        library.reorder(&ids)?;
+        Ok(library.presets.clone())
+    })
+}
+
+/// ID または名前でプリセットを起動する
+#[tauri::command]
+pub async fn preset_launch(app: AppHandle, key: String) -> Result<NamedTimerSnapshot, String> {
+    launch_preset(&app, &key)
+}
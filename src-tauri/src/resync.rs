@@ -0,0 +1,277 @@
+//! スリープ復帰や壁時計の変更を検出してタイマーを締切に合わせ直す。
+//!
+//! Linux / macOS の `Instant` はスリープ中に進まないため、そのままでは
+//! スリープした時間だけカウントダウンが遅れる。ティッカーは毎回
+//! `Instant` とスリープ中も進む起動時計（Linux は `CLOCK_BOOTTIME`、
+//! macOS は `mach_continuous_time`、Windows は `GetTickCount64`）の進み方を比べ、
+//! 起動時計だけが大きく進んでいればスリープしていたとみなして締切を前倒しする。
+//! 壁時計はユーザーや NTP が動かすので、スリープの判定には使わない。
+//! 補正したとき・壁時計の変更を検出したときは `timer-resynced` を emit する。
+//!
+//! スリープ中に締切を過ぎたタイマーやアラームは「取りこぼし」として扱い、
+//! 設定に応じて遅れて 1 回だけ鳴らすか、鳴らさずに終了させる。
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_store::StoreExt;
+
+pub const TIMER_RESYNCED_EVENT: &str = "timer-resynced";
+
+/// 時計どうしのずれがこれを超えたら補正・通知する
+pub const RESYNC_THRESHOLD: Duration = Duration::from_secs(2);
+
+/// 締切からこれ以上遅れて終了したタイマーは取りこぼしとみなす
+pub const MISSED_TIMER_GRACE: Duration = Duration::from_secs(5);
+
+/// 現在時刻の取得元。テストでは任意の時刻を返す実装に差し替える
+pub trait Clock {
+    fn monotonic(&self) -> Instant;
+    fn wall(&self) -> SystemTime;
+    /// 起動からの経過時間（スリープ中も進む）。取れない環境では `None` で、スリープを補正しない
+    fn boot_time(&self) -> Option<Duration>;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn monotonic(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn wall(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn boot_time(&self) -> Option<Duration> {
+        boot_time()
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn boot_time() -> Option<Duration> {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: `ts` は有効な書き込み先
+    if unsafe { libc::clock_gettime(libc::CLOCK_BOOTTIME, &mut ts) } != 0 {
+        return None;
+    }
+    Some(Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32))
+}
+
+#[cfg(target_os = "macos")]
+fn boot_time() -> Option<Duration> {
+    #[repr(C)]
+    struct MachTimebaseInfo {
+        numer: u32,
+        denom: u32,
+    }
+    extern "C" {
+        fn mach_continuous_time() -> u64;
+        fn mach_timebase_info(info: *mut MachTimebaseInfo) -> i32;
+    }
+    let mut info = MachTimebaseInfo { numer: 0, denom: 0 };
+    // SAFETY: `info` は有効な書き込み先で、どちらも引数以外に触れない
+    let ticks = unsafe {
+        if mach_timebase_info(&mut info) != 0 || info.denom == 0 {
+            return None;
+        }
+        mach_continuous_time()
+    };
+    let nanos = ticks as u128 * info.numer as u128 / info.denom as u128;
+    Some(Duration::from_nanos(nanos as u64))
+}
+
+#[cfg(windows)]
+fn boot_time() -> Option<Duration> {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetTickCount64() -> u64;
+    }
+    // SAFETY: 引数を取らず、失敗しない
+    Some(Duration::from_millis(unsafe { GetTickCount64() }))
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    windows
+)))]
+fn boot_time() -> Option<Duration> {
+    None
+}
+
+/// 前回の確認から検出した時計の飛び
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClockJump {
+    /// 起動時計だけが進んだ。スリープしていた時間
+    Suspended(Duration),
+    /// 単調時計ごと止まっていた（スリープ中も進む単調時計の環境での復帰など）
+    Stalled(Duration),
+    /// 壁時計が進められた。カウントダウンは単調時計基準なので補正しない
+    WallClockMovedForward(Duration),
+    /// 壁時計が巻き戻った。カウントダウンは単調時計基準なので補正しない
+    WallClockMovedBack(Duration),
+}
+
+impl ClockJump {
+    pub fn reason(&self) -> ResyncReason {
+        match self {
+            ClockJump::Suspended(_) => ResyncReason::Suspended,
+            ClockJump::Stalled(_) => ResyncReason::Stalled,
+            ClockJump::WallClockMovedForward(_) => ResyncReason::WallClockMovedForward,
+            ClockJump::WallClockMovedBack(_) => ResyncReason::WallClockMovedBack,
+        }
+    }
+
+    pub fn amount(&self) -> Duration {
+        match *self {
+            ClockJump::Suspended(d)
+            | ClockJump::Stalled(d)
+            | ClockJump::WallClockMovedForward(d)
+            | ClockJump::WallClockMovedBack(d) => d,
+        }
+    }
+}
+
+/// 3 つの時計を前回確認した時点から比べて飛びを検出する
+#[derive(Debug)]
+pub struct ClockWatch {
+    last_monotonic: Instant,
+    last_wall: SystemTime,
+    last_boot: Option<Duration>,
+    /// 確認間隔の想定上限。これを大きく超えて単調時計が進んだら `Stalled`
+    expected_interval: Duration,
+}
+
+impl ClockWatch {
+    pub fn new(clock: &impl Clock, expected_interval: Duration) -> Self {
+        Self {
+            last_monotonic: clock.monotonic(),
+            last_wall: clock.wall(),
+            last_boot: clock.boot_time(),
+            expected_interval,
+        }
+    }
+
+    pub fn check(&mut self, clock: &impl Clock) -> Option<ClockJump> {
+        let monotonic = clock.monotonic();
+        let wall = clock.wall();
+        let boot = clock.boot_time();
+        let mono_elapsed = monotonic.saturating_duration_since(self.last_monotonic);
+        let wall_elapsed = wall.duration_since(self.last_wall);
+        // `Instant` がスリープ中も進む環境では差が出ないので補正しない
+        let suspended = match (boot, self.last_boot) {
+            (Some(boot), Some(last_boot)) => {
+                boot.saturating_sub(last_boot).saturating_sub(mono_elapsed)
+            }
+            _ => Duration::ZERO,
+        };
+        self.last_monotonic = monotonic;
+        self.last_wall = wall;
+        self.last_boot = boot;
+
+        if suspended > RESYNC_THRESHOLD {
+            return Some(ClockJump::Suspended(suspended));
+        }
+        match wall_elapsed {
+            Ok(_) if mono_elapsed > self.expected_interval + RESYNC_THRESHOLD => {
+                Some(ClockJump::Stalled(mono_elapsed))
+            }
+            Ok(wall_elapsed) if wall_elapsed > mono_elapsed + RESYNC_THRESHOLD => Some(
+                ClockJump::WallClockMovedForward(wall_elapsed - mono_elapsed),
+            ),
+            Ok(wall_elapsed) if mono_elapsed > wall_elapsed + RESYNC_THRESHOLD => {
+                Some(ClockJump::WallClockMovedBack(mono_elapsed - wall_elapsed))
+            }
+            Err(e) => Some(ClockJump::WallClockMovedBack(e.duration() + mono_elapsed)),
+            Ok(_) => None,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ResyncReason {
+    Suspended,
+    Stalled,
+    WallClockMovedForward,
+    WallClockMovedBack,
+}
+
+/// 取りこぼしたタイマー・アラームの扱い
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MissedAlarmPolicy {
+    /// 遅れても 1 回だけ鳴らす
+    #[default]
+    FireLate,
+    /// 鳴らさずに終了させる
+    Drop,
+}
+
+/// `timer-resynced` のペイロード
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResyncReport {
+    pub reason: ResyncReason,
+    /// 検出した時計の飛び
+    pub jump_ms: u64,
+    /// 補正の結果、取りこぼしとして終了したタイマーの ID
+    pub missed_timers: Vec<String>,
+    pub policy: MissedAlarmPolicy,
+}
+
+#[derive(Default)]
+pub struct ResyncState(pub Mutex<MissedAlarmPolicy>);
+
+/// 現在の取りこぼし時の扱い（スレッドから参照する）
+pub fn missed_alarm_policy(state: &ResyncState) -> MissedAlarmPolicy {
+    *state.0.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// フロントエンドが `settings.json`（store プラグイン）に保存した方針を読み込む。
+/// 起動直後のタイマー復元やアラーム確認は、フロントエンドが方針を送ってくる前に行うため
+pub fn load_policy(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let store = app.store("settings.json")?;
+    let Some(policy) = store
+        .get("settings")
+        .and_then(|settings| settings.get("missedAlarmPolicy").cloned())
+    else {
+        return Ok(());
+    };
+    let policy: MissedAlarmPolicy = serde_json::from_value(policy)?;
+    println!("DEBUG: Missed alarm policy loaded: {:?}", policy);
+    *app.state::<ResyncState>()
+        .0
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = policy;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn resync_get_policy(state: State<'_, ResyncState>) -> Result<MissedAlarmPolicy, String> {
+    let policy = state
+        .0
+        .lock()
+        .map_err(|e| format!("Resync state lock poisoned: {}", e))?;
+    Ok(*policy)
+}
+
+#[tauri::command]
+pub async fn resync_set_policy(
+    state: State<'_, ResyncState>,
+    policy: MissedAlarmPolicy,
+) -> Result<(), String> {
+    let mut current = state
+        .0
+        .lock()
+        .map_err(|e| format!("Resync state lock poisoned: {}", e))?;
+    println!("DEBUG: Missed alarm policy set to {:?}", policy);
+    *current = policy;
+    Ok(())
+}
@@ -0,0 +1,371 @@
+//! ラベル付きセグメントを順番に実行するシーケンス（インターバル / 議事進行）。
+//!
+//! シーケンスはアプリデータディレクトリの `sequences/<id>.json` に 1 ファイルずつ保存し、
+//! ファイルをコピーするだけで共有できるようにしている。
+//! 実行中は既定タイマーを使い、セグメントが終わるたびに次のセグメントを開始する。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::duration::ClockTime;
+use crate::timer::{self, NamedTimerSnapshot, TimerAlarm, TimerRegistryState, DEFAULT_TIMER_ID};
+
+pub const SEQUENCE_CHANGED_EVENT: &str = "sequence-changed";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SequenceSegment {
+    pub label: String,
+    pub duration_secs: u64,
+    /// このセグメントが終わったときのアラーム
+    #[serde(default)]
+    pub alarm: TimerAlarm,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Sequence {
+    /// ファイル名にも使う ID。空なら保存時に採番する
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub segments: Vec<SequenceSegment>,
+}
+
+impl Sequence {
+    pub fn validate(&self) -> Result<(), String> {
+        if !is_valid_sequence_id(&self.id) {
+            return Err(format!("Invalid sequence id: {:?}", self.id));
+        }
+        if self.segments.is_empty() {
+            return Err("Sequence must have at least one segment".to_string());
+        }
+        if let Some(segment) = self.segments.iter().find(|s| s.duration_secs == 0) {
+            return Err(format!(
+                "Segment {:?} must be longer than zero seconds",
+                segment.label
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// ID はそのままファイル名になるため、英数字と `-` `_` のみ許可する
+pub fn is_valid_sequence_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 64
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// 実行中のシーケンスと現在のセグメント位置
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SequenceRun {
+    pub sequence: Sequence,
+    pub index: usize,
+    /// 開始前の既定タイマーのアラーム設定。終了時に戻す
+    #[serde(skip)]
+    pub saved_alarm: TimerAlarm,
+}
+
+impl SequenceRun {
+    pub fn new(sequence: Sequence) -> Self {
+        Self {
+            sequence,
+            index: 0,
+            saved_alarm: TimerAlarm::default(),
+        }
+    }
+
+    /// 開始前の既定タイマーのアラーム設定を覚えておく
+    pub fn saving_alarm(mut self, alarm: &TimerAlarm) -> Self {
+        self.saved_alarm = alarm.clone();
+        self
+    }
+
+    pub fn current(&self) -> &SequenceSegment {
+        &self.sequence.segments[self.index]
+    }
+
+    /// 次のセグメントへ進む。最後のセグメントだった場合は `false`
+    pub fn advance(&mut self) -> bool {
+        if self.index + 1 < self.sequence.segments.len() {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// 前のセグメントへ戻る（先頭ではその場でやり直し）
+    pub fn back(&mut self) {
+        self.index = self.index.saturating_sub(1);
+    }
+}
+
+#[derive(Default)]
+pub struct SequenceState(pub Mutex<Option<SequenceRun>>);
+
+fn sequence_path(id: &str) -> PathBuf {
+    crate::get_sequences_dir().join(format!("{}.json", id))
+}
+
+pub fn load_sequence(id: &str) -> Result<Sequence, Box<dyn std::error::Error>> {
+    if !is_valid_sequence_id(id) {
+        return Err(format!("Invalid sequence id: {:?}", id).into());
+    }
+    let json = fs::read_to_string(sequence_path(id))?;
+    let mut sequence: Sequence = serde_json::from_str(&json)?;
+    // 共有されたファイルはファイル名を正とする
+    sequence.id = id.to_string();
+    Ok(sequence)
+}
+
+fn load_all_sequences() -> Result<Vec<Sequence>, Box<dyn std::error::Error>> {
+    let mut sequences = Vec::new();
+    for entry in fs::read_dir(crate::get_sequences_dir())? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(id) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        match load_sequence(id) {
+            Ok(sequence) => sequences.push(sequence),
+            Err(e) => println!("DEBUG: Skipping sequence file {:?}: {}", path, e),
+        }
+    }
+    sequences.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+    Ok(sequences)
+}
+
+fn save_sequence(sequence: &Sequence) -> Result<(), Box<dyn std::error::Error>> {
+    let json = serde_json::to_string_pretty(sequence)?;
+    fs::write(sequence_path(&sequence.id), json)?;
+    Ok(())
+}
+
+/// 実行中なら現在のセグメント名を返す
+pub fn active_layer_label(app: &AppHandle) -> Option<String> {
+    let state = app.state::<SequenceState>();
+    let run = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    run.as_ref().map(|r| r.current().label.clone())
+}
+
+/// 実行中のシーケンスを破棄する（ポモドーロ開始時など）。既定タイマーの時間はそのままで、
+/// アラーム設定だけ開始前に戻す
+pub fn clear(app: &AppHandle) {
+    if let Err(e) = finish_run(app) {
+        println!("DEBUG: Failed to restore timer after sequence: {}", e);
+    }
+}
+
+fn emit_run(app: &AppHandle, run: Option<&SequenceRun>) {
+    if let Err(e) = app.emit(SEQUENCE_CHANGED_EVENT, run) {
+        println!("DEBUG: Failed to emit {}: {}", SEQUENCE_CHANGED_EVENT, e);
+    }
+}
+
+/// 既定タイマーを現在のセグメントに合わせて設定し、開始する
+fn apply_segment(app: &AppHandle, run: &SequenceRun) -> Result<(), String> {
+    let segment = run.current().clone();
+    let duration = Duration::from_secs(segment.duration_secs);
+    let registry = app.state::<TimerRegistryState>();
+    timer::update_timer(
+        app,
+        &registry,
+        Some(DEFAULT_TIMER_ID.to_string()),
+        |t, now| {
+            t.alarm = segment.alarm.clone();
+            t.engine.set(duration);
+            t.engine.start(now);
+        },
+    )?;
+
+    emit_run(app, Some(run));
+    let display = ClockTime::from_secs(segment.duration_secs);
+    if let Err(e) = crate::render_layer_timer(app, display, false, false, Some(segment.label)) {
+        println!("DEBUG: Failed to update layer for sequence: {}", e);
+    }
+    Ok(())
+}
+
+/// 実行中に超過計測を切り替えたら、終了後もその設定を残す
+pub fn remember_overtime(app: &AppHandle, enabled: bool) {
+    let state = app.state::<SequenceState>();
+    let mut current = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(run) = current.as_mut() {
+        run.saved_alarm.overtime = enabled;
+    }
+}
+
+/// シーケンスを終了し、既定タイマーのアラームを開始前の設定に戻す
+fn finish_run(app: &AppHandle) -> Result<(), String> {
+    let previous = app
+        .state::<SequenceState>()
+        .0
+        .lock()
+        .map_err(|e| format!("Sequence state lock poisoned: {}", e))?
+        .take();
+    let Some(run) = previous else {
+        return Ok(());
+    };
+    let registry = app.state::<TimerRegistryState>();
+    timer::update_timer(
+        app,
+        &registry,
+        Some(DEFAULT_TIMER_ID.to_string()),
+        |t, _| {
+            t.alarm = run.saved_alarm.clone();
+        },
+    )?;
+    emit_run(app, None);
+    Ok(())
+}
+
+/// 現在位置を `f` で動かし、続きがあればそのセグメントを開始する
+fn step<F>(app: &AppHandle, f: F) -> Result<Option<SequenceRun>, String>
+where
+    F: FnOnce(&mut SequenceRun) -> bool,
+{
+    let next = {
+        let state = app.state::<SequenceState>();
+        let mut run = state
+            .0
+            .lock()
+            .map_err(|e| format!("Sequence state lock poisoned: {}", e))?;
+        let Some(run) = run.as_mut() else {
+            return Err("No sequence is running".to_string());
+        };
+        f(run).then(|| run.clone())
+    };
+    match next {
+        Some(run) => {
+            apply_segment(app, &run)?;
+            Ok(Some(run))
+        }
+        None => {
+            finish_run(app)?;
+            Ok(None)
+        }
+    }
+}
+
+/// ティッカーから既定タイマーの終了時に呼ばれ、次のセグメントへ進める
+pub fn on_timer_finished(app: &AppHandle, snapshot: &NamedTimerSnapshot) {
+    if snapshot.id != DEFAULT_TIMER_ID {
+        return;
+    }
+    let running = app
+        .state::<SequenceState>()
+        .0
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .is_some();
+    // 超過計測するセグメントは話者が終えるまで待ち、手動で次へ進める
+    if !running || snapshot.alarm.overtime {
+        return;
+    }
+    match step(app, SequenceRun::advance) {
+        Ok(Some(run)) => println!(
+            "DEBUG: Sequence advanced to segment {} ({})",
+            run.index,
+            run.current().label
+        ),
+        Ok(None) => println!("DEBUG: Sequence finished"),
+        Err(e) => println!("DEBUG: Failed to advance sequence: {}", e),
+    }
+}
+
+#[tauri::command]
+pub async fn sequence_list() -> Result<Vec<Sequence>, String> {
+    load_all_sequences().map_err(|e| format!("Failed to load sequences: {}", e))
+}
+
+/// シーケンスを保存する。ID が空なら新規作成として採番する
+#[tauri::command]
+pub async fn sequence_save(mut sequence: Sequence) -> Result<Sequence, String> {
+    if sequence.id.is_empty() {
+        sequence.id = format!("seq-{}", timer::unix_millis(std::time::SystemTime::now()));
+    }
+    sequence.validate()?;
+    save_sequence(&sequence).map_err(|e| format!("Failed to save sequence: {}", e))?;
+    Ok(sequence)
+}
+
+#[tauri::command]
+pub async fn sequence_delete(id: String) -> Result<(), String> {
+    if !is_valid_sequence_id(&id) {
+        return Err(format!("Invalid sequence id: {:?}", id));
+    }
+    fs::remove_file(sequence_path(&id)).map_err(|e| format!("Failed to delete sequence: {}", e))
+}
+
+/// 保存済みシーケンスを先頭のセグメントから開始する
+#[tauri::command]
+pub async fn sequence_start(
+    app: AppHandle,
+    state: State<'_, SequenceState>,
+    id: String,
+) -> Result<SequenceRun, String> {
+    let sequence = load_sequence(&id).map_err(|e| format!("Failed to load sequence: {}", e))?;
+    sequence.validate()?;
+    // 既定タイマーを奪い合わないようポモドーロは止める（アラーム設定も開始前に戻る）
+    crate::pomodoro::clear(&app);
+    // 実行中のシーケンスがあればその開始前の設定に戻してから覚える
+    finish_run(&app)?;
+    let alarm = timer::timer_snapshot(&app, None)?.alarm;
+    let run = SequenceRun::new(sequence).saving_alarm(&alarm);
+    *state
+        .0
+        .lock()
+        .map_err(|e| format!("Sequence state lock poisoned: {}", e))? = Some(run.clone());
+    apply_segment(&app, &run)?;
+    Ok(run)
+}
+
+/// 現在のセグメントを打ち切って次へ進む。最後のセグメントなら終了する
+#[tauri::command]
+pub async fn sequence_next(app: AppHandle) -> Result<Option<SequenceRun>, String> {
+    step(&app, SequenceRun::advance)
+}
+
+/// 前のセグメントへ戻る
+#[tauri::command]
+pub async fn sequence_previous(app: AppHandle) -> Result<Option<SequenceRun>, String> {
+    step(&app, |run| {
+        run.back();
+        true
+    })
+}
+
+#[tauri::command]
+pub async fn sequence_stop(app: AppHandle) -> Result<(), String> {
+    finish_run(&app)?;
+    let registry = app.state::<TimerRegistryState>();
+    timer::update_timer(
+        &app,
+        &registry,
+        Some(DEFAULT_TIMER_ID.to_string()),
+        |t, _| t.engine.reset(),
+    )?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn sequence_get_state(
+    state: State<'_, SequenceState>,
+) -> Result<Option<SequenceRun>, String> {
+    let run = state
+        .0
+        .lock()
+        .map_err(|e| format!("Sequence state lock poisoned: {}", e))?;
+    Ok(run.clone())
+}
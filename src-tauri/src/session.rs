@@ -0,0 +1,97 @@
+//! 終了したカウントダウンのセッション記録。
+//!
+//! タイマーが 0 に到達したとき（超過計測中なら計測を止めたとき）に
+//! `sessions.json` へ追記し、どれだけ時間を超過したかを後から確認できるようにする。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter};
+
+use crate::timer::{unix_millis, NamedTimer};
+
+pub const SESSION_RECORDED_EVENT: &str = "session-recorded";
+
+/// `sessions.json` に保持する最大件数（古いものから捨てる）
+pub const MAX_SESSION_RECORDS: usize = 1000;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionRecord {
+    pub timer_id: String,
+    pub timer_name: String,
+    /// 設定時間
+    pub duration_ms: u64,
+    /// 0 に到達した時刻（UNIX エポックからのミリ秒）
+    pub finished_at_unix_ms: u64,
+    /// 0 到達後に超過した時間
+    pub overrun_ms: u64,
+}
+
+impl SessionRecord {
+    /// タイマーの終了記録を作る。`overrun` は 0 到達から止めるまでの時間
+    pub fn finished(timer: &NamedTimer, overrun: Duration) -> Self {
+        let finished_at = SystemTime::now()
+            .checked_sub(overrun)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        Self {
+            timer_id: timer.id.clone(),
+            timer_name: timer.name.clone(),
+            duration_ms: timer.engine.duration().as_millis() as u64,
+            finished_at_unix_ms: unix_millis(finished_at),
+            overrun_ms: overrun.as_millis() as u64,
+        }
+    }
+}
+
+/// 記録を追加し、上限を超えた分を古い順に削除する
+pub fn push_session_record(records: &mut Vec<SessionRecord>, record: SessionRecord) {
+    records.push(record);
+    if records.len() > MAX_SESSION_RECORDS {
+        let excess = records.len() - MAX_SESSION_RECORDS;
+        records.drain(..excess);
+    }
+}
+
+fn load_session_records() -> Result<Vec<SessionRecord>, Box<dyn std::error::Error>> {
+    let path = crate::get_sessions_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let json = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+fn append_session_record(record: SessionRecord) -> Result<(), Box<dyn std::error::Error>> {
+    let mut records = load_session_records()?;
+    push_session_record(&mut records, record);
+    let json = serde_json::to_string_pretty(&records)?;
+    fs::write(crate::get_sessions_path(), json)?;
+    Ok(())
+}
+
+/// 終了記録を保存して `session-recorded` を emit する。
+/// ファイルを読み書きするので、タイマー一覧のロックを放してから呼ぶ
+pub fn record_session(app: &AppHandle, record: SessionRecord) {
+    println!("DEBUG: Session recorded: {:?}", record);
+    if let Err(e) = append_session_record(record.clone()) {
+        println!("DEBUG: Failed to save session record: {}", e);
+    }
+    if let Err(e) = app.emit(SESSION_RECORDED_EVENT, &record) {
+        println!("DEBUG: Failed to emit {}: {}", SESSION_RECORDED_EVENT, e);
+    }
+}
+
+#[tauri::command]
+pub async fn session_list() -> Result<Vec<SessionRecord>, String> {
+    load_session_records().map_err(|e| format!("Failed to load session records: {}", e))
+}
+
+#[tauri::command]
+pub async fn session_clear() -> Result<(), String> {
+    let path = crate::get_sessions_path();
+    if path.exists() {
+        fs::remove_file(path).map_err(|e| format!("Failed to clear session records: {}", e))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,370 @@
+//! システム全体で効くグローバルショートカット。
+//!
+//! 割り当ては `shortcuts.json` に保存し、起動時と変更時に登録し直す。
+//! 書式は "CommandOrControl+Alt+Space" のようなアクセラレータ文字列で、
+//! 空文字列はその操作を無効にする。設定内で重複した組み合わせや、
+//! 他のアプリが使用中で登録できなかった組み合わせは `ShortcutConflict` として返し、
+//! `shortcut-conflicts` でも通知する。
+//! プリセットの起動はプリセットの `shortcut` に保存し、同じように登録・確認する。
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+
+use crate::preset::Preset;
+use crate::timer::{self, TimerRegistryState};
+
+pub const SHORTCUT_CONFLICTS_EVENT: &str = "shortcut-conflicts";
+
+/// 「1 分追加」で延長する時間
+const ADD_TIME_STEP: Duration = Duration::from_secs(60);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum ShortcutAction {
+    StartPause,
+    Reset,
+    AddMinute,
+    ToggleLayer,
+    DismissTimeup,
+    /// プリセットの起動。割り当てはプリセット側に持つので `ALL` には含めない
+    LaunchPreset,
+}
+
+impl ShortcutAction {
+    pub const ALL: [ShortcutAction; 5] = [
+        ShortcutAction::StartPause,
+        ShortcutAction::Reset,
+        ShortcutAction::AddMinute,
+        ShortcutAction::ToggleLayer,
+        ShortcutAction::DismissTimeup,
+    ];
+
+    pub fn default_accelerator(self) -> &'static str {
+        match self {
+            ShortcutAction::StartPause => "CommandOrControl+Alt+Space",
+            ShortcutAction::Reset => "CommandOrControl+Alt+R",
+            ShortcutAction::AddMinute => "CommandOrControl+Alt+Up",
+            ShortcutAction::ToggleLayer => "CommandOrControl+Alt+L",
+            ShortcutAction::DismissTimeup => "CommandOrControl+Alt+D",
+            ShortcutAction::LaunchPreset => "",
+        }
+    }
+}
+
+/// 操作ごとのアクセラレータ。保存されていない操作は既定値を使う
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ShortcutConfig {
+    #[serde(default)]
+    pub bindings: BTreeMap<ShortcutAction, String>,
+}
+
+impl ShortcutConfig {
+    /// 操作に割り当てられたアクセラレータ。無効なら `None`
+    pub fn accelerator(&self, action: ShortcutAction) -> Option<&str> {
+        let accelerator = self
+            .bindings
+            .get(&action)
+            .map(|s| s.trim())
+            .unwrap_or_else(|| action.default_accelerator());
+        (!accelerator.is_empty()).then_some(accelerator)
+    }
+
+    /// 全操作の割り当てを既定値で補って返す（設定画面の表示用）
+    pub fn resolved(&self) -> Self {
+        Self {
+            bindings: ShortcutAction::ALL
+                .iter()
+                .map(|&action| {
+                    let accelerator = self.accelerator(action).unwrap_or_default();
+                    (action, accelerator.to_string())
+                })
+                .collect(),
+        }
+    }
+
+    /// 文字列を解析し、登録するショートカットと設定内の問題を返す
+    pub fn parse(&self) -> (Vec<(ShortcutAction, Shortcut)>, Vec<ShortcutConflict>) {
+        let mut parsed: Vec<(ShortcutAction, Shortcut)> = Vec::new();
+        let mut conflicts = Vec::new();
+        for action in ShortcutAction::ALL {
+            let Some(accelerator) = self.accelerator(action) else {
+                continue;
+            };
+            match accelerator.parse::<Shortcut>() {
+                Ok(shortcut) => match parsed.iter().find(|(_, s)| *s == shortcut) {
+                    Some((other, _)) => conflicts.push(ShortcutConflict {
+                        action,
+                        preset_id: None,
+                        accelerator: accelerator.to_string(),
+                        reason: format!("Already assigned to {:?}", other),
+                    }),
+                    None => parsed.push((action, shortcut)),
+                },
+                Err(e) => conflicts.push(ShortcutConflict {
+                    action,
+                    preset_id: None,
+                    accelerator: accelerator.to_string(),
+                    reason: format!("Invalid shortcut: {}", e),
+                }),
+            }
+        }
+        (parsed, conflicts)
+    }
+}
+
+/// プリセットの割り当てを解析する。`taken` は操作に割り当て済みのショートカット
+pub fn parse_preset_shortcuts(
+    presets: &[Preset],
+    taken: &[(ShortcutAction, Shortcut)],
+) -> (Vec<(String, Shortcut)>, Vec<ShortcutConflict>) {
+    let mut parsed: Vec<(String, Shortcut)> = Vec::new();
+    let mut conflicts = Vec::new();
+    for preset in presets {
+        let Some(accelerator) = preset
+            .shortcut
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+        else {
+            continue;
+        };
+        let conflict = |reason: String| ShortcutConflict {
+            action: ShortcutAction::LaunchPreset,
+            preset_id: Some(preset.id.clone()),
+            accelerator: accelerator.to_string(),
+            reason,
+        };
+        match accelerator.parse::<Shortcut>() {
+            Ok(shortcut) => {
+                if let Some((other, _)) = taken.iter().find(|(_, s)| *s == shortcut) {
+                    conflicts.push(conflict(format!("Already assigned to {:?}", other)));
+                } else if let Some((other, _)) = parsed.iter().find(|(_, s)| *s == shortcut) {
+                    conflicts.push(conflict(format!("Already assigned to preset {}", other)));
+                } else {
+                    parsed.push((preset.id.clone(), shortcut));
+                }
+            }
+            Err(e) => conflicts.push(conflict(format!("Invalid shortcut: {}", e))),
+        }
+    }
+    (parsed, conflicts)
+}
+
+/// 登録できなかった割り当て
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ShortcutConflict {
+    pub action: ShortcutAction,
+    /// `LaunchPreset` のときの対象プリセット
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preset_id: Option<String>,
+    pub accelerator: String,
+    pub reason: String,
+}
+
+/// 押されたショートカットで実行するもの
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ShortcutTarget {
+    Action(ShortcutAction),
+    Preset(String),
+}
+
+/// 登録中のショートカットと直近の登録結果
+#[derive(Default)]
+pub struct ShortcutRegistry {
+    targets: HashMap<u32, ShortcutTarget>,
+    conflicts: Vec<ShortcutConflict>,
+}
+
+#[derive(Default)]
+pub struct ShortcutRegistryState(pub Mutex<ShortcutRegistry>);
+
+fn load_shortcut_config() -> ShortcutConfig {
+    let path = crate::get_shortcuts_path();
+    if !path.exists() {
+        return ShortcutConfig::default();
+    }
+    match fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+    {
+        Ok(config) => config,
+        Err(e) => {
+            println!(
+                "DEBUG: Failed to load shortcut config, using defaults: {}",
+                e
+            );
+            ShortcutConfig::default()
+        }
+    }
+}
+
+fn save_shortcut_config(config: &ShortcutConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let json = serde_json::to_string_pretty(config)?;
+    fs::write(crate::get_shortcuts_path(), json)?;
+    Ok(())
+}
+
+/// 登録済みのショートカットを解除し、設定どおりに登録し直す
+fn register_shortcuts(app: &AppHandle, config: &ShortcutConfig) -> Vec<ShortcutConflict> {
+    let manager = app.global_shortcut();
+    if let Err(e) = manager.unregister_all() {
+        println!("DEBUG: Failed to unregister shortcuts: {}", e);
+    }
+
+    let presets = crate::preset::load_presets()
+        .map(|library| library.presets)
+        .unwrap_or_else(|e| {
+            println!("DEBUG: Failed to load presets for shortcuts: {}", e);
+            Vec::new()
+        });
+    let (parsed, mut conflicts) = config.parse();
+    let (parsed_presets, preset_conflicts) = parse_preset_shortcuts(&presets, &parsed);
+    conflicts.extend(preset_conflicts);
+
+    // 登録するものと、登録できなかったときに報告するアクセラレータ
+    let mut candidates: Vec<(ShortcutTarget, Shortcut, String)> = Vec::new();
+    for (action, shortcut) in parsed {
+        let accelerator = config.accelerator(action).unwrap_or_default();
+        candidates.push((
+            ShortcutTarget::Action(action),
+            shortcut,
+            accelerator.to_string(),
+        ));
+    }
+    for (id, shortcut) in parsed_presets {
+        let accelerator = presets
+            .iter()
+            .find(|p| p.id == id)
+            .and_then(|p| p.shortcut.as_deref())
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+        candidates.push((ShortcutTarget::Preset(id), shortcut, accelerator));
+    }
+
+    let mut targets = HashMap::new();
+    for (target, shortcut, accelerator) in candidates {
+        if let Err(e) = manager.register(shortcut) {
+            let (action, preset_id) = match &target {
+                ShortcutTarget::Action(action) => (*action, None),
+                ShortcutTarget::Preset(id) => (ShortcutAction::LaunchPreset, Some(id.clone())),
+            };
+            conflicts.push(ShortcutConflict {
+                action,
+                preset_id,
+                accelerator,
+                reason: format!("Could not register (in use by another application?): {}", e),
+            });
+            continue;
+        }
+        targets.insert(shortcut.id(), target);
+    }
+    println!(
+        "DEBUG: Registered {} global shortcuts, {} conflicts",
+        targets.len(),
+        conflicts.len()
+    );
+
+    let state = app.state::<ShortcutRegistryState>();
+    let mut registry = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    registry.targets = targets;
+    registry.conflicts = conflicts.clone();
+    drop(registry);
+
+    if !conflicts.is_empty() {
+        if let Err(e) = app.emit(SHORTCUT_CONFLICTS_EVENT, &conflicts) {
+            println!("DEBUG: Failed to emit {}: {}", SHORTCUT_CONFLICTS_EVENT, e);
+        }
+    }
+    conflicts
+}
+
+/// 保存済みの割り当てを登録する（起動時・プリセットの変更時）
+pub fn register_saved_shortcuts(app: &AppHandle) {
+    register_shortcuts(app, &load_shortcut_config());
+}
+
+fn run_action(app: &AppHandle, action: ShortcutAction) -> Result<(), String> {
+    match action {
+        ShortcutAction::StartPause => timer::toggle_start_pause(app, None).map(|_| ()),
+        ShortcutAction::Reset => {
+            let state = app.state::<TimerRegistryState>();
+            timer::update_timer(app, &state, None, |t, _| t.engine.reset()).map(|_| ())
+        }
+        ShortcutAction::AddMinute => {
+            let state = app.state::<TimerRegistryState>();
+            timer::update_timer(app, &state, None, |t, now| {
+                t.engine.add_time(ADD_TIME_STEP, now)
+            })
+            .map(|_| ())
+        }
+        ShortcutAction::ToggleLayer => {
+            crate::toggle_layer_window(app);
+            Ok(())
+        }
+        ShortcutAction::DismissTimeup => {
+            crate::dismiss_timeup(app);
+            Ok(())
+        }
+        // プリセットは `ShortcutTarget::Preset` で起動する
+        ShortcutAction::LaunchPreset => Ok(()),
+    }
+}
+
+/// グローバルショートカットプラグインのハンドラ。押下時のみ操作を実行する
+pub fn handle_shortcut(app: &AppHandle, shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state != ShortcutState::Pressed {
+        return;
+    }
+    let target = {
+        let state = app.state::<ShortcutRegistryState>();
+        let registry = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        registry.targets.get(&shortcut.id()).cloned()
+    };
+    let Some(target) = target else {
+        return;
+    };
+    println!("DEBUG: Global shortcut {} -> {:?}", shortcut, target);
+    let result = match &target {
+        ShortcutTarget::Action(action) => run_action(app, *action),
+        ShortcutTarget::Preset(id) => crate::preset::launch_preset(app, id).map(|_| ()),
+    };
+    if let Err(e) = result {
+        println!("DEBUG: Shortcut {:?} failed: {}", target, e);
+    }
+}
+
+/// 既定値で補った現在の割り当てを返す
+#[tauri::command]
+pub async fn shortcut_get_config() -> Result<ShortcutConfig, String> {
+    Ok(load_shortcut_config().resolved())
+}
+
+/// 割り当てを保存して登録し直し、登録できなかったものを返す
+#[tauri::command]
+pub async fn shortcut_set_config(
+    app: AppHandle,
+    config: ShortcutConfig,
+) -> Result<Vec<ShortcutConflict>, String> {
+    save_shortcut_config(&config).map_err(|e| format!("Failed to save shortcut config: {}", e))?;
+    Ok(register_shortcuts(&app, &config))
+}
+
+/// 直近の登録で問題のあった割り当て（起動時の登録結果の確認用）
+#[tauri::command]
+pub async fn shortcut_conflicts(
+    state: State<'_, ShortcutRegistryState>,
+) -> Result<Vec<ShortcutConflict>, String> {
+    let registry = state
+        .0
+        .lock()
+        .map_err(|e| format!("Shortcut state lock poisoned: {}", e))?;
+    Ok(registry.conflicts.clone())
+}
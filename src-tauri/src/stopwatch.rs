@@ -0,0 +1,287 @@
+//! カウントアップのストップウォッチ（ラップ / スプリット記録付き）。
+//!
+//! 経過時間は `Instant` からミリ秒単位で計算し、計測中は `stopwatch-tick` を
+//! 一定間隔で emit する。レイヤー表示を有効にすると、残り時間の代わりに
+//! 経過時間をレイヤーへ描画する。
+
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::timer::{TimerRegistryState, TimerStatus, DEFAULT_TIMER_ID};
+
+pub const STOPWATCH_TICK_EVENT: &str = "stopwatch-tick";
+
+/// 計測中の tick 間隔（表示は 1/10 秒単位）
+const RUNNING_TICK: Duration = Duration::from_millis(100);
+const IDLE_POLL: Duration = Duration::from_millis(250);
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Lap {
+    /// 1 始まりのラップ番号
+    pub number: usize,
+    /// 前のラップからの時間
+    pub lap_ms: u64,
+    /// 計測開始からの通算時間
+    pub split_ms: u64,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StopwatchSnapshot {
+    pub running: bool,
+    pub elapsed_ms: u64,
+    pub laps: Vec<Lap>,
+    pub show_on_layer: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct Stopwatch {
+    /// 一時停止までに積算した時間
+    accumulated: Duration,
+    /// 計測中なら再開した時刻
+    started_at: Option<Instant>,
+    laps: Vec<Lap>,
+}
+
+impl Stopwatch {
+    pub fn is_running(&self) -> bool {
+        self.started_at.is_some()
+    }
+
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        self.accumulated
+            + self
+                .started_at
+                .map(|t| now.saturating_duration_since(t))
+                .unwrap_or_default()
+    }
+
+    /// 計測を開始（再開）する。既に計測中なら `false`
+    pub fn start(&mut self, now: Instant) -> bool {
+        if self.is_running() {
+            return false;
+        }
+        self.started_at = Some(now);
+        true
+    }
+
+    pub fn pause(&mut self, now: Instant) {
+        self.accumulated = self.elapsed(now);
+        self.started_at = None;
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// 計測中ならラップを記録する
+    pub fn lap(&mut self, now: Instant) -> Option<Lap> {
+        if !self.is_running() {
+            return None;
+        }
+        let split_ms = self.elapsed(now).as_millis() as u64;
+        let previous = self.laps.last().map(|l| l.split_ms).unwrap_or(0);
+        let lap = Lap {
+            number: self.laps.len() + 1,
+            lap_ms: split_ms - previous,
+            split_ms,
+        };
+        self.laps.push(lap.clone());
+        Some(lap)
+    }
+
+    pub fn laps(&self) -> &[Lap] {
+        &self.laps
+    }
+}
+
+/// 1 時間未満は `MM:SS.d`、それ以上は `H:MM:SS` で表示する
+pub fn format_elapsed(elapsed: Duration) -> String {
+    let total_secs = elapsed.as_secs();
+    let hours = total_secs / 3600;
+    let minutes = (total_secs % 3600) / 60;
+    let seconds = total_secs % 60;
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!(
+            "{:02}:{:02}.{}",
+            minutes,
+            seconds,
+            elapsed.subsec_millis() / 100
+        )
+    }
+}
+
+#[derive(Default)]
+pub struct StopwatchInner {
+    pub stopwatch: Stopwatch,
+    /// レイヤーに経過時間を表示するか
+    pub show_on_layer: bool,
+}
+
+impl StopwatchInner {
+    fn snapshot(&self, now: Instant) -> StopwatchSnapshot {
+        StopwatchSnapshot {
+            running: self.stopwatch.is_running(),
+            elapsed_ms: self.stopwatch.elapsed(now).as_millis() as u64,
+            laps: self.stopwatch.laps().to_vec(),
+            show_on_layer: self.show_on_layer,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct StopwatchState(pub Mutex<StopwatchInner>);
+
+/// レイヤーがストップウォッチ表示中か
+pub fn shows_on_layer(app: &AppHandle) -> bool {
+    let state = app.state::<StopwatchState>();
+    let inner = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    inner.show_on_layer
+}
+
+fn render_to_layer(app: &AppHandle, elapsed: Duration) {
+    if let Err(e) = crate::render_layer_elapsed(app, elapsed) {
+        println!("DEBUG: Failed to render stopwatch on layer: {}", e);
+    }
+}
+
+/// 計測中は一定間隔で `stopwatch-tick` を emit し、必要ならレイヤーも更新するスレッドを起動する
+pub fn spawn_ticker(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        let snapshot = {
+            let state = app.state::<StopwatchState>();
+            let inner = state.0.lock().unwrap_or_else(|e| e.into_inner());
+            inner
+                .stopwatch
+                .is_running()
+                .then(|| inner.snapshot(Instant::now()))
+        };
+        match snapshot {
+            Some(snapshot) => {
+                if snapshot.show_on_layer {
+                    render_to_layer(&app, Duration::from_millis(snapshot.elapsed_ms));
+                }
+                if let Err(e) = app.emit(STOPWATCH_TICK_EVENT, &snapshot) {
+                    println!("DEBUG: Failed to emit {}: {}", STOPWATCH_TICK_EVENT, e);
+                }
+                std::thread::sleep(RUNNING_TICK);
+            }
+            None => std::thread::sleep(IDLE_POLL),
+        }
+    });
+}
+
+/// ストップウォッチを操作し、変更後の状態を通知する
+fn update_stopwatch<F>(
+    app: &AppHandle,
+    state: &State<StopwatchState>,
+    f: F,
+) -> Result<StopwatchSnapshot, String>
+where
+    F: FnOnce(&mut StopwatchInner, Instant),
+{
+    let snapshot = {
+        let mut inner = state
+            .0
+            .lock()
+            .map_err(|e| format!("Stopwatch state lock poisoned: {}", e))?;
+        let now = Instant::now();
+        f(&mut inner, now);
+        inner.snapshot(now)
+    };
+    if snapshot.show_on_layer {
+        render_to_layer(app, Duration::from_millis(snapshot.elapsed_ms));
+    }
+    if let Err(e) = app.emit(STOPWATCH_TICK_EVENT, &snapshot) {
+        println!("DEBUG: Failed to emit {}: {}", STOPWATCH_TICK_EVENT, e);
+    }
+    Ok(snapshot)
+}
+
+#[tauri::command]
+pub async fn stopwatch_start(
+    app: AppHandle,
+    state: State<'_, StopwatchState>,
+) -> Result<StopwatchSnapshot, String> {
+    update_stopwatch(&app, &state, |inner, now| {
+        inner.stopwatch.start(now);
+    })
+}
+
+#[tauri::command]
+pub async fn stopwatch_pause(
+    app: AppHandle,
+    state: State<'_, StopwatchState>,
+) -> Result<StopwatchSnapshot, String> {
+    update_stopwatch(&app, &state, |inner, now| inner.stopwatch.pause(now))
+}
+
+#[tauri::command]
+pub async fn stopwatch_reset(
+    app: AppHandle,
+    state: State<'_, StopwatchState>,
+) -> Result<StopwatchSnapshot, String> {
+    update_stopwatch(&app, &state, |inner, _| inner.stopwatch.reset())
+}
+
+/// ラップを記録する。計測中でなければエラー
+#[tauri::command]
+pub async fn stopwatch_lap(
+    app: AppHandle,
+    state: State<'_, StopwatchState>,
+) -> Result<StopwatchSnapshot, String> {
+    let mut recorded = false;
+    let snapshot = update_stopwatch(&app, &state, |inner, now| {
+        recorded = inner.stopwatch.lap(now).is_some();
+    })?;
+    if !recorded {
+        return Err("Stopwatch is not running".to_string());
+    }
+    Ok(snapshot)
+}
+
+#[tauri::command]
+pub async fn stopwatch_get_state(
+    state: State<'_, StopwatchState>,
+) -> Result<StopwatchSnapshot, String> {
+    let inner = state
+        .0
+        .lock()
+        .map_err(|e| format!("Stopwatch state lock poisoned: {}", e))?;
+    Ok(inner.snapshot(Instant::now()))
+}
+
+/// レイヤーに経過時間を表示するか切り替える
+#[tauri::command]
+pub async fn stopwatch_set_layer(
+    app: AppHandle,
+    state: State<'_, StopwatchState>,
+    enabled: bool,
+) -> Result<StopwatchSnapshot, String> {
+    let snapshot = update_stopwatch(&app, &state, |inner, _| inner.show_on_layer = enabled)?;
+    if !enabled {
+        // 既定タイマーの残り時間表示に戻す
+        let timer = {
+            let registry = app.state::<TimerRegistryState>();
+            let registry = registry.0.lock().unwrap_or_else(|e| e.into_inner());
+            registry
+                .get(DEFAULT_TIMER_ID)
+                .map(|t| t.snapshot(Instant::now()).timer)
+        };
+        if let Some(timer) = timer {
+            crate::render_layer_timer(
+                &app,
+                timer.display(),
+                false,
+                timer.status == TimerStatus::Overtime,
+                None,
+            )?;
+        }
+    }
+    Ok(snapshot)
+}
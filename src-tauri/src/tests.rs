@@ -1,4 +1,39 @@
 use super::*;
+use crate::alarm::{due_occurrence, next_occurrence, AlarmBook, WallClockAlarm};
+use crate::cli_args::{parse_cli_args, CliCommand};
+use crate::control::{format_endpoint, parse_endpoint, ControlRequest};
+use crate::deep_link::{is_deep_link, parse_deep_link, DeepLink};
+use crate::duration::{parse_duration_spec, ClockTime, MAX_DURATION_SECS};
+use crate::escalation::{AlertSource, EscalationPolicy, EscalationStep, MAX_ESCALATION_DELAY_SECS};
+use crate::event_stream::{
+    client_access, parse_control, stream_messages, ClientAccess, ControlMessage, StreamMessage,
+};
+use crate::http_api::{
+    is_loopback_host, parse_request, parse_set_body, percent_decode, ApiRoute, SetTimerBody,
+};
+use crate::launch::{parse_launch_args, LaunchCommand, LaunchConfig, LaunchOptions};
+use crate::pomodoro::{PomodoroConfig, PomodoroCycle, PomodoroPhase};
+use crate::preset::{Preset, PresetLibrary};
+use crate::resync::{Clock, ClockJump, ClockWatch, MissedAlarmPolicy};
+use crate::sequence::{is_valid_sequence_id, Sequence, SequenceRun, SequenceSegment};
+use crate::session::{push_session_record, SessionRecord, MAX_SESSION_RECORDS};
+use crate::shortcut::{parse_preset_shortcuts, ShortcutAction, ShortcutConfig};
+use crate::stopwatch::{format_elapsed, Stopwatch};
+use crate::timer::{
+    parse_timer_state_file, unix_millis, NamedTimerSnapshot, TimerAlarm, TimerEngine,
+    TimerRegistry, TimerStatus, DEFAULT_TIMER_ID, TIMER_FINISHED_EVENT, TIMER_STATE_VERSION,
+    TIMER_TICK_EVENT,
+};
+use crate::timeup::{is_timeup_label, timeup_label, timeup_monitors, TimeupGeometry, TimeupScreen};
+use crate::tray::{start_pause_label, tray_title};
+use crate::window_state::{
+    parse_window_state_file, place_window, DisplayMode, MonitorInfo, MonitorLayout, Placement,
+    Rect, WindowState, WindowStateFile, WINDOW_STATE_VERSION,
+};
+use chrono::{TimeZone, Weekday};
+use chrono_tz::America::New_York;
+use std::cell::Cell;
+use std::time::{Duration, Instant, SystemTime};
 
 #[test]
 fn test_get_window_state_path() {
@@ -6,12 +41,22 @@ fn test_get_window_state_path() {
 
     // パスが有効であることを確認
     assert!(path.to_string_lossy().contains("lightning-timer"));
-    assert!(path.to_string_lossy().contains("window_state.json"));
+    assert!(path.to_string_lossy().contains("window_states.json"));
+    assert_eq!(path.parent(), get_legacy_window_state_path().parent());
 
     // パスが絶対パスであることを確認
     assert!(path.is_absolute() || path.to_string_lossy().starts_with("."));
 }
 
+#[test]
+fn test_get_timer_state_path() {
+    let path = get_timer_state_path();
+
+    // window_states.json と同じディレクトリに保存される
+    assert_eq!(path.parent(), get_window_state_path().parent());
+    assert!(path.to_string_lossy().contains("timer_state.json"));
+}
+
 #[test]
 fn test_window_state_default() {
     let state = WindowState::default();
@@ -21,6 +66,8 @@ fn test_window_state_default() {
     assert_eq!(state.y, None);
     assert_eq!(state.width, None);
     assert_eq!(state.height, None);
+    assert_eq!(state.display_mode, None);
+    assert_eq!(state.monitor, None);
 }
 
 #[test]
@@ -31,6 +78,7 @@ fn test_window_state_serialization() {
         y: Some(200),
         width: Some(800),
         height: Some(600),
+        ..Default::default()
     };
 
     // シリアライズ
@@ -56,6 +104,7 @@ fn test_window_state_serialization_with_none_values() {
         y: None,
         width: None,
         height: None,
+        ..Default::default()
     };
 
     // シリアライズ
@@ -73,6 +122,487 @@ fn test_window_state_serialization_with_none_values() {
     assert_eq!(original_state.height, deserialized_state.height);
 }
 
+#[test]
+fn test_window_state_file_keyed_by_label() {
+    let monitor = MonitorInfo {
+        name: Some("DELL U2720Q".to_string()),
+        x: 2560,
+        y: 0,
+        width: 3840,
+        height: 2160,
+        scale_factor: 2.0,
+    };
+    let mut file = WindowStateFile::default();
+    file.remember(
+        "main",
+        WindowState {
+            x: Some(2600),
+            y: Some(40),
+            width: Some(800),
+            height: Some(200),
+            display_mode: Some(DisplayMode::Compact),
+            monitor: Some(monitor.clone()),
+        },
+        None,
+    );
+    file.remember(
+        "settings",
+        WindowState {
+            x: Some(100),
+            y: Some(120),
+            width: Some(540),
+            height: Some(640),
+            ..Default::default()
+        },
+        None,
+    );
+    // 位置だけ更新しても表示モードは引き継ぐ
+    file.remember(
+        "main",
+        WindowState {
+            x: Some(2700),
+            y: Some(80),
+            width: Some(400),
+            height: Some(200),
+            display_mode: None,
+            monitor: Some(monitor),
+        },
+        None,
+    );
+    let main = &file.windows["main"];
+    assert_eq!(
+        (main.x, main.y, main.width),
+        (Some(2700), Some(80), Some(400))
+    );
+    assert_eq!(main.display_mode, Some(DisplayMode::Compact));
+
+    let json = serde_json::to_string(&file).unwrap();
+    assert!(json.contains("\"displayMode\":\"compact\""));
+    assert!(json.contains("\"scaleFactor\":2.0"));
+    assert_eq!(parse_window_state_file(&json), Ok(file));
+
+    // 以前の window_state.json（main の位置のみ）は main として移行する
+    let legacy: WindowState =
+        serde_json::from_str(r#"{"x": 10, "y": 20, "width": null, "height": null}"#).unwrap();
+    let migrated = WindowStateFile::from_legacy(legacy);
+    assert_eq!(migrated.version, WINDOW_STATE_VERSION);
+    assert_eq!(migrated.windows["main"].x, Some(10));
+    assert_eq!(migrated.windows["main"].display_mode, None);
+
+    assert!(parse_window_state_file(r#"{"version": 99, "windows": {}}"#).is_err());
+    assert!(parse_window_state_file(r#"{"windows": {"main": {"x": "left"}}}"#).is_err());
+    assert_eq!(
+        parse_window_state_file("{}"),
+        Ok(WindowStateFile::default())
+    );
+}
+
+fn monitor(name: &str, x: i32, y: i32, width: u32, height: u32) -> MonitorInfo {
+    MonitorInfo {
+        name: Some(name.to_string()),
+        x,
+        y,
+        width,
+        height,
+        scale_factor: 1.0,
+    }
+}
+
+fn window_at(x: i32, y: i32) -> Rect {
+    Rect {
+        x,
+        y,
+        width: 800,
+        height: 200,
+    }
+}
+
+#[test]
+fn test_place_window_keeps_visible_positions() {
+    let laptop = monitor("Built-in", 0, 0, 1920, 1080);
+    let external = monitor("DELL", 1920, 0, 2560, 1440);
+    let both = [laptop.clone(), external.clone()];
+
+    assert_eq!(
+        place_window(window_at(100, 100), Some(&laptop), &both, Some(&laptop)),
+        Placement::Visible
+    );
+    // 2 枚のモニターにまたがっている
+    assert_eq!(
+        place_window(window_at(1500, 300), Some(&laptop), &both, Some(&laptop)),
+        Placement::Visible
+    );
+    // 右端からはみ出していても 48px 以上見えていればそのまま
+    assert_eq!(
+        place_window(window_at(1920 + 2560 - 60, 500), None, &both, None),
+        Placement::Visible
+    );
+    // 左・上にあるモニター（負の座標）
+    let left = monitor("Left", -2560, -360, 2560, 1440);
+    assert_eq!(
+        place_window(
+            window_at(-2000, -300),
+            Some(&left),
+            &[laptop.clone(), left.clone()],
+            None
+        ),
+        Placement::Visible
+    );
+    // モニター一覧が取れなければ判断しない
+    assert_eq!(
+        place_window(window_at(99999, 99999), None, &[], None),
+        Placement::Visible
+    );
+}
+
+#[test]
+fn test_place_window_rescues_off_screen_positions() {
+    let laptop = monitor("Built-in", 0, 0, 1920, 1080);
+    let external = monitor("DELL", 1920, 0, 2560, 1440);
+    let laptop_only = [laptop.clone()];
+
+    // 外部モニターを外した: 保存時のモニターがないので main のモニター中央へ
+    assert_eq!(
+        place_window(
+            window_at(2600, 400),
+            Some(&external),
+            &laptop_only,
+            Some(&laptop)
+        ),
+        Placement::Centered { x: 560, y: 440 }
+    );
+    // 代わりのモニターの指定がなければ先頭のモニター
+    assert_eq!(
+        place_window(window_at(-5000, 0), None, &laptop_only, None),
+        Placement::Centered { x: 560, y: 440 }
+    );
+    // ほとんど画面外（右に 10px だけ見えている）ならそのモニター内に寄せる
+    assert_eq!(
+        place_window(window_at(-790, 500), None, &laptop_only, None),
+        Placement::Clamped { x: 0, y: 500 }
+    );
+    // 下端から 20px だけ見えている
+    assert_eq!(
+        place_window(window_at(300, 1060), None, &laptop_only, None),
+        Placement::Clamped { x: 300, y: 880 }
+    );
+    // 外部モニターを右から左へ並べ替えた: 同じモニターの同じ相対位置へ
+    let moved = monitor("DELL", -2560, 0, 2560, 1440);
+    assert_eq!(
+        place_window(
+            window_at(2020, 300),
+            Some(&external),
+            &[laptop.clone(), moved],
+            Some(&laptop)
+        ),
+        Placement::Clamped { x: -2460, y: 300 }
+    );
+    // 解像度が下がって相対位置が収まらないときはモニター内に収める
+    let smaller = monitor("DELL", 1920, 1080, 1280, 720);
+    assert_eq!(
+        place_window(
+            window_at(1920 + 2000, 1300),
+            Some(&external),
+            &[laptop.clone(), smaller],
+            None
+        ),
+        Placement::Clamped {
+            x: 1920 + 480,
+            y: 1080 + 520
+        }
+    );
+    // ウィンドウがモニターより大きければ左上に合わせる
+    let tiny = monitor("Tiny", 0, 0, 640, 480);
+    assert_eq!(
+        place_window(
+            Rect {
+                x: 5000,
+                y: 5000,
+                width: 1000,
+                height: 600
+            },
+            None,
+            &[tiny],
+            None
+        ),
+        Placement::Centered { x: 0, y: 0 }
+    );
+    // 名前のないモニターは照合しない
+    let unnamed_saved = MonitorInfo {
+        name: None,
+        ..external.clone()
+    };
+    let unnamed_now = MonitorInfo {
+        name: None,
+        ..monitor("", 0, 1080, 1920, 1080)
+    };
+    assert_eq!(
+        place_window(
+            window_at(2600, 400),
+            Some(&unnamed_saved),
+            &[laptop.clone(), unnamed_now],
+            Some(&laptop)
+        ),
+        Placement::Centered { x: 560, y: 440 }
+    );
+}
+
+#[test]
+fn test_monitor_layout_profiles() {
+    let laptop = MonitorInfo {
+        scale_factor: 2.0,
+        ..monitor("Built-in", 0, 0, 2880, 1800)
+    };
+    let desk = monitor("DELL", 2880, 0, 2560, 1440);
+    let laptop_only = MonitorLayout::new(vec![laptop.clone()]);
+    let docked = MonitorLayout::new(vec![desk.clone(), laptop.clone()]);
+
+    // 並び順や名前に左右されず、サイズ・倍率・位置が変われば別の構成
+    assert_eq!(docked.fingerprint(), "2880x1800@2+0+0,2560x1440@1+2880+0");
+    assert_eq!(
+        MonitorLayout::new(vec![laptop.clone(), desk.clone()]).fingerprint(),
+        docked.fingerprint()
+    );
+    let renamed = MonitorInfo {
+        name: Some("Projector".to_string()),
+        ..desk.clone()
+    };
+    assert_eq!(
+        MonitorLayout::new(vec![laptop.clone(), renamed]).fingerprint(),
+        docked.fingerprint()
+    );
+    let rescaled = MonitorInfo {
+        scale_factor: 1.5,
+        ..desk.clone()
+    };
+    assert_ne!(
+        MonitorLayout::new(vec![laptop.clone(), rescaled]).fingerprint(),
+        docked.fingerprint()
+    );
+    let above = MonitorInfo {
+        x: 0,
+        y: -1440,
+        ..desk
+    };
+    assert_ne!(
+        MonitorLayout::new(vec![laptop.clone(), above]).fingerprint(),
+        docked.fingerprint()
+    );
+    assert_eq!(
+        docked.description(),
+        "2 displays: Built-in 2880x1800, DELL 2560x1440"
+    );
+
+    let at = |x: i32, y: i32| WindowState {
+        x: Some(x),
+        y: Some(y),
+        width: Some(400),
+        height: Some(200),
+        ..Default::default()
+    };
+    let mut file = WindowStateFile::default();
+    file.remember(
+        "layer",
+        WindowState {
+            display_mode: Some(DisplayMode::Minimal),
+            ..at(3000, 100)
+        },
+        Some(&docked),
+    );
+    file.remember("layer", at(100, 100), Some(&laptop_only));
+
+    // 構成ごとの配置が優先され、最後の配置は初めての構成で使う
+    assert_eq!(
+        file.saved_state(Some(&docked), "layer").unwrap().x,
+        Some(3000)
+    );
+    assert_eq!(
+        file.saved_state(Some(&laptop_only), "layer").unwrap().x,
+        Some(100)
+    );
+    let projector = MonitorLayout::new(vec![laptop, monitor("Projector", 2880, 0, 1920, 1080)]);
+    assert_eq!(file.profile_state(&projector, "layer"), None);
+    assert_eq!(
+        file.saved_state(Some(&projector), "layer").unwrap().x,
+        Some(100)
+    );
+    assert_eq!(file.saved_state(None, "main"), None);
+    // 表示モードは構成をまたいで引き継ぐ
+    assert_eq!(
+        file.windows["layer"].display_mode,
+        Some(DisplayMode::Minimal)
+    );
+    assert_eq!(
+        file.profiles[&laptop_only.fingerprint()].description,
+        "1 display: Built-in 2880x1800"
+    );
+
+    let json = serde_json::to_string(&file).unwrap();
+    assert_eq!(parse_window_state_file(&json), Ok(file));
+    // version 1 のファイルは構成ごとの配置なしで読み込む
+    let v1 = parse_window_state_file(r#"{"version": 1, "windows": {"main": {"x": 10}}}"#).unwrap();
+    assert!(v1.profiles.is_empty());
+    assert_eq!(v1.windows["main"].x, Some(10));
+}
+
+#[test]
+fn test_timeup_monitors() {
+    let laptop = MonitorInfo {
+        scale_factor: 2.0,
+        ..monitor("Built-in", 0, 0, 2880, 1800)
+    };
+    let desk = monitor("DELL", 2880, 0, 2560, 1440);
+    let monitors = [laptop.clone(), desk.clone()];
+
+    assert_eq!(
+        timeup_monitors(&TimeupScreen::All, &monitors, Some(&desk)),
+        monitors.to_vec()
+    );
+    assert_eq!(
+        timeup_monitors(&TimeupScreen::Main, &monitors, Some(&desk)),
+        vec![desk.clone()]
+    );
+    assert_eq!(
+        timeup_monitors(
+            &TimeupScreen::Named("Built-in".to_string()),
+            &monitors,
+            Some(&desk)
+        ),
+        vec![laptop.clone()]
+    );
+    // 外したモニターを指定していたら main ウィンドウのモニター、それも取れなければ先頭
+    let projector = TimeupScreen::Named("Projector".to_string());
+    assert_eq!(
+        timeup_monitors(&projector, &monitors, Some(&desk)),
+        vec![desk.clone()]
+    );
+    assert_eq!(
+        timeup_monitors(&projector, &monitors, None),
+        vec![laptop.clone()]
+    );
+    assert!(timeup_monitors(&TimeupScreen::All, &[], None).is_empty());
+
+    // モニターごとの倍率で論理サイズを求める
+    assert_eq!(
+        TimeupGeometry::covering(&laptop),
+        TimeupGeometry {
+            x: 0,
+            y: 0,
+            width: 1440.0,
+            height: 900.0,
+            scale_factor: 2.0,
+        }
+    );
+    let desk_geometry = TimeupGeometry::covering(&desk);
+    assert_eq!((desk_geometry.x, desk_geometry.width), (2880, 2560.0));
+
+    assert_eq!(timeup_label(0), "timeup");
+    assert_eq!(timeup_label(2), "timeup-2");
+    assert!(is_timeup_label("timeup"));
+    assert!(is_timeup_label("timeup-1"));
+    assert!(!is_timeup_label("timeup-x"));
+    assert!(!is_timeup_label("layer"));
+
+    assert_eq!(
+        serde_json::from_str::<TimeupScreen>(r#"{"kind":"named","name":"DELL"}"#).unwrap(),
+        TimeupScreen::Named("DELL".to_string())
+    );
+    assert_eq!(
+        serde_json::from_str::<TimeupScreen>(r#"{"kind":"all"}"#).unwrap(),
+        TimeupScreen::All
+    );
+}
+
+#[test]
+fn test_escalation_plan() {
+    use EscalationStep::*;
+
+    // 既定ではこれまでどおり点滅とフルスクリーンをすぐに出す
+    let policy = EscalationPolicy::default();
+    assert_eq!(policy.validate(), Ok(()));
+    assert_eq!(
+        policy.plan(true),
+        vec![(Duration::ZERO, LayerFlash), (Duration::ZERO, TimeupWindow)]
+    );
+    // ポモドーロの休憩明けなどタイマー側で Time Up ウィンドウを出さない場合
+    assert_eq!(policy.plan(false), vec![(Duration::ZERO, LayerFlash)]);
+
+    // ゆるい締切: 点滅 → 30 秒後に通知 → 2 分後にフルスクリーン
+    let soft = EscalationPolicy {
+        notification: true,
+        notification_delay_secs: 30,
+        timeup_window_delay_secs: 120,
+        ..EscalationPolicy::default()
+    };
+    assert_eq!(
+        soft.plan(true),
+        vec![
+            (Duration::ZERO, LayerFlash),
+            (Duration::from_secs(30), Notification),
+            (Duration::from_secs(120), TimeupWindow),
+        ]
+    );
+    // 待ち時間の順に並べ、同じなら点滅 → 通知 → ウィンドウ
+    let reordered = EscalationPolicy {
+        notification_delay_secs: 120,
+        timeup_window_delay_secs: 0,
+        ..soft.clone()
+    };
+    assert_eq!(
+        reordered.plan(true),
+        vec![
+            (Duration::ZERO, LayerFlash),
+            (Duration::ZERO, TimeupWindow),
+            (Duration::from_secs(120), Notification),
+        ]
+    );
+    // 段階はすべて省ける
+    let quiet = EscalationPolicy {
+        layer_flash: false,
+        timeup_window: false,
+        ..EscalationPolicy::default()
+    };
+    assert!(quiet.plan(true).is_empty());
+
+    assert!(EscalationPolicy {
+        snooze_secs: 0,
+        ..EscalationPolicy::default()
+    }
+    .validate()
+    .is_err());
+    assert!(EscalationPolicy {
+        timeup_window_delay_secs: MAX_ESCALATION_DELAY_SECS + 1,
+        ..EscalationPolicy::default()
+    }
+    .validate()
+    .is_err());
+
+    let policy: EscalationPolicy = serde_json::from_str(
+        r#"{"layerFlash":false,"notification":true,"notificationDelaySecs":10,"snoozeSecs":600}"#,
+    )
+    .unwrap();
+    assert!(!policy.layer_flash && policy.notification && policy.timeup_window);
+    assert_eq!(
+        (policy.notification_delay_secs, policy.snooze_secs),
+        (10, 600)
+    );
+
+    let default_timer = AlertSource::Timer {
+        id: DEFAULT_TIMER_ID.to_string(),
+        name: "Timer".to_string(),
+    };
+    assert_eq!(default_timer.display_name(), None);
+    let tea = AlertSource::Timer {
+        id: "timer-2".to_string(),
+        name: "Tea".to_string(),
+    };
+    assert_eq!(tea.display_name(), Some("Tea".to_string()));
+    let standup = AlertSource::Alarm {
+        label: "Standup".to_string(),
+    };
+    assert_eq!(standup.display_name(), Some("Standup".to_string()));
+}
+
 #[cfg(debug_assertions)]
 #[test]
 fn test_find_available_port_debug() {
@@ -99,6 +629,7 @@ fn test_window_state_equality() {
         y: Some(200),
         width: Some(800),
         height: Some(600),
+        ..Default::default()
     };
 
     let state2 = WindowState {
@@ -106,6 +637,7 @@ fn test_window_state_equality() {
         y: Some(200),
         width: Some(800),
         height: Some(600),
+        ..Default::default()
     };
 
     let state3 = WindowState {
@@ -113,6 +645,7 @@ fn test_window_state_equality() {
         y: Some(300),
         width: Some(900),
         height: Some(700),
+        ..Default::default()
     };
 
     // 同じ値の場合は等しい
@@ -127,3 +660,1887 @@ fn test_window_state_equality() {
     assert_ne!(state1.width, state3.width);
     assert_ne!(state1.height, state3.height);
 }
+
+#[test]
+fn test_timer_engine_counts_down_from_deadline() {
+    let mut engine = TimerEngine::new();
+    let t0 = Instant::now();
+    engine.set(Duration::from_secs(90));
+    assert!(engine.start(t0));
+
+    // 開始直後は切り上げで 01:30 を表示する
+    let snapshot = engine.snapshot(t0 + Duration::from_millis(1));
+    assert_eq!(snapshot.status, TimerStatus::Running);
+    assert_eq!((snapshot.minutes, snapshot.seconds), (1, 30));
+
+    let snapshot = engine.snapshot(t0 + Duration::from_secs(31));
+    assert_eq!((snapshot.minutes, snapshot.seconds), (0, 59));
+}
+
+#[test]
+fn test_timer_engine_pause_and_resume() {
+    let mut engine = TimerEngine::new();
+    let t0 = Instant::now();
+    engine.set(Duration::from_secs(60));
+    engine.start(t0);
+    assert!(engine.pause(t0 + Duration::from_secs(20)));
+
+    // 一時停止中は時間が進まない
+    let paused = engine.snapshot(t0 + Duration::from_secs(500));
+    assert_eq!(paused.status, TimerStatus::Paused);
+    assert_eq!(paused.remaining_ms, 40_000);
+
+    assert!(engine.resume(t0 + Duration::from_secs(500)));
+    let resumed = engine.snapshot(t0 + Duration::from_secs(510));
+    assert_eq!(resumed.remaining_ms, 30_000);
+}
+
+#[test]
+fn test_timer_engine_finishes_once_and_restores_duration() {
+    let mut engine = TimerEngine::new();
+    let t0 = Instant::now();
+    engine.set(Duration::from_secs(5));
+    engine.start(t0);
+
+    assert!(!engine.tick(t0 + Duration::from_secs(4)));
+    assert!(engine.tick(t0 + Duration::from_secs(5)));
+    assert!(!engine.tick(t0 + Duration::from_secs(6)));
+
+    let snapshot = engine.snapshot(t0 + Duration::from_secs(6));
+    assert_eq!(snapshot.status, TimerStatus::Finished);
+    assert_eq!((snapshot.minutes, snapshot.seconds), (0, 5));
+}
+
+#[test]
+fn test_timer_engine_does_not_start_at_zero() {
+    let mut engine = TimerEngine::new();
+    let t0 = Instant::now();
+    assert!(!engine.start(t0));
+
+    engine.set(Duration::from_secs(10));
+    engine.start(t0);
+    engine.reset();
+    let snapshot = engine.snapshot(t0);
+    assert_eq!(snapshot.status, TimerStatus::Idle);
+    assert_eq!(snapshot.remaining_ms, 0);
+}
+
+#[test]
+fn test_timer_state_roundtrip_while_running() {
+    let mut registry = TimerRegistry::new();
+    let t0 = Instant::now();
+    let wall0 = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    {
+        let timer = registry.get_mut(DEFAULT_TIMER_ID).unwrap();
+        timer.engine.set(Duration::from_secs(300));
+        timer.engine.start(t0);
+    }
+
+    let persisted = registry.to_persisted(t0 + Duration::from_secs(100), wall0);
+    assert_eq!(persisted.version, TIMER_STATE_VERSION);
+    assert_eq!(persisted.timers[0].state.status, TimerStatus::Running);
+    assert_eq!(persisted.timers[0].state.remaining_ms, 200_000);
+
+    let json = serde_json::to_string(&persisted).expect("Failed to serialize timer state");
+    let loaded = parse_timer_state_file(&json).expect("Failed to parse timer state");
+
+    // 60 秒後に再起動した想定
+    let t1 = Instant::now();
+    let (restored, reports) =
+        TimerRegistry::from_persisted(&loaded, t1, wall0 + Duration::from_secs(60))
+            .expect("Failed to restore timer state");
+    assert!(!reports[0].expired_while_closed);
+    let snapshot = restored.get(DEFAULT_TIMER_ID).unwrap().engine.snapshot(t1);
+    assert_eq!(snapshot.status, TimerStatus::Running);
+    assert_eq!(snapshot.remaining_ms, 140_000);
+    assert_eq!(snapshot.duration_ms, 300_000);
+}
+
+#[test]
+fn test_timer_state_expired_while_closed() {
+    let mut engine = TimerEngine::new();
+    let t0 = Instant::now();
+    let wall0 = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    engine.set(Duration::from_secs(60));
+    engine.start(t0);
+    let persisted = engine.to_persisted(t0, wall0);
+
+    let (restored, overdue) =
+        TimerEngine::from_persisted(&persisted, t0, wall0 + Duration::from_secs(90))
+            .expect("Failed to restore timer state");
+    assert_eq!(overdue, Some(Duration::from_secs(30)));
+    let snapshot = restored.snapshot(t0);
+    assert_eq!(snapshot.status, TimerStatus::Finished);
+    assert_eq!(snapshot.remaining_ms, 60_000);
+}
+
+#[test]
+fn test_timer_state_expired_long_ago_can_still_overtime() {
+    let mut engine = TimerEngine::new();
+    let t0 = Instant::now();
+    let wall0 = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+    engine.set(Duration::from_secs(60));
+    engine.start(t0);
+    let persisted = engine.to_persisted(t0, wall0);
+
+    // 起動からの単調時計より古い締切でも終了時刻を失わない
+    let long_ago = Duration::from_secs(100 * 365 * 24 * 3600);
+    let (mut restored, overdue) = TimerEngine::from_persisted(&persisted, t0, wall0 + long_ago)
+        .expect("Failed to restore timer state");
+    assert!(overdue.is_some());
+    assert_eq!(restored.status(), TimerStatus::Finished);
+    assert!(restored.overdue_at(t0).is_some());
+    assert!(restored.start_overtime());
+    assert_eq!(restored.status(), TimerStatus::Overtime);
+}
+
+#[test]
+fn test_timer_state_paused_is_restored_as_is() {
+    let mut engine = TimerEngine::new();
+    let t0 = Instant::now();
+    let wall0 = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    engine.set(Duration::from_secs(60));
+    engine.start(t0);
+    engine.pause(t0 + Duration::from_secs(15));
+    let persisted = engine.to_persisted(t0, wall0);
+    assert_eq!(persisted.deadline_unix_ms, None);
+
+    let (restored, overdue) =
+        TimerEngine::from_persisted(&persisted, t0, wall0 + Duration::from_secs(3600))
+            .expect("Failed to restore timer state");
+    assert_eq!(overdue, None);
+    let snapshot = restored.snapshot(t0);
+    assert_eq!(snapshot.status, TimerStatus::Paused);
+    assert_eq!(snapshot.remaining_ms, 45_000);
+}
+
+#[test]
+fn test_timer_state_rejects_unknown_version() {
+    let json = format!(
+        r#"{{"version": {}, "timers": []}}"#,
+        TIMER_STATE_VERSION + 1
+    );
+    assert!(parse_timer_state_file(&json).is_err());
+    assert!(parse_timer_state_file("{}").is_err());
+}
+
+#[test]
+fn test_timer_state_migrates_single_timer_v1() {
+    let json = r#"{
+        "version": 1,
+        "status": "paused",
+        "durationMs": 60000,
+        "remainingMs": 42000,
+        "deadlineUnixMs": null,
+        "savedAtUnixMs": 0
+    }"#;
+    let timers = parse_timer_state_file(json).expect("Failed to migrate v1 timer state");
+    assert_eq!(timers.len(), 1);
+    assert_eq!(timers[0].id, DEFAULT_TIMER_ID);
+    assert_eq!(timers[0].state.remaining_ms, 42_000);
+    assert_eq!(timers[0].alarm, TimerAlarm::default());
+}
+
+#[test]
+fn test_timer_registry_runs_timers_independently() {
+    let mut registry = TimerRegistry::new();
+    let t0 = Instant::now();
+    let standup = registry
+        .create("Stand-up", Duration::from_secs(900), TimerAlarm::default())
+        .id
+        .clone();
+    let tea = registry
+        .create(
+            "Tea",
+            Duration::from_secs(180),
+            TimerAlarm {
+                sound: Some("gong.mp3".to_string()),
+                show_timeup_window: false,
+                ..TimerAlarm::default()
+            },
+        )
+        .id
+        .clone();
+    assert_ne!(standup, tea);
+
+    registry.get_mut(&standup).unwrap().engine.start(t0);
+    registry.get_mut(&tea).unwrap().engine.start(t0);
+    registry
+        .get_mut(&standup)
+        .unwrap()
+        .engine
+        .pause(t0 + Duration::from_secs(10));
+
+    // 紅茶タイマーだけが終了する
+    let finished = registry.tick(t0 + Duration::from_secs(180));
+    assert_eq!(finished, vec![tea.clone()]);
+
+    let snapshots = registry.snapshots(t0 + Duration::from_secs(180));
+    assert_eq!(snapshots.len(), 3);
+    let standup_snapshot = snapshots.iter().find(|s| s.id == standup).unwrap();
+    assert_eq!(standup_snapshot.timer.status, TimerStatus::Paused);
+    assert_eq!(standup_snapshot.timer.remaining_ms, 890_000);
+    let tea_snapshot = snapshots.iter().find(|s| s.id == tea).unwrap();
+    assert_eq!(tea_snapshot.name, "Tea");
+    assert_eq!(tea_snapshot.alarm.sound.as_deref(), Some("gong.mp3"));
+}
+
+#[test]
+fn test_timer_registry_remove() {
+    let mut registry = TimerRegistry::new();
+    let id = registry
+        .create("Deploy", Duration::from_secs(600), TimerAlarm::default())
+        .id
+        .clone();
+
+    assert!(registry.remove(DEFAULT_TIMER_ID).is_err());
+    assert!(registry.remove(&id).is_ok());
+    assert!(registry.remove(&id).is_err());
+    assert!(registry.get(&id).is_none());
+}
+
+#[test]
+fn test_timer_registry_restore_keeps_ids_unique() {
+    let mut registry = TimerRegistry::new();
+    let t0 = Instant::now();
+    let wall0 = SystemTime::now();
+    registry.create("A", Duration::from_secs(60), TimerAlarm::default());
+    let b = registry
+        .create("B", Duration::from_secs(60), TimerAlarm::default())
+        .id
+        .clone();
+    let persisted = registry.to_persisted(t0, wall0);
+
+    let (mut restored, reports) = TimerRegistry::from_persisted(&persisted.timers, t0, wall0)
+        .expect("Failed to restore timer state");
+    assert_eq!(reports.len(), 3);
+    let c = restored
+        .create("C", Duration::from_secs(60), TimerAlarm::default())
+        .id
+        .clone();
+    assert_ne!(b, c);
+    assert_eq!(restored.snapshots(t0).len(), 4);
+}
+
+#[test]
+fn test_timer_registry_restore_adds_missing_default() {
+    let (registry, reports) = TimerRegistry::from_persisted(&[], Instant::now(), SystemTime::now())
+        .expect("Failed to restore timer state");
+    assert!(reports.is_empty());
+    assert!(registry.get(DEFAULT_TIMER_ID).is_some());
+}
+
+#[test]
+fn test_pomodoro_cycle_phases() {
+    let config = PomodoroConfig {
+        rounds_before_long_break: 2,
+        ..PomodoroConfig::default()
+    };
+    let mut cycle = PomodoroCycle::new(config);
+    assert_eq!(cycle.phase, PomodoroPhase::Work);
+    assert_eq!(cycle.phase_duration(), Duration::from_secs(25 * 60));
+
+    let expected = [
+        (PomodoroPhase::ShortBreak, 1),
+        (PomodoroPhase::Work, 2),
+        (PomodoroPhase::LongBreak, 2),
+        (PomodoroPhase::Work, 3),
+        (PomodoroPhase::ShortBreak, 3),
+    ];
+    for (phase, round) in expected {
+        cycle.advance();
+        assert_eq!((cycle.phase, cycle.round), (phase, round));
+    }
+    assert_eq!(cycle.phase_duration(), Duration::from_secs(5 * 60));
+}
+
+#[test]
+fn test_pomodoro_skips_timeup_only_after_breaks() {
+    let mut cycle = PomodoroCycle::new(PomodoroConfig::default());
+    assert!(cycle.shows_timeup_at_end());
+    cycle.advance();
+    assert!(!cycle.shows_timeup_at_end());
+
+    cycle.config.skip_timeup_on_break_end = false;
+    assert!(cycle.shows_timeup_at_end());
+}
+
+#[test]
+fn test_pomodoro_keeps_timer_alarm_after_stop() {
+    let user_alarm = TimerAlarm {
+        sound: Some("gong.mp3".to_string()),
+        show_timeup_window: true,
+        overtime: true,
+    };
+    // 開始 → 作業 → 休憩 → 作業 → 停止
+    let mut alarm = user_alarm.clone();
+    let mut cycle = PomodoroCycle::new(PomodoroConfig::default()).saving_alarm(&alarm);
+    alarm = cycle.phase_alarm(&alarm);
+    assert_eq!(alarm, user_alarm);
+    cycle.advance();
+    alarm = cycle.phase_alarm(&alarm);
+    // 休憩明けは Time Up ウィンドウだけを止め、音と超過計測は変えない
+    assert!(!alarm.show_timeup_window);
+    assert_eq!(alarm.sound, user_alarm.sound);
+    assert!(alarm.overtime);
+    cycle.advance();
+    alarm = cycle.phase_alarm(&alarm);
+    cycle.advance();
+    alarm = cycle.phase_alarm(&alarm);
+    assert_eq!(cycle.restored_alarm(&alarm), user_alarm);
+
+    // Time Up ウィンドウを出さない設定のタイマーでは作業明けでも出さない
+    let quiet = TimerAlarm {
+        show_timeup_window: false,
+        ..TimerAlarm::default()
+    };
+    let cycle = PomodoroCycle::new(PomodoroConfig::default()).saving_alarm(&quiet);
+    assert!(!cycle.phase_alarm(&quiet).show_timeup_window);
+    assert_eq!(cycle.restored_alarm(&cycle.phase_alarm(&quiet)), quiet);
+}
+
+#[test]
+fn test_pomodoro_config_validation() {
+    assert!(PomodoroConfig::default().validate().is_ok());
+    let config: PomodoroConfig = serde_json::from_str(r#"{"workSecs": 0}"#).unwrap();
+    assert!(config.validate().is_err());
+    let config: PomodoroConfig = serde_json::from_str(r#"{"roundsBeforeLongBreak": 0}"#).unwrap();
+    assert!(config.validate().is_err());
+}
+
+fn agenda() -> Sequence {
+    let segment = |label: &str, secs| SequenceSegment {
+        label: label.to_string(),
+        duration_secs: secs,
+        alarm: TimerAlarm::default(),
+    };
+    Sequence {
+        id: "standup".to_string(),
+        name: "Standup".to_string(),
+        segments: vec![
+            segment("Intro", 120),
+            segment("Demo", 600),
+            segment("Q&A", 300),
+        ],
+    }
+}
+
+#[test]
+fn test_sequence_run_steps_through_segments() {
+    let mut run = SequenceRun::new(agenda());
+    assert_eq!(run.current().label, "Intro");
+
+    run.back();
+    assert_eq!(run.index, 0);
+
+    assert!(run.advance());
+    assert!(run.advance());
+    assert_eq!(run.current().label, "Q&A");
+    assert!(!run.advance());
+    assert_eq!(run.index, 2);
+
+    run.back();
+    assert_eq!(run.current().label, "Demo");
+}
+
+#[test]
+fn test_sequence_run_keeps_saved_alarm() {
+    let user_alarm = TimerAlarm {
+        sound: Some("gong.mp3".to_string()),
+        show_timeup_window: false,
+        overtime: true,
+    };
+    let mut run = SequenceRun::new(agenda()).saving_alarm(&user_alarm);
+    assert_ne!(run.current().alarm, user_alarm);
+    while run.advance() {}
+    run.back();
+    assert_eq!(run.saved_alarm, user_alarm);
+
+    // 覚えたアラームはフロントエンドに送らない
+    let json = serde_json::to_value(&run).expect("Failed to serialize run");
+    assert!(json.get("savedAlarm").is_none());
+    assert!(json.get("saved_alarm").is_none());
+}
+
+#[test]
+fn test_sequence_validation() {
+    assert!(agenda().validate().is_ok());
+
+    let mut empty = agenda();
+    empty.segments.clear();
+    assert!(empty.validate().is_err());
+
+    let mut zero = agenda();
+    zero.segments[1].duration_secs = 0;
+    assert!(zero.validate().is_err());
+
+    assert!(is_valid_sequence_id("hiit_4x-30"));
+    assert!(!is_valid_sequence_id(""));
+    assert!(!is_valid_sequence_id("../window_state"));
+}
+
+#[test]
+fn test_sequence_file_defaults_alarm() {
+    let json = r#"{"name":"HIIT","segments":[{"label":"Work","durationSecs":30}]}"#;
+    let sequence: Sequence = serde_json::from_str(json).expect("Failed to parse sequence");
+    assert!(sequence.id.is_empty());
+    assert_eq!(sequence.segments[0].alarm, TimerAlarm::default());
+}
+
+#[test]
+fn test_stopwatch_laps_and_splits() {
+    let mut stopwatch = Stopwatch::default();
+    let t0 = Instant::now();
+    assert!(stopwatch.lap(t0).is_none());
+
+    assert!(stopwatch.start(t0));
+    assert!(!stopwatch.start(t0));
+    let first = stopwatch.lap(t0 + Duration::from_millis(1_250)).unwrap();
+    assert_eq!(
+        (first.number, first.lap_ms, first.split_ms),
+        (1, 1_250, 1_250)
+    );
+
+    // 一時停止中の時間はラップに含めない
+    stopwatch.pause(t0 + Duration::from_millis(2_000));
+    assert!(stopwatch.lap(t0 + Duration::from_millis(3_000)).is_none());
+    stopwatch.start(t0 + Duration::from_millis(5_000));
+    let second = stopwatch.lap(t0 + Duration::from_millis(5_500)).unwrap();
+    assert_eq!(
+        (second.number, second.lap_ms, second.split_ms),
+        (2, 1_250, 2_500)
+    );
+    assert_eq!(stopwatch.laps().len(), 2);
+
+    stopwatch.reset();
+    assert!(!stopwatch.is_running());
+    assert!(stopwatch.laps().is_empty());
+    assert_eq!(stopwatch.elapsed(t0), Duration::ZERO);
+}
+
+#[test]
+fn test_format_elapsed() {
+    assert_eq!(format_elapsed(Duration::ZERO), "00:00.0");
+    assert_eq!(format_elapsed(Duration::from_millis(83_456)), "01:23.4");
+    assert_eq!(format_elapsed(Duration::from_millis(3_599_999)), "59:59.9");
+    assert_eq!(format_elapsed(Duration::from_secs(3_600 + 62)), "1:01:02");
+    assert_eq!(
+        format_elapsed(Duration::from_secs(100 * 3_600)),
+        "100:00:00"
+    );
+}
+
+#[test]
+fn test_timer_registry_counts_overtime() {
+    let mut registry = TimerRegistry::new();
+    let t0 = Instant::now();
+    let alarm = TimerAlarm {
+        overtime: true,
+        ..TimerAlarm::default()
+    };
+    let id = registry
+        .create("Talk", Duration::from_secs(60), alarm)
+        .id
+        .clone();
+    registry.get_mut(&id).unwrap().engine.start(t0);
+
+    let finished = registry.tick(t0 + Duration::from_secs(60));
+    assert_eq!(finished, vec![id.clone()]);
+
+    let timer = registry.get(&id).unwrap();
+    assert_eq!(timer.engine.status(), TimerStatus::Overtime);
+    let snapshot = timer.engine.snapshot(t0 + Duration::from_millis(143_500));
+    assert_eq!(snapshot.overrun_ms, 83_500);
+    assert_eq!((snapshot.minutes, snapshot.seconds), (1, 23));
+    // 超過計測中に再度 finished にはならない
+    assert!(registry.tick(t0 + Duration::from_secs(200)).is_empty());
+
+    let engine = &mut registry.get_mut(&id).unwrap().engine;
+    assert!(engine.pause(t0 + Duration::from_secs(150)));
+    assert_eq!(engine.status(), TimerStatus::Finished);
+    assert_eq!(engine.overrun_at(t0 + Duration::from_secs(150)), None);
+}
+
+#[test]
+fn test_overtime_survives_restore() {
+    let mut registry = TimerRegistry::new();
+    let t0 = Instant::now();
+    let wall0 = SystemTime::now();
+    let alarm = TimerAlarm {
+        overtime: true,
+        ..TimerAlarm::default()
+    };
+    let id = registry
+        .create("Talk", Duration::from_secs(60), alarm)
+        .id
+        .clone();
+    registry.get_mut(&id).unwrap().engine.start(t0);
+    let persisted = registry.to_persisted(t0, wall0);
+
+    // 終了中に締切を 30 秒過ぎていた
+    let t1 = t0 + Duration::from_secs(90);
+    let wall1 = wall0 + Duration::from_secs(90);
+    let (restored, reports) = TimerRegistry::from_persisted(&persisted.timers, t1, wall1)
+        .expect("Failed to restore timer state");
+    let report = reports.iter().find(|r| r.id == id).unwrap();
+    assert!(report.expired_while_closed);
+    assert_eq!(report.status, TimerStatus::Overtime);
+    let overrun = restored.get(&id).unwrap().engine.overrun_at(t1).unwrap();
+    assert!((29_900..=30_100).contains(&(overrun.as_millis() as u64)));
+
+    // 超過計測中の状態も保存・復元できる
+    let persisted = restored.to_persisted(t1, wall1);
+    let (again, _) = TimerRegistry::from_persisted(
+        &persisted.timers,
+        t1 + Duration::from_secs(5),
+        wall1 + Duration::from_secs(5),
+    )
+    .expect("Failed to restore timer state");
+    let overrun = again
+        .get(&id)
+        .unwrap()
+        .engine
+        .overrun_at(t1 + Duration::from_secs(5))
+        .unwrap();
+    assert!((34_900..=35_100).contains(&(overrun.as_millis() as u64)));
+}
+
+#[test]
+fn test_session_record_from_finished_timer() {
+    let mut registry = TimerRegistry::new();
+    let id = registry
+        .create("Tea", Duration::from_secs(180), TimerAlarm::default())
+        .id
+        .clone();
+    let before = unix_millis(SystemTime::now());
+    let record = SessionRecord::finished(registry.get(&id).unwrap(), Duration::from_secs(5));
+    assert_eq!(record.timer_id, id);
+    assert_eq!(record.timer_name, "Tea");
+    assert_eq!(record.duration_ms, 180_000);
+    assert_eq!(record.overrun_ms, 5_000);
+    assert!(record.finished_at_unix_ms + 5_000 <= before + 1_000);
+}
+
+#[test]
+fn test_session_records_are_capped() {
+    let record = |n: u64| SessionRecord {
+        timer_id: DEFAULT_TIMER_ID.to_string(),
+        timer_name: "Timer".to_string(),
+        duration_ms: 60_000,
+        finished_at_unix_ms: n,
+        overrun_ms: n,
+    };
+    let mut records: Vec<SessionRecord> = (0..MAX_SESSION_RECORDS as u64).map(record).collect();
+    push_session_record(&mut records, record(MAX_SESSION_RECORDS as u64));
+    assert_eq!(records.len(), MAX_SESSION_RECORDS);
+    assert_eq!(records[0].finished_at_unix_ms, 1);
+    assert_eq!(
+        records.last().unwrap().overrun_ms,
+        MAX_SESSION_RECORDS as u64
+    );
+}
+
+#[test]
+fn test_clock_time_normalizes_overflow() {
+    assert_eq!(
+        ClockTime::normalize(0, 0, 90),
+        ClockTime {
+            hours: 0,
+            minutes: 1,
+            seconds: 30
+        }
+    );
+    // 旧 UI の上限 99:99 は 1:40:39 になる
+    assert_eq!(ClockTime::normalize(0, 99, 99).to_string(), "1:40:39");
+    assert_eq!(ClockTime::normalize(24, 0, 0).total_secs(), 24 * 3600);
+    assert_eq!(
+        ClockTime::normalize(200, 0, 0).total_secs(),
+        MAX_DURATION_SECS
+    );
+}
+
+#[test]
+fn test_clock_time_format() {
+    assert_eq!(ClockTime::from_secs(0).to_string(), "00:00");
+    assert_eq!(ClockTime::from_secs(59 * 60 + 59).to_string(), "59:59");
+    assert_eq!(ClockTime::from_secs(3600).to_string(), "1:00:00");
+    assert_eq!(
+        ClockTime::from_secs(MAX_DURATION_SECS).to_string(),
+        "99:59:59"
+    );
+}
+
+#[test]
+fn test_timer_engine_snapshot_splits_hours() {
+    let mut engine = TimerEngine::new();
+    let t0 = Instant::now();
+    engine.set(Duration::from_secs(2 * 3600 + 5 * 60 + 7));
+    let snapshot = engine.snapshot(t0);
+    assert_eq!(
+        (snapshot.hours, snapshot.minutes, snapshot.seconds),
+        (2, 5, 7)
+    );
+    assert_eq!(snapshot.display().to_string(), "2:05:07");
+}
+
+#[test]
+fn test_layer_size_grows_with_longer_text() {
+    let (short_w, short_h) = layer_size_for_font(6.0, 5);
+    let (time_up_w, _) = layer_size_for_font(6.0, 7);
+    let (long_w, long_h) = layer_size_for_font(6.0, "-1:00:00".len());
+    // "TIME UP" より短い表示でも幅は縮めない
+    assert_eq!(short_w, time_up_w);
+    assert!(long_w > time_up_w);
+    assert_eq!(short_h, long_h);
+    // 小さいフォントでも最小サイズを保つ
+    assert_eq!(layer_size_for_font(1.0, 5), (320.0, 120.0));
+}
+
+#[test]
+fn test_layer_payloads() {
+    let text = LayerText::for_timer(ClockTime::from_secs(83), false, true, None);
+    assert_eq!(
+        serde_json::to_value(&text).unwrap(),
+        serde_json::json!({"content": "-01:23", "style": "overtime", "label": ""})
+    );
+    let text = LayerText::for_timer(ClockTime::from_secs(0), true, false, Some("Tea".into()));
+    assert_eq!(
+        (text.content.as_str(), text.style),
+        ("TIME UP", LayerTextStyle::Timeup)
+    );
+    // ラベルはそのまま文字列として渡す（ページは textContent に入れる）
+    let label = "'); alert(1); ('".to_string();
+    let text = LayerText::for_timer(ClockTime::from_secs(5), false, false, Some(label.clone()));
+    assert_eq!(text.label, label);
+    assert_eq!(text.style, LayerTextStyle::Normal);
+
+    let style = LayerStyle::sanitized("#fff'); alert(1); ('", "light", 50.0);
+    assert_eq!(style.color, "#00ff66");
+    assert!(style.shadow.contains("255,255,255"));
+    assert_eq!(style.font_size, 20.0);
+    let style = LayerStyle::sanitized(" #12ab34 ", "anything", 0.5);
+    assert_eq!(style.color, "#12ab34");
+    assert!(style.shadow.contains("rgba(0,0,0"));
+    assert_eq!(style.font_size, 1.0);
+    assert_eq!(
+        serde_json::to_value(TimeupInfo { timer_name: None }).unwrap(),
+        serde_json::json!({"timerName": null})
+    );
+}
+
+fn wall_clock_alarm(hour: u32, minute: u32, weekdays: Vec<Weekday>) -> WallClockAlarm {
+    WallClockAlarm {
+        id: "alarm-1".to_string(),
+        label: "Standup".to_string(),
+        hour,
+        minute,
+        weekdays,
+        enabled: true,
+        alarm: TimerAlarm::default(),
+        last_fired_unix_ms: None,
+        armed_unix_ms: None,
+    }
+}
+
+#[test]
+fn test_alarm_one_shot_next_occurrence() {
+    let alarm = wall_clock_alarm(9, 30, Vec::new());
+    // 当日の 9:30 より前なら当日、過ぎていれば翌日
+    let before = New_York.with_ymd_and_hms(2026, 5, 4, 8, 0, 0).unwrap();
+    let after = New_York.with_ymd_and_hms(2026, 5, 4, 9, 30, 0).unwrap();
+    assert_eq!(
+        next_occurrence(&alarm, &before),
+        Some(New_York.with_ymd_and_hms(2026, 5, 4, 9, 30, 0).unwrap())
+    );
+    assert_eq!(
+        next_occurrence(&alarm, &after),
+        Some(New_York.with_ymd_and_hms(2026, 5, 5, 9, 30, 0).unwrap())
+    );
+}
+
+#[test]
+fn test_alarm_weekday_repeat() {
+    let alarm = wall_clock_alarm(7, 0, vec![Weekday::Mon, Weekday::Thu]);
+    // 2026-05-05 は火曜日。次は木曜日、その次は翌週の月曜日
+    let tuesday = New_York.with_ymd_and_hms(2026, 5, 5, 12, 0, 0).unwrap();
+    let thursday = next_occurrence(&alarm, &tuesday).unwrap();
+    assert_eq!(
+        thursday,
+        New_York.with_ymd_and_hms(2026, 5, 7, 7, 0, 0).unwrap()
+    );
+    assert_eq!(
+        next_occurrence(&alarm, &thursday),
+        Some(New_York.with_ymd_and_hms(2026, 5, 11, 7, 0, 0).unwrap())
+    );
+}
+
+#[test]
+fn test_alarm_across_dst_transitions() {
+    // 2026-03-08 は 2:00 が 3:00 に飛ぶので 2:30 は 3:30 として鳴る
+    let gap = wall_clock_alarm(2, 30, Vec::new());
+    let night = New_York.with_ymd_and_hms(2026, 3, 8, 0, 0, 0).unwrap();
+    assert_eq!(
+        next_occurrence(&gap, &night),
+        Some(New_York.with_ymd_and_hms(2026, 3, 8, 3, 30, 0).unwrap())
+    );
+
+    // 2026-11-01 は 1:30 が 2 回あるので早い方（夏時間）で鳴る
+    let ambiguous = wall_clock_alarm(1, 30, Vec::new());
+    let night = New_York.with_ymd_and_hms(2026, 11, 1, 0, 0, 0).unwrap();
+    let fired = next_occurrence(&ambiguous, &night).unwrap();
+    assert_eq!(
+        fired,
+        New_York
+            .from_local_datetime(&fired.naive_local())
+            .earliest()
+            .unwrap()
+    );
+    assert_eq!((fired - night).num_minutes(), 90);
+}
+
+#[test]
+fn test_alarm_due_occurrence_fires_once() {
+    let mut alarm = wall_clock_alarm(9, 0, vec![Weekday::Mon]);
+    let checked = New_York.with_ymd_and_hms(2026, 5, 4, 8, 59, 59).unwrap();
+    let now = New_York.with_ymd_and_hms(2026, 5, 4, 9, 0, 1).unwrap();
+    let due = due_occurrence(&alarm, &checked, &now).unwrap();
+    assert_eq!(due, New_York.with_ymd_and_hms(2026, 5, 4, 9, 0, 0).unwrap());
+
+    // 時計が巻き戻って同じ区間を再確認しても二重には鳴らない
+    alarm.last_fired_unix_ms = Some(due.timestamp_millis());
+    assert_eq!(due_occurrence(&alarm, &checked, &now), None);
+
+    // 無効なアラームは鳴らない
+    alarm.last_fired_unix_ms = None;
+    alarm.enabled = false;
+    assert_eq!(due_occurrence(&alarm, &checked, &now), None);
+}
+
+#[test]
+fn test_alarm_validation() {
+    assert!(wall_clock_alarm(23, 59, Vec::new()).validate().is_ok());
+    assert!(wall_clock_alarm(24, 0, Vec::new()).validate().is_err());
+    assert!(wall_clock_alarm(12, 60, Vec::new()).validate().is_err());
+}
+
+/// 単調時計・壁時計・起動時計を個別に進められるテスト用の時計
+struct FakeClock {
+    monotonic: Cell<Instant>,
+    wall: Cell<SystemTime>,
+    boot: Cell<Duration>,
+}
+
+impl FakeClock {
+    fn new() -> Self {
+        Self {
+            monotonic: Cell::new(Instant::now()),
+            wall: Cell::new(SystemTime::UNIX_EPOCH + Duration::from_secs(1_800_000_000)),
+            boot: Cell::new(Duration::from_secs(86_400)),
+        }
+    }
+
+    /// 起きている間の経過。起動時計は単調時計と同じだけ進む
+    fn advance(&self, monotonic: Duration, wall: Duration) {
+        self.monotonic.set(self.monotonic.get() + monotonic);
+        self.wall.set(self.wall.get() + wall);
+        self.boot.set(self.boot.get() + monotonic);
+    }
+
+    /// スリープ。単調時計だけが止まる
+    fn suspend(&self, duration: Duration) {
+        self.wall.set(self.wall.get() + duration);
+        self.boot.set(self.boot.get() + duration);
+    }
+}
+
+impl Clock for FakeClock {
+    fn monotonic(&self) -> Instant {
+        self.monotonic.get()
+    }
+
+    fn wall(&self) -> SystemTime {
+        self.wall.get()
+    }
+
+    fn boot_time(&self) -> Option<Duration> {
+        Some(self.boot.get())
+    }
+}
+
+#[test]
+fn test_clock_watch_detects_jumps() {
+    let clock = FakeClock::new();
+    let mut watch = ClockWatch::new(&clock, Duration::from_millis(250));
+
+    // 通常の tick では何も検出しない
+    clock.advance(Duration::from_millis(250), Duration::from_millis(260));
+    assert_eq!(watch.check(&clock), None);
+
+    // 単調時計が止まったまま起動時計が 1 時間進んだ = スリープ
+    clock.advance(Duration::from_millis(250), Duration::from_millis(250));
+    clock.suspend(Duration::from_secs(3600));
+    assert_eq!(
+        watch.check(&clock),
+        Some(ClockJump::Suspended(Duration::from_secs(3600)))
+    );
+
+    // 壁時計だけが進んだ = 時計の変更。スリープではない
+    clock.advance(Duration::from_millis(250), Duration::from_secs(3600));
+    assert_eq!(
+        watch.check(&clock),
+        Some(ClockJump::WallClockMovedForward(
+            Duration::from_secs(3600) - Duration::from_millis(250)
+        ))
+    );
+
+    // 両方が大きく進んだ = スリープ中も進む単調時計での復帰
+    clock.advance(Duration::from_secs(600), Duration::from_secs(600));
+    assert_eq!(
+        watch.check(&clock),
+        Some(ClockJump::Stalled(Duration::from_secs(600)))
+    );
+
+    // 壁時計の巻き戻し
+    clock.advance(Duration::from_millis(250), Duration::ZERO);
+    clock.wall.set(clock.wall.get() - Duration::from_secs(120));
+    assert_eq!(
+        watch.check(&clock),
+        Some(ClockJump::WallClockMovedBack(
+            Duration::from_secs(120) + Duration::from_millis(250)
+        ))
+    );
+}
+
+#[test]
+fn test_suspend_resync_finishes_timer_as_missed() {
+    let clock = FakeClock::new();
+    let mut watch = ClockWatch::new(&clock, Duration::from_millis(250));
+    let mut registry = TimerRegistry::new();
+    let long_id = registry
+        .create("Long", Duration::from_secs(3600), TimerAlarm::default())
+        .id
+        .clone();
+    registry
+        .get_mut(DEFAULT_TIMER_ID)
+        .unwrap()
+        .engine
+        .set(Duration::from_secs(60));
+    let t0 = clock.monotonic();
+    registry.get_mut(DEFAULT_TIMER_ID).unwrap().engine.start(t0);
+    registry.get_mut(&long_id).unwrap().engine.start(t0);
+
+    // 10 分スリープ。単調時計は 1 秒しか進まない
+    clock.advance(Duration::from_secs(1), Duration::from_secs(1));
+    clock.suspend(Duration::from_secs(600));
+    let jump = watch.check(&clock).unwrap();
+    let ClockJump::Suspended(suspended) = jump else {
+        panic!("expected suspend, got {:?}", jump);
+    };
+    let now = clock.monotonic();
+    registry.skip_suspended(suspended, now);
+
+    let finished = registry.tick(now);
+    assert_eq!(finished, vec![DEFAULT_TIMER_ID.to_string()]);
+    // 締切を 9 分過ぎて終了したので取りこぼし扱い
+    assert_eq!(registry.missed(&finished, now), finished);
+    // 長いタイマーは壁時計どおり 10 分 1 秒進んでいる
+    let long = registry.get(&long_id).unwrap().engine.snapshot(now);
+    assert_eq!(long.remaining_ms, (3600 - 601) * 1000);
+}
+
+#[test]
+fn test_wall_clock_jump_keeps_remaining_time() {
+    let clock = FakeClock::new();
+    let mut watch = ClockWatch::new(&clock, Duration::from_millis(250));
+    let mut registry = TimerRegistry::new();
+    let t0 = clock.monotonic();
+    let engine = &mut registry.get_mut(DEFAULT_TIMER_ID).unwrap().engine;
+    engine.set(Duration::from_secs(600));
+    engine.start(t0);
+
+    // スリープせずに壁時計だけ 1 時間進んだ（手動変更や NTP）
+    clock.advance(Duration::from_secs(1), Duration::from_secs(3601));
+    let jump = watch.check(&clock).unwrap();
+    assert!(
+        !matches!(jump, ClockJump::Suspended(_)),
+        "unexpected suspend: {:?}",
+        jump
+    );
+    let now = clock.monotonic();
+    if let ClockJump::Suspended(suspended) = jump {
+        registry.skip_suspended(suspended, now);
+    }
+    assert!(registry.tick(now).is_empty());
+    let snapshot = registry.get(DEFAULT_TIMER_ID).unwrap().engine.snapshot(now);
+    assert_eq!(snapshot.remaining_ms, 599_000);
+}
+
+#[test]
+fn test_on_time_finish_is_not_missed() {
+    let clock = FakeClock::new();
+    let mut registry = TimerRegistry::new();
+    let t0 = clock.monotonic();
+    let engine = &mut registry.get_mut(DEFAULT_TIMER_ID).unwrap().engine;
+    engine.set(Duration::from_secs(5));
+    engine.start(t0);
+
+    clock.advance(Duration::from_millis(5100), Duration::from_millis(5100));
+    let now = clock.monotonic();
+    let finished = registry.tick(now);
+    assert_eq!(finished.len(), 1);
+    assert!(registry.missed(&finished, now).is_empty());
+}
+
+#[test]
+fn test_suspend_extends_overtime() {
+    let t0 = Instant::now();
+    let mut engine = TimerEngine::new();
+    engine.set(Duration::from_secs(1));
+    engine.start(t0);
+    let t1 = t0 + Duration::from_secs(1);
+    assert!(engine.tick(t1));
+    assert!(engine.start_overtime());
+
+    // スリープしていた時間も超過時間に含める
+    engine.skip_suspended(Duration::from_secs(30), t1);
+    assert_eq!(engine.overrun_at(t1), Some(Duration::from_secs(30)));
+}
+
+#[test]
+fn test_alarm_missed_occurrences_collapse_to_one() {
+    let alarm = wall_clock_alarm(9, 0, Vec::new());
+    let mut daily = alarm.clone();
+    daily.weekdays = vec![
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+        Weekday::Sun,
+    ];
+    // 3 日間スリープしていても鳴らすのは最後の 1 回だけ
+    let slept = New_York.with_ymd_and_hms(2026, 5, 1, 22, 0, 0).unwrap();
+    let woke = New_York.with_ymd_and_hms(2026, 5, 4, 10, 0, 0).unwrap();
+    assert_eq!(
+        due_occurrence(&daily, &slept, &woke),
+        Some(New_York.with_ymd_and_hms(2026, 5, 4, 9, 0, 0).unwrap())
+    );
+    assert_eq!(
+        due_occurrence(&alarm, &slept, &woke),
+        Some(New_York.with_ymd_and_hms(2026, 5, 4, 9, 0, 0).unwrap())
+    );
+}
+
+#[test]
+fn test_alarm_due_while_app_closed_follows_policy() {
+    // 前日の 20:00 に翌朝 7:00 の 1 回限りのアラームを保存し、9:00 に起動した
+    let mut alarm = wall_clock_alarm(7, 0, Vec::new());
+    let armed = New_York.with_ymd_and_hms(2026, 5, 3, 20, 0, 0).unwrap();
+    alarm.armed_unix_ms = Some(armed.timestamp_millis());
+    let started = New_York.with_ymd_and_hms(2026, 5, 4, 9, 0, 0).unwrap();
+    let due = New_York.with_ymd_and_hms(2026, 5, 4, 7, 0, 0).unwrap();
+
+    let mut book = AlarmBook {
+        alarms: vec![alarm.clone()],
+    };
+    let (fired, changed) = book.take_due(None, &started, MissedAlarmPolicy::FireLate);
+    assert!(changed);
+    assert_eq!(fired.len(), 1);
+    assert_eq!(fired[0].last_fired_unix_ms, Some(due.timestamp_millis()));
+    assert!(!book.alarms[0].enabled);
+
+    let mut book = AlarmBook {
+        alarms: vec![alarm.clone()],
+    };
+    let (fired, changed) = book.take_due(None, &started, MissedAlarmPolicy::Drop);
+    assert!(changed);
+    assert!(fired.is_empty());
+    assert!(!book.alarms[0].enabled);
+
+    // 次の確認では同じ回を鳴らさない
+    let later = started + chrono::Duration::seconds(1);
+    assert_eq!(
+        book.take_due(Some(&started), &later, MissedAlarmPolicy::FireLate),
+        (Vec::new(), false)
+    );
+
+    // 保存より前の時刻は鳴らさない（起動時に前日 7:00 を拾わない）
+    alarm.armed_unix_ms = Some(started.timestamp_millis());
+    let mut book = AlarmBook {
+        alarms: vec![alarm],
+    };
+    let (fired, changed) = book.take_due(None, &started, MissedAlarmPolicy::FireLate);
+    assert!(fired.is_empty());
+    assert!(!changed);
+}
+
+fn preset(name: &str, duration_secs: u64) -> Preset {
+    Preset {
+        id: String::new(),
+        name: name.to_string(),
+        duration_secs,
+        alarm: None,
+        shortcut: None,
+    }
+}
+
+#[test]
+fn test_preset_library_crud() {
+    let mut library = PresetLibrary::default();
+    let tea = library.save(preset(" Tea ", 180)).unwrap();
+    let retro = library.save(preset("Retro", 45 * 60)).unwrap();
+    assert_eq!(tea.id, "preset-1");
+    assert_eq!(tea.name, "Tea");
+    assert_eq!(retro.id, "preset-2");
+
+    // ID と名前（大文字小文字を無視）のどちらでも引ける
+    assert_eq!(library.find("preset-2"), Some(&retro));
+    assert_eq!(library.find("tea"), Some(&tea));
+    assert_eq!(library.find("coffee"), None);
+
+    let mut longer_tea = tea.clone();
+    longer_tea.duration_secs = 240;
+    library.save(longer_tea).unwrap();
+    assert_eq!(library.find("Tea").unwrap().duration_secs, 240);
+
+    library.remove("preset-1").unwrap();
+    assert!(library.remove("preset-1").is_err());
+    // 削除後も ID は重複しない
+    assert_eq!(library.save(preset("Tea", 180)).unwrap().id, "preset-3");
+
+    let mut unknown = preset("Ghost", 60);
+    unknown.id = "preset-99".to_string();
+    assert!(library.save(unknown).is_err());
+}
+
+#[test]
+fn test_preset_validation() {
+    let mut library = PresetLibrary::default();
+    assert!(library.save(preset("  ", 60)).is_err());
+    assert!(library.save(preset("Zero", 0)).is_err());
+    assert!(library
+        .save(preset("Too long", MAX_DURATION_SECS + 1))
+        .is_err());
+    assert!(library.presets.is_empty());
+}
+
+#[test]
+fn test_preset_reorder() {
+    let mut library = PresetLibrary::default();
+    for (name, secs) in [("Tea", 180), ("Retro", 2700), ("Standup", 900)] {
+        library.save(preset(name, secs)).unwrap();
+    }
+    let ids = |library: &PresetLibrary| -> Vec<String> {
+        library.presets.iter().map(|p| p.id.clone()).collect()
+    };
+    let order: Vec<String> = ["preset-3", "preset-1", "preset-2"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    library.reorder(&order).unwrap();
+    assert_eq!(ids(&library), order);
+
+    // 不足・重複・未知の ID は拒否して並びを変えない
+    let bad_orders = [
+        vec!["preset-1", "preset-2"],
+        vec!["preset-1", "preset-1", "preset-2"],
+        vec!["preset-1", "preset-2", "preset-9"],
+    ];
+    for bad in bad_orders {
+        let bad: Vec<String> = bad.iter().map(|s| s.to_string()).collect();
+        assert!(library.reorder(&bad).is_err());
+        assert_eq!(ids(&library), order);
+    }
+
+    // 保存形式は並び順どおりの配列
+    let json = serde_json::to_value(&library).unwrap();
+    assert_eq!(json[0]["name"], "Standup");
+    assert_eq!(json[0]["durationSecs"], 900);
+}
+
+#[test]
+fn test_tray_title_follows_timer_status() {
+    let t0 = Instant::now();
+    let mut engine = TimerEngine::new();
+    assert_eq!(tray_title(&engine.snapshot(t0)), None);
+    assert_eq!(start_pause_label(engine.status()), "Start");
+
+    engine.set(Duration::from_secs(3600 + 5));
+    engine.start(t0);
+    assert_eq!(
+        tray_title(&engine.snapshot(t0)),
+        Some("1:00:05".to_string())
+    );
+    assert_eq!(start_pause_label(engine.status()), "Pause");
+
+    engine.pause(t0 + Duration::from_secs(6));
+    assert_eq!(
+        tray_title(&engine.snapshot(t0)),
+        Some("⏸ 59:59".to_string())
+    );
+    assert_eq!(start_pause_label(engine.status()), "Resume");
+
+    // 残り 59:59 から再開
+    engine.start(t0);
+    let end = t0 + Duration::from_secs(3599);
+    assert!(engine.tick(end));
+    assert_eq!(
+        tray_title(&engine.snapshot(end)),
+        Some("TIME UP".to_string())
+    );
+
+    assert!(engine.start_overtime());
+    let later = end + Duration::from_secs(83);
+    assert_eq!(
+        tray_title(&engine.snapshot(later)),
+        Some("-01:23".to_string())
+    );
+    assert_eq!(start_pause_label(engine.status()), "Pause");
+}
+
+#[test]
+fn test_shortcut_defaults_parse_without_conflicts() {
+    let config = ShortcutConfig::default();
+    let (parsed, conflicts) = config.parse();
+    assert_eq!(parsed.len(), ShortcutAction::ALL.len());
+    assert!(conflicts.is_empty());
+
+    let resolved = config.resolved();
+    assert_eq!(
+        resolved.bindings[&ShortcutAction::StartPause],
+        "CommandOrControl+Alt+Space"
+    );
+    assert_eq!(resolved.bindings.len(), ShortcutAction::ALL.len());
+}
+
+#[test]
+fn test_shortcut_config_reports_conflicts() {
+    let config: ShortcutConfig = serde_json::from_str(
+        r#"{"bindings": {
+            "reset": "CommandOrControl+Alt+Space",
+            "addMinute": "Ctrl+Nope",
+            "toggleLayer": "  "
+        }}"#,
+    )
+    .unwrap();
+    // 空文字列は無効化
+    assert_eq!(config.accelerator(ShortcutAction::ToggleLayer), None);
+    assert_eq!(config.resolved().bindings[&ShortcutAction::ToggleLayer], "");
+
+    let (parsed, conflicts) = config.parse();
+    let registered: Vec<ShortcutAction> = parsed.iter().map(|(a, _)| *a).collect();
+    assert_eq!(
+        registered,
+        vec![ShortcutAction::StartPause, ShortcutAction::DismissTimeup]
+    );
+    let failed: Vec<ShortcutAction> = conflicts.iter().map(|c| c.action).collect();
+    assert_eq!(
+        failed,
+        vec![ShortcutAction::Reset, ShortcutAction::AddMinute]
+    );
+    assert!(conflicts[0].reason.contains("StartPause"));
+}
+
+#[test]
+fn test_preset_shortcuts() {
+    // 保存した割り当てはそのまま読み戻せる。古い presets.json には項目がない
+    let json = r#"[
+        {"id":"preset-1","name":"Tea","durationSecs":180,"shortcut":"CommandOrControl+Alt+1"},
+        {"id":"preset-2","name":"Nap","durationSecs":1200}
+    ]"#;
+    let library: PresetLibrary = serde_json::from_str(json).expect("Failed to parse presets");
+    assert_eq!(
+        library.presets[0].shortcut.as_deref(),
+        Some("CommandOrControl+Alt+1")
+    );
+    assert_eq!(library.presets[1].shortcut, None);
+    let saved = serde_json::to_string(&library).unwrap();
+    assert_eq!(
+        serde_json::from_str::<PresetLibrary>(&saved).unwrap(),
+        library
+    );
+
+    let with_shortcut = |id: &str, accelerator: &str| Preset {
+        id: id.to_string(),
+        shortcut: Some(accelerator.to_string()),
+        ..preset(id, 60)
+    };
+    let presets = vec![
+        with_shortcut("preset-1", "CommandOrControl+Alt+1"),
+        with_shortcut("preset-2", "CommandOrControl+Alt+Space"),
+        with_shortcut("preset-3", "CommandOrControl+Alt+1"),
+        with_shortcut("preset-4", "Ctrl+Nope"),
+        with_shortcut("preset-5", " "),
+    ];
+    let (taken, _) = ShortcutConfig::default().parse();
+    let (parsed, conflicts) = parse_preset_shortcuts(&presets, &taken);
+    let registered: Vec<&str> = parsed.iter().map(|(id, _)| id.as_str()).collect();
+    assert_eq!(registered, vec!["preset-1"]);
+    let failed: Vec<(ShortcutAction, Option<&str>)> = conflicts
+        .iter()
+        .map(|c| (c.action, c.preset_id.as_deref()))
+        .collect();
+    assert_eq!(
+        failed,
+        vec![
+            (ShortcutAction::LaunchPreset, Some("preset-2")),
+            (ShortcutAction::LaunchPreset, Some("preset-3")),
+            (ShortcutAction::LaunchPreset, Some("preset-4")),
+        ]
+    );
+    assert!(conflicts[0].reason.contains("StartPause"));
+    assert!(conflicts[1].reason.contains("preset-1"));
+}
+
+#[test]
+fn test_timer_engine_add_time() {
+    let t0 = Instant::now();
+    let minute = Duration::from_secs(60);
+    let mut engine = TimerEngine::new();
+
+    // 停止中は設定時間ごと延長
+    engine.add_time(minute, t0);
+    assert_eq!(engine.status(), TimerStatus::Idle);
+    assert_eq!(engine.duration(), minute);
+
+    engine.start(t0);
+    let t1 = t0 + Duration::from_secs(50);
+    engine.add_time(minute, t1);
+    assert_eq!(engine.remaining_at(t1), Duration::from_secs(70));
+
+    engine.pause(t1);
+    engine.add_time(minute, t1);
+    assert_eq!(engine.remaining_at(t1), Duration::from_secs(130));
+
+    // 終了後は 1 分から再スタート
+    engine.resume(t1);
+    let t2 = t1 + Duration::from_secs(130);
+    assert!(engine.tick(t2));
+    engine.add_time(minute, t2);
+    assert_eq!(engine.status(), TimerStatus::Running);
+    assert_eq!(engine.remaining_at(t2), minute);
+
+    // 上限で丸める
+    engine.add_time(Duration::from_secs(MAX_DURATION_SECS), t2);
+    assert_eq!(
+        engine.remaining_at(t2),
+        Duration::from_secs(MAX_DURATION_SECS)
+    );
+}
+
+#[test]
+fn test_http_api_routes() {
+    let get = parse_request("GET", "/timer").unwrap();
+    assert_eq!(get.route, ApiRoute::GetTimer);
+    assert_eq!(get.timer_id, None);
+
+    let start = parse_request("POST", "/timer/start/?id=timer-2").unwrap();
+    assert_eq!(start.route, ApiRoute::Start);
+    assert_eq!(start.timer_id.as_deref(), Some("timer-2"));
+
+    assert_eq!(
+        parse_request("POST", "/timer/pause").unwrap().route,
+        ApiRoute::Pause
+    );
+    assert_eq!(
+        parse_request("post", "/timer/reset").unwrap().route,
+        ApiRoute::Reset
+    );
+    assert_eq!(
+        parse_request("POST", "/timer/set?id=").unwrap().timer_id,
+        None
+    );
+
+    assert_eq!(
+        parse_request("GET", "/timer/start").unwrap_err().status,
+        405
+    );
+    assert_eq!(parse_request("POST", "/timer").unwrap_err().status, 405);
+    assert_eq!(parse_request("GET", "/").unwrap_err().status, 404);
+    assert_eq!(parse_request("GET", "/timers").unwrap_err().status, 404);
+
+    // パスのセグメントと id はデコードしてから解釈する
+    let encoded = parse_request("POST", "/timer/st%61rt?id=my%20timer").unwrap();
+    assert_eq!(encoded.route, ApiRoute::Start);
+    assert_eq!(encoded.timer_id.as_deref(), Some("my timer"));
+    assert_eq!(
+        parse_request("GET", "/timer?id=Tea+%E2%98%95")
+            .unwrap()
+            .timer_id
+            .as_deref(),
+        Some("Tea ☕")
+    );
+    assert_eq!(
+        parse_request("POST", "/timer%2Fstart").unwrap_err().status,
+        404
+    );
+    assert_eq!(parse_request("GET", "/tim%2").unwrap_err().status, 400);
+    assert_eq!(
+        parse_request("GET", "/timer?id=%FF").unwrap_err().status,
+        400
+    );
+    assert_eq!(percent_decode("my%20timer").unwrap(), "my timer");
+}
+
+#[test]
+fn test_port_candidates_wrap_around() {
+    let ports: Vec<u16> = port_candidates(65534).collect();
+    assert_eq!(&ports[..3], &[65534, 65535, 1024]);
+    assert_eq!(ports.last(), Some(&65533));
+    assert_eq!(ports.len(), 65535 - 1024 + 1);
+
+    // 使用中のポートは飛ばし、一周しても空きがなければエラー
+    let bound = bind_from_port(65535, |port| match port {
+        1025 => Ok(port),
+        _ => Err("in use"),
+    });
+    assert_eq!(bound, Ok((1025, 1025)));
+    assert!(bind_from_port(21420, |_| Err::<(), _>("in use")).is_err());
+}
+
+#[test]
+fn test_http_api_set_body() {
+    assert_eq!(
+        parse_set_body(r#"{"minutes": 5}"#).unwrap(),
+        SetTimerBody {
+            hours: 0,
+            minutes: 5,
+            seconds: 0
+        }
+    );
+    assert_eq!(parse_set_body("{}").unwrap(), SetTimerBody::default());
+    assert_eq!(parse_set_body("").unwrap_err().status, 400);
+    assert_eq!(
+        parse_set_body(r#"{"minutes": -1}"#).unwrap_err().status,
+        400
+    );
+    assert_eq!(parse_set_body(r#"{"mins": 5}"#).unwrap_err().status, 400);
+}
+
+fn named_snapshot(engine: &TimerEngine, now: Instant) -> NamedTimerSnapshot {
+    NamedTimerSnapshot {
+        id: DEFAULT_TIMER_ID.to_string(),
+        name: "Timer".to_string(),
+        alarm: TimerAlarm::default(),
+        timer: engine.snapshot(now),
+    }
+}
+
+fn message_types(messages: &[StreamMessage]) -> Vec<String> {
+    messages
+        .iter()
+        .map(|m| {
+            serde_json::to_value(m).unwrap()["type"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn test_event_stream_messages_follow_status() {
+    let t0 = Instant::now();
+    let mut engine = TimerEngine::new();
+    engine.set(Duration::from_secs(10));
+    engine.start(t0);
+
+    let running = named_snapshot(&engine, t0);
+    let messages = stream_messages(Some(TimerStatus::Idle), TIMER_TICK_EVENT, &running);
+    assert_eq!(message_types(&messages), ["tick", "stateChange"]);
+    assert_eq!(
+        messages[1],
+        StreamMessage::StateChange {
+            from: Some(TimerStatus::Idle),
+            to: TimerStatus::Running,
+            timer: running.clone(),
+        }
+    );
+    let later = named_snapshot(&engine, t0 + Duration::from_secs(1));
+    assert_eq!(
+        message_types(&stream_messages(
+            Some(TimerStatus::Running),
+            TIMER_TICK_EVENT,
+            &later
+        )),
+        ["tick"]
+    );
+
+    let end = t0 + Duration::from_secs(10);
+    assert!(engine.tick(end));
+    let finished = named_snapshot(&engine, end);
+    assert_eq!(
+        message_types(&stream_messages(
+            Some(TimerStatus::Running),
+            TIMER_FINISHED_EVENT,
+            &finished
+        )),
+        ["finished"]
+    );
+
+    assert!(engine.start_overtime());
+    let overtime = named_snapshot(&engine, end + Duration::from_secs(1));
+    assert_eq!(
+        message_types(&stream_messages(
+            Some(TimerStatus::Finished),
+            TIMER_TICK_EVENT,
+            &overtime
+        )),
+        ["tick", "stateChange", "overtime"]
+    );
+    // 超過計測中の一時停止からの再開では overtime を送り直さない
+    assert_eq!(
+        message_types(&stream_messages(
+            Some(TimerStatus::Paused),
+            TIMER_TICK_EVENT,
+            &overtime
+        )),
+        ["tick", "stateChange"]
+    );
+    assert!(stream_messages(None, "timers-changed", &overtime).is_empty());
+}
+
+#[test]
+fn test_event_stream_control_messages() {
+    let start: ControlMessage = serde_json::from_str(r#"{"action":"start"}"#).unwrap();
+    assert_eq!(start.into_route(), (ApiRoute::Start, None, None));
+
+    let set: ControlMessage =
+        serde_json::from_str(r#"{"action":"set","id":"timer-2","minutes":5}"#).unwrap();
+    assert_eq!(
+        set.into_route(),
+        (
+            ApiRoute::Set,
+            Some("timer-2".to_string()),
+            Some(SetTimerBody {
+                hours: 0,
+                minutes: 5,
+                seconds: 0
+            })
+        )
+    );
+
+    assert!(serde_json::from_str::<ControlMessage>(r#"{"action":"explode"}"#).is_err());
+    assert!(serde_json::from_str::<ControlMessage>(r#"{"id":"timer-2"}"#).is_err());
+}
+
+#[test]
+fn test_event_stream_web_clients_are_read_only() {
+    assert_eq!(
+        client_access(Some("127.0.0.1:21421"), None),
+        Ok(ClientAccess::Control)
+    );
+    assert_eq!(
+        client_access(Some("localhost:21421"), Some("https://obsproject.com")),
+        Ok(ClientAccess::ReadOnly)
+    );
+    // DNS リバインディング
+    assert!(client_access(Some("evil.example:21421"), None).is_err());
+    assert!(client_access(None, None).is_err());
+
+    // Origin 付きの接続からの操作は拒否し、状態の取得だけ許す
+    let start = r#"{"action":"start","id":"timer-2"}"#;
+    assert!(parse_control(start, ClientAccess::ReadOnly).is_err());
+    assert!(parse_control(r#"{"action":"set","minutes":5}"#, ClientAccess::ReadOnly).is_err());
+    assert!(parse_control(r#"{"action":"reset"}"#, ClientAccess::ReadOnly).is_err());
+    assert_eq!(
+        parse_control(r#"{"action":"get"}"#, ClientAccess::ReadOnly),
+        Ok(ControlMessage::Get { id: None })
+    );
+    assert_eq!(
+        parse_control(start, ClientAccess::Control),
+        Ok(ControlMessage::Start {
+            id: Some("timer-2".to_string())
+        })
+    );
+
+    assert!(is_loopback_host("127.0.0.1"));
+    assert!(is_loopback_host("[::1]:21420"));
+    assert!(is_loopback_host("LOCALHOST"));
+    assert!(!is_loopback_host("192.168.1.10:21420"));
+    assert!(!is_loopback_host("127.0.0.1.evil.example"));
+}
+
+#[test]
+fn test_parse_duration_spec() {
+    assert_eq!(parse_duration_spec("5m"), Ok(300));
+    assert_eq!(parse_duration_spec("1h30m"), Ok(5400));
+    assert_eq!(parse_duration_spec("1h 30m 15s"), Ok(5415));
+    assert_eq!(parse_duration_spec("90s"), Ok(90));
+    assert_eq!(parse_duration_spec("2 MIN"), Ok(120));
+    assert_eq!(parse_duration_spec("25"), Ok(1500));
+    assert_eq!(parse_duration_spec("25:00"), Ok(1500));
+    assert_eq!(parse_duration_spec("1:05:30"), Ok(3930));
+    assert_eq!(parse_duration_spec("99:59:59"), Ok(MAX_DURATION_SECS));
+    // 桁あふれは繰り上げる
+    assert_eq!(parse_duration_spec("0:90"), Ok(90));
+    assert_eq!(parse_duration_spec("1:75"), Ok(135));
+    assert_eq!(parse_duration_spec("90:00"), Ok(5400));
+    assert_eq!(parse_duration_spec("0:75:90"), Ok(4590));
+
+    for invalid in [
+        "",
+        "0",
+        "0m",
+        "5x",
+        "m5",
+        "5m5m",
+        "0:00",
+        "100:00:00",
+        "1:2:3:4",
+        ":30",
+        "1.5h",
+        "-5m",
+        "100h",
+    ] {
+        assert!(parse_duration_spec(invalid).is_err(), "{:?}", invalid);
+    }
+}
+
+fn cli(args: &[&str]) -> Result<CliCommand, String> {
+    parse_cli_args(&args.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+}
+
+#[test]
+fn test_control_endpoint_file() {
+    let text = format_endpoint(49152, "0123456789abcdef");
+    assert_eq!(
+        parse_endpoint(&text),
+        Some((49152, "0123456789abcdef".to_string()))
+    );
+    assert_eq!(parse_endpoint("49152"), None);
+    assert_eq!(parse_endpoint("49152 "), None);
+    assert_eq!(parse_endpoint("port token"), None);
+    assert_eq!(parse_endpoint(""), None);
+}
+
+#[test]
+fn test_parse_cli_args() {
+    assert_eq!(cli(&[]), Ok(CliCommand::Help));
+    assert_eq!(cli(&["help"]), Ok(CliCommand::Help));
+    assert_eq!(
+        cli(&["start", "5m"]),
+        Ok(CliCommand::Send {
+            request: ControlRequest::Start {
+                id: None,
+                duration_secs: Some(300)
+            },
+            json: false
+        })
+    );
+    assert_eq!(
+        cli(&["start", "1h", "30m", "--id", "timer-2"]),
+        Ok(CliCommand::Send {
+            request: ControlRequest::Start {
+                id: Some("timer-2".to_string()),
+                duration_secs: Some(5400)
+            },
+            json: false
+        })
+    );
+    assert_eq!(
+        cli(&["status", "--json"]),
+        Ok(CliCommand::Send {
+            request: ControlRequest::Status { id: None },
+            json: true
+        })
+    );
+    assert_eq!(
+        cli(&["preset", "Green", "Tea"]),
+        Ok(CliCommand::Send {
+            request: ControlRequest::Preset {
+                key: "Green Tea".to_string()
+            },
+            json: false
+        })
+    );
+
+    // ソケットに流す JSON の形
+    assert_eq!(
+        serde_json::to_value(ControlRequest::Start {
+            id: None,
+            duration_secs: Some(300)
+        })
+        .unwrap(),
+        serde_json::json!({ "command": "start", "id": null, "durationSecs": 300 })
+    );
+
+    assert!(cli(&["start", "soon"]).is_err());
+    assert!(cli(&["pause", "now"]).is_err());
+    assert!(cli(&["preset"]).is_err());
+    assert!(cli(&["status", "--id"]).is_err());
+    assert!(cli(&["status", "--verbose"]).is_err());
+    assert!(cli(&["explode"]).is_err());
+}
+
+#[test]
+fn test_instance_lock_is_exclusive() {
+    let path = std::env::temp_dir().join(format!(
+        "lightning-timer-instance-{}.lock",
+        std::process::id()
+    ));
+    let first = crate::instance::acquire_at(&path).unwrap();
+    assert!(first.is_some());
+    assert!(crate::instance::acquire_at(&path).unwrap().is_none());
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap().trim(),
+        std::process::id().to_string()
+    );
+
+    // 先のプロセスが終わればロックを取り直せる
+    drop(first);
+    assert!(crate::instance::acquire_at(&path).unwrap().is_some());
+    std::fs::remove_file(&path).ok();
+}
+
+fn launch(args: &[&str]) -> Result<LaunchCommand, String> {
+    parse_launch_args(&args.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+}
+
+fn launch_options(args: &[&str]) -> LaunchOptions {
+    match launch(args) {
+        Ok(LaunchCommand::Run(options)) => options,
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_launch_args() {
+    assert_eq!(launch_options(&[]), LaunchOptions::default());
+    assert_eq!(launch(&["--help"]), Ok(LaunchCommand::Help));
+
+    let options = launch_options(&["--start", "25m", "--layer", "--always-on-top"]);
+    assert_eq!(options.start_secs, Some(1500));
+    assert!(options.layer && options.always_on_top && !options.minimized);
+
+    let options = launch_options(&["--preset=Green Tea", "--minimized", "-psn_0_12345"]);
+    assert_eq!(options.preset.as_deref(), Some("Green Tea"));
+    assert!(options.minimized);
+
+    let options = launch_options(&["--config", "profiles/standup.json"]);
+    assert_eq!(
+        options.config,
+        Some(std::path::PathBuf::from("profiles/standup.json"))
+    );
+
+    for (args, message) in [
+        (&["--start"][..], "--start requires a value"),
+        (&["--start", "soon"][..], "--start: Invalid duration"),
+        (
+            &["--start", "5m", "--preset", "tea"][..],
+            "cannot be used together",
+        ),
+        (&["--layer=yes"][..], "does not take a value"),
+        (&["--fullscreen"][..], "Unknown option: --fullscreen"),
+        (&["25m"][..], "Unexpected argument: 25m"),
+    ] {
+        let error = launch(args).unwrap_err();
+        assert!(error.contains(message), "{:?} -> {}", args, error);
+    }
+}
+
+#[test]
+fn test_launch_config_merge_and_forwarding() {
+    let config: LaunchConfig =
+        serde_json::from_str(r#"{"start": "1h5m", "layer": true, "alwaysOnTop": true}"#).unwrap();
+    let mut options = launch_options(&["--minimized"]);
+    options.merge_config(config).unwrap();
+    assert_eq!(options.start_secs, Some(3900));
+    assert!(options.layer && options.always_on_top && options.minimized);
+
+    // コマンドラインの指定が優先する
+    let config: LaunchConfig = serde_json::from_str(r#"{"preset": "Tea"}"#).unwrap();
+    let mut options = launch_options(&["--start", "5m"]);
+    options.merge_config(config).unwrap();
+    assert_eq!(
+        (options.start_secs, options.preset.as_deref()),
+        (Some(300), None)
+    );
+
+    let config: LaunchConfig = serde_json::from_str(r#"{"start": "5m", "preset": "Tea"}"#).unwrap();
+    assert!(LaunchOptions::default().merge_config(config).is_err());
+    assert!(serde_json::from_str::<LaunchConfig>(r#"{"fullscreen": true}"#).is_err());
+
+    // 先に起動したプロセスへ渡す引数は同じ内容に解釈し直せる
+    let forwarded = options.to_args();
+    assert_eq!(forwarded, ["--start", "05:00"]);
+    assert_eq!(
+        launch_options(&forwarded.iter().map(String::as_str).collect::<Vec<_>>()),
+        options
+    );
+    let long = launch_options(&["--start", "1h5m", "--layer", "--always-on-top"]);
+    let forwarded = long.to_args();
+    assert_eq!(
+        launch_options(&forwarded.iter().map(String::as_str).collect::<Vec<_>>()),
+        long
+    );
+}
+
+#[test]
+fn test_parse_deep_link() {
+    assert_eq!(
+        parse_deep_link("lightning-timer://start?d=5m&label=Standup"),
+        Ok(DeepLink::Start {
+            duration_secs: Some(300),
+            label: Some("Standup".to_string()),
+        })
+    );
+    assert_eq!(
+        parse_deep_link("lightning-timer://start"),
+        Ok(DeepLink::Start {
+            duration_secs: None,
+            label: None,
+        })
+    );
+    // 大文字、末尾の `/`、`//` なし、`duration` の別名、パーセントエンコード・`+` の空白
+    assert_eq!(
+        parse_deep_link("LIGHTNING-TIMER://Start/?duration=1h30m&label=Daily%20Sync+%E6%9C%9D"),
+        Ok(DeepLink::Start {
+            duration_secs: Some(5400),
+            label: Some("Daily Sync 朝".to_string()),
+        })
+    );
+    assert_eq!(
+        parse_deep_link(" lightning-timer:pause?label=%20Tea%20 "),
+        Ok(DeepLink::Pause {
+            label: Some("Tea".to_string()),
+        })
+    );
+    assert_eq!(
+        parse_deep_link("lightning-timer://reset#from-calendar"),
+        Ok(DeepLink::Reset { label: None })
+    );
+    assert_eq!(
+        parse_deep_link("lightning-timer://preset?name=Green%20Tea"),
+        Ok(DeepLink::Preset {
+            name: "Green Tea".to_string(),
+        })
+    );
+}
+
+#[test]
+fn test_parse_deep_link_rejects_malformed_links() {
+    let long_label = format!("lightning-timer://start?label={}", "a".repeat(65));
+    let long_link = format!("lightning-timer://start?label={}", "a".repeat(2048));
+    for (link, message) in [
+        ("", "Invalid link"),
+        ("lightning-timer", "Invalid link"),
+        (
+            "https://example.com/start?d=5m",
+            "Unsupported scheme: https",
+        ),
+        ("lightning-timers://start", "Unsupported scheme"),
+        ("lightning-timer://", "Missing action"),
+        ("lightning-timer:", "Missing action"),
+        ("lightning-timer://stop", "Unknown action: stop"),
+        ("lightning-timer://start/now", "Unexpected path: /now"),
+        ("lightning-timer://user@start", "unexpected user"),
+        ("lightning-timer://start:80", "unexpected user or port"),
+        ("lightning-timer://start?d=", "\"d\": Invalid duration"),
+        ("lightning-timer://start?d=soon", "\"d\": Invalid duration"),
+        ("lightning-timer://start?d=0", "\"d\""),
+        ("lightning-timer://start?d=100h", "\"d\""),
+        ("lightning-timer://start?d=-5m", "\"d\""),
+        (
+            "lightning-timer://start?d=5m&d=10m",
+            "Duplicate parameter: d",
+        ),
+        (
+            "lightning-timer://start?d=5m&duration=10m",
+            "Duplicate parameter: d",
+        ),
+        (
+            "lightning-timer://start?label=a&label=b",
+            "Duplicate parameter: label",
+        ),
+        (
+            "lightning-timer://start?d=5m&sound=bell",
+            "Unknown parameter: sound",
+        ),
+        ("lightning-timer://start?D=5m", "Unknown parameter: D"),
+        (
+            "lightning-timer://start?label=",
+            "\"label\" must not be empty",
+        ),
+        ("lightning-timer://start?label=%20%20", "must not be empty"),
+        ("lightning-timer://start?label=a%0Ab", "control characters"),
+        (long_label.as_str(), "at most 64 characters"),
+        (long_link.as_str(), "longer than 2048 bytes"),
+        (
+            "lightning-timer://start?name=tea",
+            "\"start\" does not take \"name\"",
+        ),
+        (
+            "lightning-timer://pause?d=5m",
+            "\"pause\" does not take \"d\"",
+        ),
+        (
+            "lightning-timer://reset?name=tea",
+            "\"reset\" does not take \"name\"",
+        ),
+        ("lightning-timer://preset", "\"preset\" requires \"name\""),
+        ("lightning-timer://preset?name=%20", "requires \"name\""),
+        (
+            "lightning-timer://preset?name=tea&label=x",
+            "does not take \"label\"",
+        ),
+        (
+            "lightning-timer://preset?name=tea&d=5m",
+            "does not take \"d\"",
+        ),
+    ] {
+        match parse_deep_link(link) {
+            Ok(parsed) => panic!("{:?} was accepted as {:?}", link, parsed),
+            Err(error) => assert!(error.contains(message), "{:?} -> {}", link, error),
+        }
+    }
+}
+
+#[test]
+fn test_deep_link_launch_args() {
+    assert!(is_deep_link("lightning-timer://start"));
+    assert!(is_deep_link("Lightning-Timer:pause"));
+    assert!(!is_deep_link("lightning-timer"));
+    assert!(!is_deep_link("--start=lightning-timer:5m"));
+
+    // Windows・Linux では OS がリンクを引数で渡す。2 つ目の起動からはそのまま転送する
+    let link = "lightning-timer://start?d=5m&label=Standup";
+    let options = launch_options(&[link]);
+    assert_eq!(options.deep_link.as_deref(), Some(link));
+    assert_eq!(options.to_args(), [link]);
+
+    for (args, message) in [
+        (&["lightning-timer://start?d=soon"][..], "Invalid duration"),
+        (&[link, "lightning-timer://pause"][..], "Only one link"),
+        (&["--start", "5m", link][..], "cannot be combined"),
+        (&[link, "--preset", "tea"][..], "cannot be combined"),
+    ] {
+        let error = launch(args).unwrap_err();
+        assert!(error.contains(message), "{:?} -> {}", args, error);
+    }
+
+    // --config の start / preset はリンクがあれば使わない
+    let config: LaunchConfig = serde_json::from_str(r#"{"start": "25m", "layer": true}"#).unwrap();
+    let mut options = launch_options(&[link, "--layer"]);
+    options.merge_config(config).unwrap();
+    assert_eq!(options.start_secs, None);
+}
+
+#[test]
+fn test_timer_registry_find_by_name() {
+    let mut registry = TimerRegistry::new();
+    let id = registry
+        .create(" Standup ", Duration::from_secs(300), TimerAlarm::default())
+        .id
+        .clone();
+    assert_eq!(
+        registry.find_by_name("standup").map(|t| t.id.as_str()),
+        Some(id.as_str())
+    );
+    assert_eq!(
+        registry.find_by_name("Timer").map(|t| t.id.as_str()),
+        Some(DEFAULT_TIMER_ID)
+    );
+    assert!(registry.find_by_name("Retro").is_none());
+}
@@ -0,0 +1,1164 @@
+//! バックエンドで管理するカウントダウンエンジン。
+//!
+//! 残り時間は単調増加クロック (`Instant`) 上の締切時刻から算出するため、
+//! webview のスロットリングやウィンドウ間の描画タイミングに影響されない。
+//! ティッカースレッドが表示秒の変化ごとに `timer-tick` を、
+//! 0 到達時に `timer-finished` を全ウィンドウへ emit する。
+//!
+//! タイマーは ID をキーにした `TimerRegistry` で複数同時に管理する。
+//! ID を省略したコマンドは main ウィンドウの既定タイマー (`default`) を操作する。
+//!
+//! 状態は `timer_state.json` に壁時計ベースの締切時刻とともに保存し、
+//! 次回起動時に復元する。
+//!
+//! アラームで超過計測 (`overtime`) を有効にしたタイマーは、0 到達後も
+//! 停止されるまで超過時間をカウントアップし、終了時にセッション記録へ残す。
+//!
+//! スリープ復帰や壁時計の変更は `resync` モジュールで検出し、締切を合わせ直す。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::duration::{ClockTime, MAX_DURATION_SECS};
+use crate::resync::{
+    ClockJump, ClockWatch, MissedAlarmPolicy, ResyncReport, ResyncState, SystemClock,
+    MISSED_TIMER_GRACE, TIMER_RESYNCED_EVENT,
+};
+use crate::session::SessionRecord;
+
+pub const TIMER_TICK_EVENT: &str = "timer-tick";
+pub const TIMER_FINISHED_EVENT: &str = "timer-finished";
+pub const TIMERS_CHANGED_EVENT: &str = "timers-changed";
+
+/// `timer_state.json` のフォーマットバージョン
+pub const TIMER_STATE_VERSION: u32 = 2;
+
+/// 停止中のティッカーのポーリング間隔
+const IDLE_POLL: Duration = Duration::from_millis(250);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TimerStatus {
+    Idle,
+    Running,
+    Paused,
+    Finished,
+    /// 0 到達後に超過時間を計測中
+    Overtime,
+}
+
+/// エンジン単体の状態
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimerSnapshot {
+    pub status: TimerStatus,
+    pub duration_ms: u64,
+    pub remaining_ms: u64,
+    /// 0 到達からの超過時間（超過計測中のみ 0 以外）
+    pub overrun_ms: u64,
+    /// 表示用の残り時間（秒単位に切り上げ）。超過計測中は超過時間（切り捨て）
+    pub hours: u64,
+    pub minutes: u64,
+    pub seconds: u64,
+}
+
+impl TimerSnapshot {
+    pub fn display(&self) -> ClockTime {
+        ClockTime::normalize(self.hours, self.minutes, self.seconds)
+    }
+}
+
+#[derive(Debug)]
+pub struct TimerEngine {
+    /// 最後に設定された時間。終了時はこの値に戻る
+    duration: Duration,
+    /// 停止・一時停止中の残り時間
+    remaining: Duration,
+    /// 実行中の終了予定時刻
+    deadline: Option<Instant>,
+    /// 直近で 0 に到達した時刻（超過時間の起点）
+    finished_at: Option<Instant>,
+    status: TimerStatus,
+}
+
+impl Default for TimerEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimerEngine {
+    pub fn new() -> Self {
+        Self {
+            duration: Duration::ZERO,
+            remaining: Duration::ZERO,
+            deadline: None,
+            finished_at: None,
+            status: TimerStatus::Idle,
+        }
+    }
+
+    /// 時間を設定して停止状態に戻す
+    pub fn set(&mut self, duration: Duration) {
+        self.duration = duration;
+        self.remaining = duration;
+        self.deadline = None;
+        self.finished_at = None;
+        self.status = TimerStatus::Idle;
+    }
+
+    /// カウントダウンを開始する。一時停止中なら再開する。
+    /// 残り時間が 0 の場合は何もせず `false` を返す
+    pub fn start(&mut self, now: Instant) -> bool {
+        match self.status {
+            TimerStatus::Running => true,
+            TimerStatus::Paused => self.resume(now),
+            TimerStatus::Idle | TimerStatus::Finished | TimerStatus::Overtime => {
+                if self.remaining.is_zero() {
+                    return false;
+                }
+                self.deadline = Some(now + self.remaining);
+                self.finished_at = None;
+                self.status = TimerStatus::Running;
+                true
+            }
+        }
+    }
+
+    /// 一時停止する。超過計測中なら計測を止めて終了状態にする
+    pub fn pause(&mut self, now: Instant) -> bool {
+        if self.status == TimerStatus::Overtime {
+            self.status = TimerStatus::Finished;
+            return true;
+        }
+        if self.status != TimerStatus::Running {
+            return false;
+        }
+        self.remaining = self.remaining_at(now);
+        self.deadline = None;
+        self.status = TimerStatus::Paused;
+        true
+    }
+
+    pub fn resume(&mut self, now: Instant) -> bool {
+        if self.status != TimerStatus::Paused || self.remaining.is_zero() {
+            return false;
+        }
+        self.deadline = Some(now + self.remaining);
+        self.status = TimerStatus::Running;
+        true
+    }
+
+    /// 残り時間を延長する。終了後（超過計測中を含む）なら `extra` から再スタートする
+    pub fn add_time(&mut self, extra: Duration, now: Instant) {
+        let max = Duration::from_secs(MAX_DURATION_SECS);
+        match self.status {
+            TimerStatus::Running => {
+                let remaining = (self.remaining_at(now) + extra).min(max);
+                self.deadline = Some(now + remaining);
+            }
+            TimerStatus::Paused => {
+                self.remaining = (self.remaining + extra).min(max);
+            }
+            TimerStatus::Idle => {
+                self.set((self.remaining + extra).min(max));
+            }
+            TimerStatus::Finished | TimerStatus::Overtime => {
+                self.set(extra.min(max));
+                self.start(now);
+            }
+        }
+    }
+
+    /// タイマーを停止し、設定時間も含めて 00:00 に戻す
+    pub fn reset(&mut self) {
+        self.set(Duration::ZERO);
+    }
+
+    pub fn remaining_at(&self, now: Instant) -> Duration {
+        match self.deadline {
+            Some(deadline) => deadline.saturating_duration_since(now),
+            None => self.remaining,
+        }
+    }
+
+    /// 締切に到達していれば終了状態へ遷移する。今回の呼び出しで終了した場合のみ `true`
+    pub fn tick(&mut self, now: Instant) -> bool {
+        if self.status != TimerStatus::Running || !self.remaining_at(now).is_zero() {
+            return false;
+        }
+        self.finished_at = self.deadline.take();
+        self.remaining = self.duration;
+        self.status = TimerStatus::Finished;
+        true
+    }
+
+    /// 終了直後のタイマーで超過時間の計測を始める
+    pub fn start_overtime(&mut self) -> bool {
+        if self.status != TimerStatus::Finished || self.finished_at.is_none() {
+            return false;
+        }
+        self.status = TimerStatus::Overtime;
+        true
+    }
+
+    /// 超過計測中なら 0 到達からの経過時間を返す
+    pub fn overrun_at(&self, now: Instant) -> Option<Duration> {
+        match (self.status, self.finished_at) {
+            (TimerStatus::Overtime, Some(finished_at)) => {
+                Some(now.saturating_duration_since(finished_at))
+            }
+            _ => None,
+        }
+    }
+
+    /// 終了（超過計測中を含む）していれば 0 到達からの経過時間を返す
+    pub fn overdue_at(&self, now: Instant) -> Option<Duration> {
+        match (self.status, self.finished_at) {
+            (TimerStatus::Finished | TimerStatus::Overtime, Some(finished_at)) => {
+                Some(now.saturating_duration_since(finished_at))
+            }
+            _ => None,
+        }
+    }
+
+    /// スリープしていた時間だけ締切（超過計測の起点）を前倒しする。
+    /// スリープ中に進まない単調時計を壁時計に合わせるために使う
+    pub fn skip_suspended(&mut self, suspended: Duration, now: Instant) {
+        match self.status {
+            TimerStatus::Running => {
+                self.deadline = self
+                    .deadline
+                    .map(|deadline| deadline.checked_sub(suspended).unwrap_or(now).min(deadline));
+            }
+            TimerStatus::Overtime => {
+                self.finished_at = self
+                    .finished_at
+                    .map(|finished_at| finished_at.checked_sub(suspended).unwrap_or(finished_at));
+            }
+            _ => {}
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub fn status(&self) -> TimerStatus {
+        self.status
+    }
+
+    /// 表示秒が次に変わるまでの待ち時間
+    pub fn until_next_second(&self, now: Instant) -> Duration {
+        let wait = match self.status {
+            TimerStatus::Running => {
+                let sub_ms = (self.remaining_at(now).as_millis() % 1000) as u64;
+                if sub_ms == 0 {
+                    1000
+                } else {
+                    sub_ms
+                }
+            }
+            TimerStatus::Overtime => {
+                let overrun = self.overrun_at(now).unwrap_or_default();
+                1000 - (overrun.as_millis() % 1000) as u64
+            }
+            _ => return IDLE_POLL,
+        };
+        // 境界の直後に起きるよう少しだけ余裕を持たせる
+        Duration::from_millis(wait + 5).min(IDLE_POLL)
+    }
+
+    pub fn snapshot(&self, now: Instant) -> TimerSnapshot {
+        let remaining = self.remaining_at(now);
+        let remaining_ms = remaining.as_millis() as u64;
+        let overrun_ms = self
+            .overrun_at(now)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        let display_secs = if self.status == TimerStatus::Overtime {
+            overrun_ms / 1000
+        } else {
+            remaining_ms.div_ceil(1000)
+        };
+        let display = ClockTime::from_secs(display_secs);
+        TimerSnapshot {
+            status: self.status,
+            duration_ms: self.duration.as_millis() as u64,
+            remaining_ms,
+            overrun_ms,
+            hours: display.hours,
+            minutes: display.minutes,
+            seconds: display.seconds,
+        }
+    }
+}
+
+/// 1 つのタイマーの保存状態
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedTimerState {
+    pub status: TimerStatus,
+    pub duration_ms: u64,
+    pub remaining_ms: u64,
+    /// 実行中は終了予定時刻、超過計測中は 0 に到達した時刻（UNIX エポックからのミリ秒）
+    pub deadline_unix_ms: Option<u64>,
+    pub saved_at_unix_ms: u64,
+}
+
+/// 起動時の復元結果。アプリ終了中に時間切れになったかをフロントエンドへ伝える
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreReport {
+    pub id: String,
+    pub name: String,
+    pub status: TimerStatus,
+    pub expired_while_closed: bool,
+    /// 締切を過ぎてからの経過時間
+    pub overdue_ms: u64,
+}
+
+pub fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl TimerEngine {
+    pub fn to_persisted(&self, now: Instant, wall_now: SystemTime) -> PersistedTimerState {
+        let remaining = self.remaining_at(now);
+        let now_ms = unix_millis(wall_now);
+        PersistedTimerState {
+            status: self.status,
+            duration_ms: self.duration.as_millis() as u64,
+            remaining_ms: remaining.as_millis() as u64,
+            deadline_unix_ms: match self.status {
+                TimerStatus::Running => Some(now_ms + remaining.as_millis() as u64),
+                TimerStatus::Overtime => self
+                    .overrun_at(now)
+                    .map(|d| now_ms.saturating_sub(d.as_millis() as u64)),
+                _ => None,
+            },
+            saved_at_unix_ms: now_ms,
+        }
+    }
+
+    /// 保存済み状態からエンジンを再構築する。
+    /// 実行中だったタイマーは壁時計の締切から残り時間を求め直す。
+    /// 戻り値の 2 番目は締切を過ぎていた場合の超過時間
+    pub fn from_persisted(
+        persisted: &PersistedTimerState,
+        now: Instant,
+        wall_now: SystemTime,
+    ) -> Result<(Self, Option<Duration>), String> {
+        let mut engine = Self::new();
+        engine.set(Duration::from_millis(persisted.duration_ms));
+        let mut overdue = None;
+
+        match persisted.status {
+            TimerStatus::Running => {
+                let deadline_ms = persisted
+                    .deadline_unix_ms
+                    .ok_or("Running timer state has no deadline")?;
+                let now_ms = unix_millis(wall_now);
+                if deadline_ms > now_ms {
+                    engine.remaining = Duration::from_millis(deadline_ms - now_ms);
+                    engine.deadline = Some(now + engine.remaining);
+                    engine.status = TimerStatus::Running;
+                } else {
+                    let overdue_by = Duration::from_millis(now_ms - deadline_ms);
+                    engine.finished_at = Some(now.checked_sub(overdue_by).unwrap_or(now));
+                    engine.status = TimerStatus::Finished;
+                    overdue = Some(overdue_by);
+                }
+            }
+            TimerStatus::Overtime => {
+                let finished_ms = persisted
+                    .deadline_unix_ms
+                    .ok_or("Overtime timer state has no finish time")?;
+                let overrun =
+                    Duration::from_millis(unix_millis(wall_now).saturating_sub(finished_ms));
+                engine.finished_at = Some(now.checked_sub(overrun).unwrap_or(now));
+                engine.status = TimerStatus::Overtime;
+            }
+            TimerStatus::Paused => {
+                engine.remaining = Duration::from_millis(persisted.remaining_ms);
+                engine.status = TimerStatus::Paused;
+            }
+            TimerStatus::Idle | TimerStatus::Finished => {
+                engine.remaining = Duration::from_millis(persisted.remaining_ms);
+                engine.status = persisted.status;
+            }
+        }
+
+        Ok((engine, overdue))
+    }
+}
+
+/// main ウィンドウが操作する既定のタイマー。削除できない
+pub const DEFAULT_TIMER_ID: &str = "default";
+
+/// タイマーごとのアラーム設定
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TimerAlarm {
+    /// アラーム音のファイル名。`None` なら設定画面の既定音を使う
+    pub sound: Option<String>,
+    /// 終了時に Time Up ウィンドウを表示するか
+    pub show_timeup_window: bool,
+    /// 0 到達後も止めるまで超過時間を計測するか
+    pub overtime: bool,
+}
+
+impl Default for TimerAlarm {
+    fn default() -> Self {
+        Self {
+            sound: None,
+            show_timeup_window: true,
+            overtime: false,
+        }
+    }
+}
+
+/// 名前付きタイマーの状態。`timer-tick` / `timer-finished` のペイロード
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct NamedTimerSnapshot {
+    pub id: String,
+    pub name: String,
+    pub alarm: TimerAlarm,
+    #[serde(flatten)]
+    pub timer: TimerSnapshot,
+}
+
+#[derive(Debug)]
+pub struct NamedTimer {
+    pub id: String,
+    pub name: String,
+    pub alarm: TimerAlarm,
+    pub engine: TimerEngine,
+}
+
+impl NamedTimer {
+    pub fn snapshot(&self, now: Instant) -> NamedTimerSnapshot {
+        NamedTimerSnapshot {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            alarm: self.alarm.clone(),
+            timer: self.engine.snapshot(now),
+        }
+    }
+}
+
+/// ID をキーにした複数タイマーの管理。作成順を保持する
+#[derive(Debug)]
+pub struct TimerRegistry {
+    timers: Vec<NamedTimer>,
+    next_id: u64,
+}
+
+impl Default for TimerRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimerRegistry {
+    pub fn new() -> Self {
+        Self {
+            timers: vec![NamedTimer {
+                id: DEFAULT_TIMER_ID.to_string(),
+                name: "Timer".to_string(),
+                alarm: TimerAlarm::default(),
+                engine: TimerEngine::new(),
+            }],
+            next_id: 1,
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&NamedTimer> {
+        self.timers.iter().find(|t| t.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: &str) -> Result<&mut NamedTimer, String> {
+        self.timers
+            .iter_mut()
+            .find(|t| t.id == id)
+            .ok_or_else(|| format!("Timer not found: {}", id))
+    }
+
+    /// 名前（大文字小文字を区別しない）で探す。ディープリンクの `label` などに使う
+    pub fn find_by_name(&self, name: &str) -> Option<&NamedTimer> {
+        let name = name.trim();
+        self.timers
+            .iter()
+            .find(|t| t.name.trim().eq_ignore_ascii_case(name))
+    }
+
+    pub fn create(&mut self, name: &str, duration: Duration, alarm: TimerAlarm) -> &NamedTimer {
+        let id = format!("timer-{}", self.next_id);
+        self.next_id += 1;
+        let mut engine = TimerEngine::new();
+        engine.set(duration);
+        self.timers.push(NamedTimer {
+            id,
+            name: name.trim().to_string(),
+            alarm,
+            engine,
+        });
+        self.timers.last().expect("timer was just pushed")
+    }
+
+    pub fn remove(&mut self, id: &str) -> Result<(), String> {
+        if id == DEFAULT_TIMER_ID {
+            return Err("The default timer cannot be removed".to_string());
+        }
+        let before = self.timers.len();
+        self.timers.retain(|t| t.id != id);
+        if self.timers.len() == before {
+            return Err(format!("Timer not found: {}", id));
+        }
+        Ok(())
+    }
+
+    /// 全タイマーを進め、今回終了したタイマーの ID を返す。
+    /// 超過計測が有効なタイマーはそのまま超過計測に入る
+    pub fn tick(&mut self, now: Instant) -> Vec<String> {
+        let mut finished = Vec::new();
+        for timer in &mut self.timers {
+            if timer.engine.tick(now) {
+                if timer.alarm.overtime {
+                    timer.engine.start_overtime();
+                }
+                finished.push(timer.id.clone());
+            }
+        }
+        finished
+    }
+
+    /// スリープしていた時間を全タイマーの締切に反映する
+    pub fn skip_suspended(&mut self, suspended: Duration, now: Instant) {
+        for timer in &mut self.timers {
+            timer.engine.skip_suspended(suspended, now);
+        }
+    }
+
+    /// 今回終了したタイマーのうち、締切から大きく遅れて終了したもの（取りこぼし）を返す
+    pub fn missed(&self, finished: &[String], now: Instant) -> Vec<String> {
+        finished
+            .iter()
+            .filter(|id| {
+                self.get(id)
+                    .and_then(|t| t.engine.overdue_at(now))
+                    .is_some_and(|overdue| overdue > MISSED_TIMER_GRACE)
+            })
+            .cloned()
+            .collect()
+    }
+
+    pub fn snapshots(&self, now: Instant) -> Vec<NamedTimerSnapshot> {
+        self.timers.iter().map(|t| t.snapshot(now)).collect()
+    }
+
+    /// いずれかのタイマーの表示秒が次に変わるまでの待ち時間
+    pub fn until_next_second(&self, now: Instant) -> Duration {
+        self.timers
+            .iter()
+            .map(|t| t.engine.until_next_second(now))
+            .min()
+            .unwrap_or(IDLE_POLL)
+    }
+
+    pub fn to_persisted(&self, now: Instant, wall_now: SystemTime) -> TimerStateFile {
+        TimerStateFile {
+            version: TIMER_STATE_VERSION,
+            timers: self
+                .timers
+                .iter()
+                .map(|t| PersistedNamedTimer {
+                    id: t.id.clone(),
+                    name: t.name.clone(),
+                    alarm: t.alarm.clone(),
+                    state: t.engine.to_persisted(now, wall_now),
+                })
+                .collect(),
+        }
+    }
+
+    /// 保存済みのタイマー一覧から再構築する。既定タイマーが無ければ補う
+    pub fn from_persisted(
+        timers: &[PersistedNamedTimer],
+        now: Instant,
+        wall_now: SystemTime,
+    ) -> Result<(Self, Vec<RestoreReport>), String> {
+        let mut registry = Self {
+            timers: Vec::new(),
+            next_id: 1,
+        };
+        let mut reports = Vec::new();
+
+        for persisted in timers {
+            let (mut engine, overdue) =
+                TimerEngine::from_persisted(&persisted.state, now, wall_now)?;
+            // 終了中に時間切れになった超過計測タイマーは、締切からの超過として計測を続ける
+            if overdue.is_some() && persisted.alarm.overtime {
+                engine.start_overtime();
+            }
+            if let Some(n) = persisted
+                .id
+                .strip_prefix("timer-")
+                .and_then(|n| n.parse::<u64>().ok())
+            {
+                registry.next_id = registry.next_id.max(n + 1);
+            }
+            reports.push(RestoreReport {
+                id: persisted.id.clone(),
+                name: persisted.name.clone(),
+                status: engine.status(),
+                expired_while_closed: overdue.is_some(),
+                overdue_ms: overdue.map(|d| d.as_millis() as u64).unwrap_or(0),
+            });
+            registry.timers.push(NamedTimer {
+                id: persisted.id.clone(),
+                name: persisted.name.clone(),
+                alarm: persisted.alarm.clone(),
+                engine,
+            });
+        }
+
+        if registry.get(DEFAULT_TIMER_ID).is_none() {
+            registry.timers.insert(0, Self::new().timers.remove(0));
+        }
+        Ok((registry, reports))
+    }
+}
+
+/// `timer_state.json` に保存する名前付きタイマー
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedNamedTimer {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub alarm: TimerAlarm,
+    #[serde(flatten)]
+    pub state: PersistedTimerState,
+}
+
+/// `timer_state.json` のファイル全体
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimerStateFile {
+    pub version: u32,
+    pub timers: Vec<PersistedNamedTimer>,
+}
+
+/// `timer_state.json` を読み込む。単一タイマー時代の v1 形式は既定タイマーとして移行する
+pub fn parse_timer_state_file(json: &str) -> Result<Vec<PersistedNamedTimer>, String> {
+    let value: serde_json::Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let version = value
+        .get("version")
+        .and_then(|v| v.as_u64())
+        .ok_or("Timer state has no version")?;
+    match version {
+        1 => {
+            let state: PersistedTimerState =
+                serde_json::from_value(value).map_err(|e| e.to_string())?;
+            Ok(vec![PersistedNamedTimer {
+                id: DEFAULT_TIMER_ID.to_string(),
+                name: "Timer".to_string(),
+                alarm: TimerAlarm::default(),
+                state,
+            }])
+        }
+        v if v == u64::from(TIMER_STATE_VERSION) => {
+            let file: TimerStateFile = serde_json::from_value(value).map_err(|e| e.to_string())?;
+            Ok(file.timers)
+        }
+        v => Err(format!("Unsupported timer state version: {}", v)),
+    }
+}
+
+/// Tauri の managed state として保持するタイマー一覧
+#[derive(Default)]
+pub struct TimerRegistryState(pub Mutex<TimerRegistry>);
+
+/// 起動時の復元結果（復元していなければ空）
+#[derive(Default)]
+pub struct TimerRestoreState(pub Mutex<Vec<RestoreReport>>);
+
+/// ロック中に作った保存内容。書き込みはロックを放してから `write_timer_state` で行う
+struct PendingTimerState {
+    generation: u64,
+    file: TimerStateFile,
+}
+
+/// 保存内容に振る通し番号。レジストリのロック中に採番するので変更順と一致する
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(1);
+/// 最後に書き込んだ通し番号。ロック外の書き込みが前後しても古い内容で上書きしない
+static WRITTEN_GENERATION: Mutex<u64> = Mutex::new(0);
+
+/// レジストリのロック中に呼び、保存内容を作る
+fn pending_timer_state(registry: &TimerRegistry) -> PendingTimerState {
+    PendingTimerState {
+        generation: NEXT_GENERATION.fetch_add(1, Ordering::Relaxed),
+        file: registry.to_persisted(Instant::now(), SystemTime::now()),
+    }
+}
+
+fn write_timer_state(pending: PendingTimerState) -> Result<(), Box<dyn std::error::Error>> {
+    let mut written = WRITTEN_GENERATION.lock().unwrap_or_else(|e| e.into_inner());
+    if pending.generation < *written {
+        return Ok(());
+    }
+    let json = serde_json::to_string_pretty(&pending.file)?;
+    fs::write(crate::get_timer_state_path(), json)?;
+    *written = pending.generation;
+    Ok(())
+}
+
+/// 現在のタイマー状態を `timer_state.json` に保存する
+pub fn save_timer_state(app: &AppHandle) {
+    let pending = {
+        let state = app.state::<TimerRegistryState>();
+        let registry = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        pending_timer_state(&registry)
+    };
+    match write_timer_state(pending) {
+        Ok(()) => println!("DEBUG: Timer state saved"),
+        Err(e) => println!("DEBUG: Failed to save timer state: {}", e),
+    }
+}
+
+/// `timer_state.json` を読み込み、タイマー一覧と復元結果を managed state に反映する
+pub fn restore_timer_state(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let path = crate::get_timer_state_path();
+    if !path.exists() {
+        println!("DEBUG: No saved timer state found");
+        return Ok(());
+    }
+
+    let json = fs::read_to_string(&path)?;
+    let timers = parse_timer_state_file(&json)?;
+    let (registry, reports) =
+        TimerRegistry::from_persisted(&timers, Instant::now(), SystemTime::now())?;
+    println!("DEBUG: Timer state restored: {:?}", reports);
+
+    *app.state::<TimerRegistryState>()
+        .0
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = registry;
+    *app.state::<TimerRestoreState>()
+        .0
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = reports;
+    Ok(())
+}
+
+fn emit_snapshot(app: &AppHandle, event: &str, snapshot: &NamedTimerSnapshot) {
+    if event == TIMER_TICK_EVENT && snapshot.id == DEFAULT_TIMER_ID {
+        crate::tray::update_timer_status(app, &snapshot.timer);
+    }
+    crate::event_stream::publish(app, event, snapshot);
+    if let Err(e) = app.emit(event, snapshot) {
+        println!("DEBUG: Failed to emit {}: {}", event, e);
+    }
+}
+
+fn emit_timer_list(app: &AppHandle, timers: &[NamedTimerSnapshot]) {
+    if let Err(e) = app.emit(TIMERS_CHANGED_EVENT, timers) {
+        println!("DEBUG: Failed to emit {}: {}", TIMERS_CHANGED_EVENT, e);
+    }
+}
+
+fn lock_registry<'a>(
+    state: &'a State<TimerRegistryState>,
+) -> Result<MutexGuard<'a, TimerRegistry>, String> {
+    state
+        .0
+        .lock()
+        .map_err(|e| format!("Timer registry lock poisoned: {}", e))
+}
+
+/// 指定タイマーを操作し、変更後の状態を全ウィンドウへ通知する
+pub(crate) fn update_timer<F>(
+    app: &AppHandle,
+    state: &State<TimerRegistryState>,
+    id: Option<String>,
+    f: F,
+) -> Result<NamedTimerSnapshot, String>
+where
+    F: FnOnce(&mut NamedTimer, Instant),
+{
+    let id = id.unwrap_or_else(|| DEFAULT_TIMER_ID.to_string());
+    let (snapshot, session, pending) = {
+        let mut registry = lock_registry(state)?;
+        let now = Instant::now();
+        let timer = registry.get_mut(&id)?;
+        let overrun = timer.engine.overrun_at(now);
+        f(timer, now);
+        let session = overrun
+            .filter(|_| timer.engine.status() != TimerStatus::Overtime)
+            .map(|overrun| SessionRecord::finished(timer, overrun));
+        let snapshot = timer.snapshot(now);
+        (snapshot, session, pending_timer_state(&registry))
+    };
+    if let Err(e) = write_timer_state(pending) {
+        println!("DEBUG: Failed to save timer state: {}", e);
+    }
+    if let Some(record) = session {
+        crate::session::record_session(app, record);
+    }
+    emit_snapshot(app, TIMER_TICK_EVENT, &snapshot);
+    Ok(snapshot)
+}
+
+/// 指定タイマーの現在の状態。HTTP API や CLI など main ウィンドウ以外から使う
+pub(crate) fn timer_snapshot(
+    app: &AppHandle,
+    id: Option<String>,
+) -> Result<NamedTimerSnapshot, String> {
+    let id = id.unwrap_or_else(|| DEFAULT_TIMER_ID.to_string());
+    let state = app.state::<TimerRegistryState>();
+    let registry = lock_registry(&state)?;
+    registry
+        .get(&id)
+        .map(|t| t.snapshot(Instant::now()))
+        .ok_or_else(|| format!("Timer not found: {}", id))
+}
+
+/// 名前でタイマーを探して ID を返す。見つからず `create` があれば、その時間で作る。
+/// ID を知らないディープリンクからの操作に使う
+pub(crate) fn timer_id_by_name(
+    app: &AppHandle,
+    name: &str,
+    create: Option<Duration>,
+) -> Result<String, String> {
+    let state = app.state::<TimerRegistryState>();
+    let mut registry = lock_registry(&state)?;
+    if let Some(timer) = registry.find_by_name(name) {
+        return Ok(timer.id.clone());
+    }
+    let duration = create.ok_or_else(|| format!("Timer not found: {}", name.trim()))?;
+    let id = registry
+        .create(name, duration, TimerAlarm::default())
+        .id
+        .clone();
+    println!("DEBUG: Timer created: {} ({})", name.trim(), id);
+    commit_timer_list(app, registry);
+    Ok(id)
+}
+
+/// 実行中（超過計測中を含む）なら一時停止し、それ以外なら開始・再開する。
+/// トレイやショートカットなど main ウィンドウ以外からの操作に使う
+pub(crate) fn toggle_start_pause(
+    app: &AppHandle,
+    id: Option<String>,
+) -> Result<NamedTimerSnapshot, String> {
+    let state = app.state::<TimerRegistryState>();
+    update_timer(app, &state, id, |timer, now| {
+        match timer.engine.status() {
+            TimerStatus::Running | TimerStatus::Overtime => timer.engine.pause(now),
+            _ => timer.engine.start(now),
+        };
+    })
+}
+
+/// タイマーの増減・名前変更後に一覧を保存して通知する。
+/// 保存内容と一覧を作ったらロックを放してから書き込む
+fn commit_timer_list(app: &AppHandle, registry: MutexGuard<'_, TimerRegistry>) {
+    let pending = pending_timer_state(&registry);
+    let snapshots = registry.snapshots(Instant::now());
+    drop(registry);
+    if let Err(e) = write_timer_state(pending) {
+        println!("DEBUG: Failed to save timer state: {}", e);
+    }
+    emit_timer_list(app, &snapshots);
+}
+
+/// 表示秒が変わるたびに tick を、終了時に finished を emit するスレッドを起動する。
+/// 毎回時計の飛びを確認し、スリープ復帰時は締切を合わせ直して `timer-resynced` を emit する
+pub fn spawn_ticker(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut last_display: HashMap<String, (TimerStatus, u64)> = HashMap::new();
+        let mut watch = ClockWatch::new(&SystemClock, IDLE_POLL);
+        loop {
+            let jump = watch.check(&SystemClock);
+            let policy = crate::resync::missed_alarm_policy(&app.state::<ResyncState>());
+            let (snapshots, finished, missed, sessions, pending, wait) = {
+                let state = app.state::<TimerRegistryState>();
+                let mut registry = state.0.lock().unwrap_or_else(|e| e.into_inner());
+                let now = Instant::now();
+                if let Some(ClockJump::Suspended(suspended)) = jump {
+                    registry.skip_suspended(suspended, now);
+                }
+                let finished = registry.tick(now);
+                let missed = registry.missed(&finished, now);
+                // 超過計測に入ったタイマーは計測終了時に記録する
+                let sessions: Vec<SessionRecord> = finished
+                    .iter()
+                    .filter_map(|id| registry.get(id))
+                    .filter(|timer| timer.engine.status() != TimerStatus::Overtime)
+                    .map(|timer| SessionRecord::finished(timer, Duration::ZERO))
+                    .collect();
+                (
+                    registry.snapshots(now),
+                    finished,
+                    missed,
+                    sessions,
+                    jump.map(|_| pending_timer_state(&registry)),
+                    registry.until_next_second(now),
+                )
+            };
+            if let Some(pending) = pending {
+                if let Err(e) = write_timer_state(pending) {
+                    println!("DEBUG: Failed to save timer state: {}", e);
+                }
+            }
+            // 記録はファイルを書くのでロックを放してから
+            for record in sessions {
+                crate::session::record_session(&app, record);
+            }
+
+            if let Some(jump) = jump {
+                println!("DEBUG: Clock jump detected: {:?}", jump);
+                let report = ResyncReport {
+                    reason: jump.reason(),
+                    jump_ms: jump.amount().as_millis() as u64,
+                    missed_timers: missed.clone(),
+                    policy,
+                };
+                if let Err(e) = app.emit(TIMER_RESYNCED_EVENT, &report) {
+                    println!("DEBUG: Failed to emit {}: {}", TIMER_RESYNCED_EVENT, e);
+                }
+            }
+
+            for snapshot in &snapshots {
+                let display = (snapshot.timer.status, snapshot.timer.display().total_secs());
+                let previous = last_display.insert(snapshot.id.clone(), display);
+                let counting = matches!(
+                    snapshot.timer.status,
+                    TimerStatus::Running | TimerStatus::Overtime
+                );
+                let finished_now = finished.contains(&snapshot.id);
+                // 終了したタイマーは下で必ず tick を送るので、ここでは重ねない
+                if !finished_now && (counting || jump.is_some()) && previous != Some(display) {
+                    emit_snapshot(&app, TIMER_TICK_EVENT, snapshot);
+                }
+
+                if finished_now {
+                    println!("DEBUG: Timer finished: {} ({})", snapshot.name, snapshot.id);
+                    emit_snapshot(&app, TIMER_TICK_EVENT, snapshot);
+                    if missed.contains(&snapshot.id) && policy == MissedAlarmPolicy::Drop {
+                        println!("DEBUG: Dropping missed alarm for timer {}", snapshot.id);
+                    } else {
+                        emit_snapshot(&app, TIMER_FINISHED_EVENT, snapshot);
+                        crate::escalation::on_timer_finished(&app, snapshot);
+                    }
+                    crate::pomodoro::on_timer_finished(&app, snapshot);
+                    crate::sequence::on_timer_finished(&app, snapshot);
+                }
+            }
+            last_display.retain(|id, _| snapshots.iter().any(|s| &s.id == id));
+
+            std::thread::sleep(wait);
+        }
+    });
+}
+
+/// コマンド引数の時・分・秒を正規化して秒数にする
+pub(crate) fn clock_total_secs(hours: Option<u32>, minutes: u32, seconds: u32) -> u64 {
+    ClockTime::normalize(
+        u64::from(hours.unwrap_or(0)),
+        u64::from(minutes),
+        u64::from(seconds),
+    )
+    .total_secs()
+}
+
+#[tauri::command]
+pub async fn timer_set(
+    app: AppHandle,
+    state: State<'_, TimerRegistryState>,
+    id: Option<String>,
+    hours: Option<u32>,
+    minutes: u32,
+    seconds: u32,
+) -> Result<NamedTimerSnapshot, String> {
+    let total = clock_total_secs(hours, minutes, seconds);
+    update_timer(&app, &state, id, |timer, _| {
+        timer.engine.set(Duration::from_secs(total))
+    })
+}
+
+#[tauri::command]
+pub async fn timer_start(
+    app: AppHandle,
+    state: State<'_, TimerRegistryState>,
+    id: Option<String>,
+) -> Result<NamedTimerSnapshot, String> {
+    update_timer(&app, &state, id, |timer, now| {
+        timer.engine.start(now);
+    })
+}
+
+#[tauri::command]
+pub async fn timer_pause(
+    app: AppHandle,
+    state: State<'_, TimerRegistryState>,
+    id: Option<String>,
+) -> Result<NamedTimerSnapshot, String> {
+    update_timer(&app, &state, id, |timer, now| {
+        timer.engine.pause(now);
+    })
+}
+
+#[tauri::command]
+pub async fn timer_resume(
+    app: AppHandle,
+    state: State<'_, TimerRegistryState>,
+    id: Option<String>,
+) -> Result<NamedTimerSnapshot, String> {
+    update_timer(&app, &state, id, |timer, now| {
+        timer.engine.resume(now);
+    })
+}
+
+#[tauri::command]
+pub async fn timer_reset(
+    app: AppHandle,
+    state: State<'_, TimerRegistryState>,
+    id: Option<String>,
+) -> Result<NamedTimerSnapshot, String> {
+    update_timer(&app, &state, id, |timer, _| timer.engine.reset())
+}
+
+#[tauri::command]
+pub async fn timer_get_state(
+    state: State<'_, TimerRegistryState>,
+    id: Option<String>,
+) -> Result<NamedTimerSnapshot, String> {
+    let id = id.unwrap_or_else(|| DEFAULT_TIMER_ID.to_string());
+    let registry = lock_registry(&state)?;
+    registry
+        .get(&id)
+        .map(|t| t.snapshot(Instant::now()))
+        .ok_or_else(|| format!("Timer not found: {}", id))
+}
+
+#[tauri::command]
+pub async fn timer_list(
+    state: State<'_, TimerRegistryState>,
+) -> Result<Vec<NamedTimerSnapshot>, String> {
+    let registry = lock_registry(&state)?;
+    Ok(registry.snapshots(Instant::now()))
+}
+
+#[tauri::command]
+pub async fn timer_create(
+    app: AppHandle,
+    state: State<'_, TimerRegistryState>,
+    name: String,
+    hours: Option<u32>,
+    minutes: u32,
+    seconds: u32,
+    alarm: Option<TimerAlarm>,
+) -> Result<NamedTimerSnapshot, String> {
+    if name.trim().is_empty() {
+        return Err("Timer name must not be empty".to_string());
+    }
+    let total = clock_total_secs(hours, minutes, seconds);
+    let mut registry = lock_registry(&state)?;
+    let snapshot = registry
+        .create(&name, Duration::from_secs(total), alarm.unwrap_or_default())
+        .snapshot(Instant::now());
+    println!("DEBUG: Timer created: {} ({})", snapshot.name, snapshot.id);
+    commit_timer_list(&app, registry);
+    Ok(snapshot)
+}
+
+#[tauri::command]
+pub async fn timer_remove(
+    app: AppHandle,
+    state: State<'_, TimerRegistryState>,
+    id: String,
+) -> Result<(), String> {
+    let mut registry = lock_registry(&state)?;
+    registry.remove(&id)?;
+    println!("DEBUG: Timer removed: {}", id);
+    commit_timer_list(&app, registry);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn timer_rename(
+    app: AppHandle,
+    state: State<'_, TimerRegistryState>,
+    id: String,
+    name: String,
+) -> Result<NamedTimerSnapshot, String> {
+    if name.trim().is_empty() {
+        return Err("Timer name must not be empty".to_string());
+    }
+    let mut registry = lock_registry(&state)?;
+    let timer = registry.get_mut(&id)?;
+    timer.name = name.trim().to_string();
+    let snapshot = timer.snapshot(Instant::now());
+    commit_timer_list(&app, registry);
+    Ok(snapshot)
+}
+
+#[tauri::command]
+pub async fn timer_set_alarm(
+    app: AppHandle,
+    state: State<'_, TimerRegistryState>,
+    id: String,
+    alarm: TimerAlarm,
+) -> Result<NamedTimerSnapshot, String> {
+    let mut registry = lock_registry(&state)?;
+    let timer = registry.get_mut(&id)?;
+    timer.alarm = alarm;
+    let snapshot = timer.snapshot(Instant::now());
+    commit_timer_list(&app, registry);
+    Ok(snapshot)
+}
+
+/// 超過計測の有効・無効だけを切り替える（他のアラーム設定は維持する）
+#[tauri::command]
+pub async fn timer_set_overtime(
+    app: AppHandle,
+    state: State<'_, TimerRegistryState>,
+    id: Option<String>,
+    enabled: bool,
+) -> Result<NamedTimerSnapshot, String> {
+    let is_default = id.as_deref().is_none_or(|id| id == DEFAULT_TIMER_ID);
+    let snapshot = update_timer(&app, &state, id, |timer, now| {
+        timer.alarm.overtime = enabled;
+        // 計測中に無効化されたらその時点で打ち切る
+        if !enabled && timer.engine.status() == TimerStatus::Overtime {
+            timer.engine.pause(now);
+        }
+    })?;
+    // シーケンス終了時に戻すアラームにも反映する（ポモドーロは超過計測を戻さない）
+    if is_default {
+        crate::sequence::remember_overtime(&app, enabled);
+    }
+    Ok(snapshot)
+}
+
+/// 起動時に復元した状態を返す。終了中に時間切れになったタイマーは `expiredWhileClosed` が立つ
+#[tauri::command]
+pub async fn timer_restore_report(
+    state: State<'_, TimerRestoreState>,
+) -> Result<Vec<RestoreReport>, String> {
+    let reports = state
+        .0
+        .lock()
+        .map_err(|e| format!("Timer restore state lock poisoned: {}", e))?;
+    Ok(reports.clone())
+}
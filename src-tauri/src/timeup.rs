@@ -0,0 +1,157 @@
+//! Time Up ウィンドウを出すモニターの選択。
+//!
+//! すべてのモニター（モニターごとに 1 枚）、main ウィンドウのあるモニター、名前で指定したモニターから選ぶ。
+//! 1 枚目のウィンドウのラベルは `timeup`、2 枚目以降は `timeup-1`、`timeup-2`…。
+//! 各ウィンドウはそのモニターの倍率で論理サイズを求め、モニター全体を覆う。
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::window_state::MonitorInfo;
+
+/// 1 枚目の Time Up ウィンドウのラベル
+pub const TIMEUP_WINDOW: &str = "timeup";
+
+/// Time Up ウィンドウを出すモニター
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(tag = "kind", content = "name", rename_all = "camelCase")]
+pub enum TimeupScreen {
+    /// 接続中のすべてのモニター
+    All,
+    /// main ウィンドウのあるモニター
+    #[default]
+    Main,
+    /// 名前で指定したモニター。見つからなければ main ウィンドウのモニター
+    Named(String),
+}
+
+#[derive(Default)]
+pub struct TimeupScreenState(pub Mutex<TimeupScreen>);
+
+/// モニターを覆うウィンドウの位置（物理）と大きさ（そのモニターの倍率での論理サイズ）
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeupGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: f64,
+    pub height: f64,
+    pub scale_factor: f64,
+}
+
+impl TimeupGeometry {
+    pub fn covering(monitor: &MonitorInfo) -> Self {
+        Self {
+            x: monitor.x,
+            y: monitor.y,
+            width: monitor.width as f64 / monitor.scale_factor,
+            height: monitor.height as f64 / monitor.scale_factor,
+            scale_factor: monitor.scale_factor,
+        }
+    }
+}
+
+/// `index` 枚目の Time Up ウィンドウのラベル
+pub fn timeup_label(index: usize) -> String {
+    match index {
+        0 => TIMEUP_WINDOW.to_string(),
+        n => format!("{}-{}", TIMEUP_WINDOW, n),
+    }
+}
+
+/// Time Up ウィンドウのラベルか
+pub fn is_timeup_label(label: &str) -> bool {
+    label == TIMEUP_WINDOW
+        || label
+            .strip_prefix(TIMEUP_WINDOW)
+            .and_then(|rest| rest.strip_prefix('-'))
+            .is_some_and(|n| n.parse::<usize>().is_ok())
+}
+
+/// Time Up ウィンドウを出すモニター。`main_monitor` は main ウィンドウのモニター
+/// （取れなければプライマリモニター）。どれも見つからなければ先頭のモニター
+pub fn timeup_monitors(
+    screen: &TimeupScreen,
+    monitors: &[MonitorInfo],
+    main_monitor: Option<&MonitorInfo>,
+) -> Vec<MonitorInfo> {
+    let main = || {
+        main_monitor
+            .or_else(|| monitors.first())
+            .cloned()
+            .into_iter()
+            .collect()
+    };
+    match screen {
+        TimeupScreen::All if !monitors.is_empty() => monitors.to_vec(),
+        TimeupScreen::All | TimeupScreen::Main => main(),
+        TimeupScreen::Named(name) => match monitors
+            .iter()
+            .find(|m| m.name.as_deref() == Some(name.as_str()))
+        {
+            Some(monitor) => vec![monitor.clone()],
+            None => {
+                println!(
+                    "DEBUG: Monitor {:?} not found, showing Time Up on the main window's monitor",
+                    name
+                );
+                main()
+            }
+        },
+    }
+}
+
+/// 今の設定で Time Up ウィンドウを出すモニター。モニターが取れなければ空
+pub fn target_monitors(app: &AppHandle) -> Vec<MonitorInfo> {
+    let screen = app
+        .state::<TimeupScreenState>()
+        .0
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    let monitors: Vec<MonitorInfo> = match app.available_monitors() {
+        Ok(monitors) => monitors.iter().map(MonitorInfo::from_monitor).collect(),
+        Err(e) => {
+            println!("DEBUG: Failed to list monitors: {}", e);
+            Vec::new()
+        }
+    };
+    let main_monitor = app
+        .get_webview_window("main")
+        .and_then(|w| w.current_monitor().ok().flatten())
+        .or_else(|| app.primary_monitor().ok().flatten())
+        .map(|m| MonitorInfo::from_monitor(&m));
+    timeup_monitors(&screen, &monitors, main_monitor.as_ref())
+}
+
+/// 開いている Time Up ウィンドウ
+pub fn timeup_windows(app: &AppHandle) -> Vec<tauri::WebviewWindow> {
+    app.webview_windows()
+        .into_iter()
+        .filter(|(label, _)| is_timeup_label(label))
+        .map(|(_, window)| window)
+        .collect()
+}
+
+#[tauri::command]
+pub async fn timeup_set_screen(
+    state: State<'_, TimeupScreenState>,
+    screen: TimeupScreen,
+) -> Result<(), String> {
+    let mut current = state
+        .0
+        .lock()
+        .map_err(|e| format!("Time Up screen state lock poisoned: {}", e))?;
+    println!("DEBUG: Time Up screen set to {:?}", screen);
+    *current = screen;
+    Ok(())
+}
+
+/// 設定画面で選べるモニター名
+#[tauri::command]
+pub async fn timeup_list_monitors(app: AppHandle) -> Result<Vec<String>, String> {
+    let monitors = app
+        .available_monitors()
+        .map_err(|e| format!("Failed to list monitors: {}", e))?;
+    Ok(monitors.iter().filter_map(|m| m.name().cloned()).collect())
+}
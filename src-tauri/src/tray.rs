@@ -0,0 +1,251 @@
+//! システムトレイ（macOS ではメニューバー）のアイコンとメニュー。
+//!
+//! タイトル・ツールチップに既定タイマーの残り時間を表示し、
+//! main ウィンドウを隠したままでも開始・一時停止・リセット・スヌーズやプリセットの起動、
+//! レイヤー表示の切り替え、設定画面の表示ができるようにする。
+//! 表示は既定タイマーの `timer-tick` と同じタイミングで更新する。
+
+use std::sync::Mutex;
+use tauri::menu::{IsMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::timer::{self, TimerRegistryState, TimerSnapshot, TimerStatus, DEFAULT_TIMER_ID};
+
+const TRAY_ID: &str = "main";
+const TRAY_TOOLTIP: &str = "Lightning Timer";
+
+const MENU_START_PAUSE: &str = "start_pause";
+const MENU_RESET: &str = "reset";
+const MENU_SNOOZE: &str = "snooze";
+const MENU_TOGGLE_LAYER: &str = "toggle_layer";
+const MENU_SHOW_MAIN: &str = "show_main";
+const MENU_SETTINGS: &str = "settings";
+const MENU_QUIT: &str = "quit";
+/// プリセット項目の ID はこの接頭辞 + プリセット ID
+const MENU_PRESET_PREFIX: &str = "preset:";
+
+/// 状態に応じて書き換えるメニュー項目
+#[derive(Default)]
+pub struct TrayState(pub Mutex<Option<MenuItem<Wry>>>);
+
+/// トレイのタイトルに出す文字列。停止中は `None`（アイコンだけにする）
+pub fn tray_title(snapshot: &TimerSnapshot) -> Option<String> {
+    let display = snapshot.display();
+    match snapshot.status {
+        TimerStatus::Idle => None,
+        TimerStatus::Running => Some(display.to_string()),
+        TimerStatus::Paused => Some(format!("⏸ {}", display)),
+        TimerStatus::Overtime => Some(format!("-{}", display)),
+        TimerStatus::Finished => Some("TIME UP".to_string()),
+    }
+}
+
+/// 開始／一時停止項目の表示名
+pub fn start_pause_label(status: TimerStatus) -> &'static str {
+    match status {
+        TimerStatus::Running | TimerStatus::Overtime => "Pause",
+        TimerStatus::Paused => "Resume",
+        TimerStatus::Idle | TimerStatus::Finished => "Start",
+    }
+}
+
+fn default_timer_status(app: &AppHandle) -> TimerStatus {
+    let state = app.state::<TimerRegistryState>();
+    let registry = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    registry
+        .get(DEFAULT_TIMER_ID)
+        .map(|t| t.engine.status())
+        .unwrap_or(TimerStatus::Idle)
+}
+
+fn build_menu(app: &AppHandle) -> tauri::Result<(Menu<Wry>, MenuItem<Wry>)> {
+    let status = default_timer_status(app);
+    let start_pause = MenuItem::with_id(
+        app,
+        MENU_START_PAUSE,
+        start_pause_label(status),
+        true,
+        None::<&str>,
+    )?;
+    let reset = MenuItem::with_id(app, MENU_RESET, "Reset", true, None::<&str>)?;
+    let snooze = MenuItem::with_id(app, MENU_SNOOZE, "Snooze", true, None::<&str>)?;
+
+    let presets = match crate::preset::load_presets() {
+        Ok(library) => library.presets,
+        Err(e) => {
+            println!("DEBUG: Failed to load presets for tray: {}", e);
+            Vec::new()
+        }
+    };
+    let preset_items = presets
+        .iter()
+        .map(|p| {
+            let label = format!(
+                "{} ({})",
+                p.name,
+                crate::duration::ClockTime::from_secs(p.duration_secs)
+            );
+            MenuItem::with_id(
+                app,
+                format!("{}{}", MENU_PRESET_PREFIX, p.id),
+                label,
+                true,
+                None::<&str>,
+            )
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let preset_refs: Vec<&dyn IsMenuItem<Wry>> = preset_items
+        .iter()
+        .map(|item| item as &dyn IsMenuItem<Wry>)
+        .collect();
+    let presets_menu = Submenu::with_items(app, "Presets", !presets.is_empty(), &preset_refs)?;
+
+    let toggle_layer = MenuItem::with_id(
+        app,
+        MENU_TOGGLE_LAYER,
+        "Toggle Layer Overlay",
+        true,
+        None::<&str>,
+    )?;
+    let show_main = MenuItem::with_id(
+        app,
+        MENU_SHOW_MAIN,
+        "Show Lightning Timer",
+        true,
+        None::<&str>,
+    )?;
+    let settings = MenuItem::with_id(app, MENU_SETTINGS, "Settings…", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, MENU_QUIT, "Quit", true, None::<&str>)?;
+
+    let menu = Menu::with_items(
+        app,
+        &[
+            &start_pause,
+            &reset,
+            &snooze,
+            &PredefinedMenuItem::separator(app)?,
+            &presets_menu,
+            &PredefinedMenuItem::separator(app)?,
+            &toggle_layer,
+            &show_main,
+            &settings,
+            &PredefinedMenuItem::separator(app)?,
+            &quit,
+        ],
+    )?;
+    Ok((menu, start_pause))
+}
+
+/// トレイアイコンを作成する
+pub fn create_tray(app: &AppHandle) -> tauri::Result<()> {
+    let (menu, start_pause) = build_menu(app)?;
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip(TRAY_TOOLTIP)
+        .menu(&menu)
+        .on_menu_event(handle_menu_event);
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+    *app.state::<TrayState>()
+        .0
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = Some(start_pause);
+    Ok(())
+}
+
+/// メニューを作り直す（プリセットの変更時など）
+pub fn refresh_menu(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    match build_menu(app) {
+        Ok((menu, start_pause)) => {
+            if let Err(e) = tray.set_menu(Some(menu)) {
+                println!("DEBUG: Failed to set tray menu: {}", e);
+            }
+            *app.state::<TrayState>()
+                .0
+                .lock()
+                .unwrap_or_else(|e| e.into_inner()) = Some(start_pause);
+        }
+        Err(e) => println!("DEBUG: Failed to build tray menu: {}", e),
+    }
+}
+
+/// 既定タイマーの状態をタイトル・ツールチップ・開始／一時停止項目に反映する
+pub fn update_timer_status(app: &AppHandle, snapshot: &TimerSnapshot) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let title = tray_title(snapshot);
+    let tooltip = match &title {
+        Some(title) => format!("{} — {}", TRAY_TOOLTIP, title),
+        None => TRAY_TOOLTIP.to_string(),
+    };
+    if let Err(e) = tray.set_title(title) {
+        println!("DEBUG: Failed to set tray title: {}", e);
+    }
+    if let Err(e) = tray.set_tooltip(Some(tooltip)) {
+        println!("DEBUG: Failed to set tray tooltip: {}", e);
+    }
+    let state = app.state::<TrayState>();
+    let item = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(item) = item.as_ref() {
+        if let Err(e) = item.set_text(start_pause_label(snapshot.status)) {
+            println!("DEBUG: Failed to update tray menu item: {}", e);
+        }
+    }
+}
+
+fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        if let Err(e) = window.show() {
+            println!("DEBUG: Failed to show main window: {}", e);
+        }
+        crate::force_focus_window(&window);
+    }
+}
+
+fn handle_menu_event(app: &AppHandle, event: MenuEvent) {
+    let id = event.id().as_ref();
+    println!("DEBUG: Tray menu selected: {}", id);
+    let registry = app.state::<TimerRegistryState>();
+    let result = match id {
+        MENU_START_PAUSE => timer::toggle_start_pause(app, None).map(|_| ()),
+        MENU_RESET => {
+            timer::update_timer(app, &registry, None, |t, _| t.engine.reset()).map(|_| ())
+        }
+        MENU_SNOOZE => crate::escalation::snooze(app).map(|_| ()),
+        MENU_TOGGLE_LAYER => {
+            crate::toggle_layer_window(app);
+            Ok(())
+        }
+        MENU_SHOW_MAIN => {
+            show_main_window(app);
+            Ok(())
+        }
+        MENU_SETTINGS => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = crate::show_settings_window(app).await {
+                    println!("DEBUG: Failed to show settings from tray: {}", e);
+                }
+            });
+            Ok(())
+        }
+        // 状態の保存などは `RunEvent::Exit` で行う
+        MENU_QUIT => {
+            app.exit(0);
+            Ok(())
+        }
+        _ => match id.strip_prefix(MENU_PRESET_PREFIX) {
+            Some(preset_id) => crate::preset::launch_preset(app, preset_id).map(|_| ()),
+            None => Ok(()),
+        },
+    };
+    if let Err(e) = result {
+        println!("DEBUG: Tray action {} failed: {}", id, e);
+    }
+}
@@ -0,0 +1,641 @@
+//! ウィンドウの位置・サイズ・表示モード・モニターの保存と復元。
+//!
+//! `main`・`layer_ctrl`・`settings`・`layer` をラベルをキーにして `window_states.json` に保存する。
+//! 移動・リサイズのたびにメモリ上の記録を更新し、ファイルへはドラッグ終了・ウィンドウを隠す・
+//! 終了のときに書き出す。位置は物理座標、サイズは論理サイズで持つ。
+//! 各ウィンドウは作成したときに 1 度だけ復元する。
+//!
+//! 外部モニターを外した後などで保存位置が画面外になる場合は、復元時に `place_window` で
+//! 見える位置へ寄せるか、main ウィンドウのモニター中央に置き直す（main は枠がないので見失いやすい）。
+//!
+//! 配置はモニター構成（台数・サイズ・倍率・位置）の指紋ごとにも `profiles` へ記録する。
+//! ノート PC 単体・デスクでのドッキング・プロジェクター接続のそれぞれで最後の配置を覚えておき、
+//! 起動時とモニター構成が変わったとき（`spawn_monitor_watch` が検出）にその構成の配置を反映する。
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+/// 保存・復元するウィンドウのラベル
+pub const TRACKED_WINDOWS: [&str; 4] = ["main", "layer_ctrl", "settings", "layer"];
+
+/// 2: モニター構成ごとの `profiles` を追加
+pub const WINDOW_STATE_VERSION: u32 = 2;
+
+/// モニター構成の変化を確かめる間隔
+const MONITOR_POLL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// main ウィンドウの表示モード
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DisplayMode {
+    Normal,
+    Compact,
+    Minimal,
+}
+
+/// ウィンドウが乗っていたモニター。位置・サイズは物理ピクセル
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorInfo {
+    pub name: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f64,
+}
+
+/// 接続中のモニターの組み合わせ。位置の順に並べて比べる
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorLayout {
+    monitors: Vec<MonitorInfo>,
+}
+
+impl MonitorLayout {
+    pub fn new(mut monitors: Vec<MonitorInfo>) -> Self {
+        monitors.sort_by_key(|m| (m.x, m.y));
+        Self { monitors }
+    }
+
+    /// 台数・サイズ・倍率・位置から作る指紋（名前は接続先で変わることがあるので含めない）。
+    /// 例: `1440x900@2+0+0,2560x1440@1+1440+0`
+    pub fn fingerprint(&self) -> String {
+        self.monitors
+            .iter()
+            .map(|m| {
+                format!(
+                    "{}x{}@{}+{}+{}",
+                    m.width, m.height, m.scale_factor, m.x, m.y
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// 調べるときに読みやすい説明。例: `2 displays: Built-in 1440x900, DELL 2560x1440`
+    pub fn description(&self) -> String {
+        let count = match self.monitors.len() {
+            1 => "1 display".to_string(),
+            n => format!("{} displays", n),
+        };
+        let monitors = self
+            .monitors
+            .iter()
+            .map(|m| {
+                let size = format!("{}x{}", m.width, m.height);
+                match &m.name {
+                    Some(name) if !name.is_empty() => format!("{} {}", name, size),
+                    _ => size,
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{}: {}", count, monitors)
+    }
+}
+
+/// 1 つのモニター構成での配置
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct LayoutProfile {
+    /// `MonitorLayout::description`
+    pub description: String,
+    pub windows: BTreeMap<String, WindowState>,
+}
+
+/// 物理ピクセルの矩形
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    /// 重なっている幅と高さ（重なっていなければ 0 以下）
+    fn overlap(&self, other: &Rect) -> (i64, i64) {
+        let span = |a: i32, a_len: u32, b: i32, b_len: u32| {
+            let start = i64::from(a).max(i64::from(b));
+            let end = (i64::from(a) + i64::from(a_len)).min(i64::from(b) + i64::from(b_len));
+            end - start
+        };
+        (
+            span(self.x, self.width, other.x, other.width),
+            span(self.y, self.height, other.y, other.height),
+        )
+    }
+}
+
+/// 見えているとみなすのに必要な、モニターと重なる幅・高さ（物理ピクセル）
+const MIN_VISIBLE_PX: i64 = 48;
+
+/// 保存位置の扱い
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    /// 十分見えているのでそのまま
+    Visible,
+    /// 一部しか見えていない・保存時のモニターが移動したので、モニター内に収めた位置
+    Clamped { x: i32, y: i32 },
+    /// 保存時のモニターがないので、代わりのモニターの中央
+    Centered { x: i32, y: i32 },
+}
+
+/// `value` を `start`〜`start + span - len` に収める。`len` が大きすぎるときは `start`
+fn clamp_axis(value: i64, len: u32, start: i32, span: u32) -> i32 {
+    let max = i64::from(start) + i64::from(span) - i64::from(len);
+    value.min(max).max(i64::from(start)) as i32
+}
+
+fn clamp_into(x: i64, y: i64, window: &Rect, monitor: &Rect) -> (i32, i32) {
+    (
+        clamp_axis(x, window.width, monitor.x, monitor.width),
+        clamp_axis(y, window.height, monitor.y, monitor.height),
+    )
+}
+
+/// 保存した矩形を現在のモニター構成と照らし合わせ、どこに置くかを決める。
+///
+/// - どれかのモニターと縦横 48px 以上（ウィンドウがそれより小さければ全体）重なっていればそのまま
+/// - 少しでも重なっていれば、最も重なっているモニター内に収める
+/// - 重なっていなくても保存時のモニター（名前で照合）が残っていれば、そのモニター内の相対位置に移して収める
+/// - どれでもなければ `fallback`（なければ先頭のモニター）の中央に置く
+pub fn place_window(
+    window: Rect,
+    saved_monitor: Option<&MonitorInfo>,
+    monitors: &[MonitorInfo],
+    fallback: Option<&MonitorInfo>,
+) -> Placement {
+    let best = monitors
+        .iter()
+        .map(|m| (m.rect(), window.overlap(&m.rect())))
+        .filter(|(_, (w, h))| *w > 0 && *h > 0)
+        .max_by_key(|(_, (w, h))| w * h);
+    if let Some((monitor, (w, h))) = best {
+        if w >= MIN_VISIBLE_PX.min(i64::from(window.width))
+            && h >= MIN_VISIBLE_PX.min(i64::from(window.height))
+        {
+            return Placement::Visible;
+        }
+        let (x, y) = clamp_into(i64::from(window.x), i64::from(window.y), &window, &monitor);
+        return Placement::Clamped { x, y };
+    }
+
+    let moved = saved_monitor.and_then(|saved| {
+        let name = saved.name.as_ref()?;
+        monitors
+            .iter()
+            .find(|m| m.name.as_ref() == Some(name))
+            .map(|m| (saved, m))
+    });
+    if let Some((saved, monitor)) = moved {
+        let x = i64::from(monitor.x) + i64::from(window.x) - i64::from(saved.x);
+        let y = i64::from(monitor.y) + i64::from(window.y) - i64::from(saved.y);
+        let (x, y) = clamp_into(x, y, &window, &monitor.rect());
+        return Placement::Clamped { x, y };
+    }
+
+    match fallback.or(monitors.first()) {
+        Some(monitor) => {
+            let monitor = monitor.rect();
+            let x = i64::from(monitor.x) + (i64::from(monitor.width) - i64::from(window.width)) / 2;
+            let y =
+                i64::from(monitor.y) + (i64::from(monitor.height) - i64::from(window.height)) / 2;
+            let (x, y) = clamp_into(x, y, &window, &monitor);
+            Placement::Centered { x, y }
+        }
+        // モニターが取れないときは判断できないのでそのまま
+        None => Placement::Visible,
+    }
+}
+
+impl MonitorInfo {
+    pub fn rect(&self) -> Rect {
+        Rect {
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    pub fn from_monitor(monitor: &tauri::Monitor) -> Self {
+        Self {
+            name: monitor.name().cloned(),
+            x: monitor.position().x,
+            y: monitor.position().y,
+            width: monitor.size().width,
+            height: monitor.size().height,
+            scale_factor: monitor.scale_factor(),
+        }
+    }
+}
+
+/// 1 ウィンドウ分の状態。`x`/`y` は物理座標の外枠の位置、`width`/`height` は論理サイズ
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct WindowState {
+    pub x: Option<i32>,
+    pub y: Option<i32>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// main のみ
+    pub display_mode: Option<DisplayMode>,
+    pub monitor: Option<MonitorInfo>,
+}
+
+/// `window_states.json` の内容
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct WindowStateFile {
+    pub version: u32,
+    /// 最後に記録した配置。初めてのモニター構成ではこれを画面内に寄せて使う
+    pub windows: BTreeMap<String, WindowState>,
+    /// モニター構成の指紋（`MonitorLayout::fingerprint`）ごとの配置
+    pub profiles: BTreeMap<String, LayoutProfile>,
+}
+
+impl Default for WindowStateFile {
+    fn default() -> Self {
+        Self {
+            version: WINDOW_STATE_VERSION,
+            windows: BTreeMap::new(),
+            profiles: BTreeMap::new(),
+        }
+    }
+}
+
+impl WindowStateFile {
+    /// 以前の `window_state.json`（main ウィンドウの位置だけ）から移行する
+    pub fn from_legacy(main: WindowState) -> Self {
+        let mut file = Self::default();
+        file.windows.insert("main".to_string(), main);
+        file
+    }
+
+    /// 取得した位置・サイズ・モニターで記録を更新する。表示モードは以前の値を引き継ぐ。
+    /// `layout` があればそのモニター構成の配置も更新する
+    pub fn remember(&mut self, label: &str, captured: WindowState, layout: Option<&MonitorLayout>) {
+        let merge = |entry: &mut WindowState| {
+            *entry = WindowState {
+                display_mode: captured.display_mode.or(entry.display_mode),
+                ..captured.clone()
+            };
+        };
+        merge(self.windows.entry(label.to_string()).or_default());
+        if let Some(layout) = layout {
+            let profile = self.profiles.entry(layout.fingerprint()).or_default();
+            profile.description = layout.description();
+            merge(profile.windows.entry(label.to_string()).or_default());
+        }
+    }
+
+    /// このモニター構成で記録した配置
+    pub fn profile_state(&self, layout: &MonitorLayout, label: &str) -> Option<&WindowState> {
+        self.profiles
+            .get(&layout.fingerprint())
+            .and_then(|profile| profile.windows.get(label))
+    }
+
+    /// 復元に使う配置。このモニター構成の記録がなければ最後に記録した配置
+    pub fn saved_state(&self, layout: Option<&MonitorLayout>, label: &str) -> Option<&WindowState> {
+        layout
+            .and_then(|layout| self.profile_state(layout, label))
+            .or_else(|| self.windows.get(label))
+    }
+}
+
+/// 対応していない新しい形式は読み込まない（上書きもしない）
+pub fn parse_window_state_file(json: &str) -> Result<WindowStateFile, String> {
+    let file: WindowStateFile =
+        serde_json::from_str(json).map_err(|e| format!("Invalid window state: {}", e))?;
+    if file.version > WINDOW_STATE_VERSION {
+        return Err(format!(
+            "Unsupported window state version: {}",
+            file.version
+        ));
+    }
+    Ok(file)
+}
+
+/// 保存済みの状態と、フルスクリーン中の main を記録しないためのフラグ
+#[derive(Default)]
+pub struct WindowStateStore {
+    pub file: WindowStateFile,
+    pub main_fullscreen: bool,
+    /// 配置を記録・復元する対象のモニター構成
+    pub layout: Option<MonitorLayout>,
+}
+
+#[derive(Default)]
+pub struct WindowStatesState(pub Mutex<WindowStateStore>);
+
+fn lock_store(app: &AppHandle) -> std::sync::MutexGuard<'_, WindowStateStore> {
+    app.state::<WindowStatesState>()
+        .inner()
+        .0
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+fn read_window_states() -> Result<WindowStateFile, Box<dyn std::error::Error>> {
+    let path = crate::get_window_state_path();
+    if path.exists() {
+        return Ok(parse_window_state_file(&fs::read_to_string(&path)?)?);
+    }
+    let legacy = crate::get_legacy_window_state_path();
+    if legacy.exists() {
+        let main: WindowState = serde_json::from_str(&fs::read_to_string(&legacy)?)?;
+        println!("DEBUG: Migrating legacy window state: {:?}", main);
+        return Ok(WindowStateFile::from_legacy(main));
+    }
+    println!("DEBUG: No saved window state found");
+    Ok(WindowStateFile::default())
+}
+
+fn current_layout(app: &AppHandle) -> Option<MonitorLayout> {
+    match app.available_monitors() {
+        Ok(monitors) if !monitors.is_empty() => Some(MonitorLayout::new(
+            monitors.iter().map(MonitorInfo::from_monitor).collect(),
+        )),
+        Ok(_) => None,
+        Err(e) => {
+            println!("DEBUG: Failed to list monitors: {}", e);
+            None
+        }
+    }
+}
+
+/// 起動時に保存済みの状態と現在のモニター構成を読み込む
+pub fn load(app: &AppHandle) {
+    let layout = current_layout(app);
+    if let Some(layout) = &layout {
+        println!("DEBUG: Monitor layout: {}", layout.description());
+    }
+    let mut store = lock_store(app);
+    store.layout = layout;
+    match read_window_states() {
+        Ok(file) => store.file = file,
+        Err(e) => println!("DEBUG: Failed to load window state: {}", e),
+    }
+}
+
+/// 現在の記録をファイルへ書き出す
+pub fn flush(app: &AppHandle) {
+    let result = serde_json::to_string_pretty(&lock_store(app).file)
+        .map_err(|e| e.to_string())
+        .and_then(|json| {
+            fs::write(crate::get_window_state_path(), json).map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        println!("DEBUG: Failed to save window state: {}", e);
+    }
+}
+
+fn capture(window: &tauri::WebviewWindow) -> Result<WindowState, tauri::Error> {
+    let position = window.outer_position()?;
+    let size = window
+        .inner_size()?
+        .to_logical::<f64>(window.scale_factor()?);
+    Ok(WindowState {
+        x: Some(position.x),
+        y: Some(position.y),
+        width: Some(size.width.round() as u32),
+        height: Some(size.height.round() as u32),
+        display_mode: None,
+        monitor: window
+            .current_monitor()?
+            .as_ref()
+            .map(MonitorInfo::from_monitor),
+    })
+}
+
+/// ウィンドウの現在の位置・サイズを記録する（ファイルへは書き出さない）。
+/// 隠れているウィンドウと、フルスクリーン中の main は記録しない
+pub fn remember(app: &AppHandle, label: &str) {
+    if !TRACKED_WINDOWS.contains(&label) {
+        return;
+    }
+    let Some(window) = app.get_webview_window(label) else {
+        return;
+    };
+    if !window.is_visible().unwrap_or(false) || window.is_minimized().unwrap_or(false) {
+        return;
+    }
+    let captured = match capture(&window) {
+        Ok(captured) => captured,
+        Err(e) => {
+            println!("DEBUG: Failed to read {} window geometry: {}", label, e);
+            return;
+        }
+    };
+    // モニターの抜き差しで OS が動かした位置は、新しい構成の配置を反映するまで記録しない
+    let layout = current_layout(app);
+    let mut store = lock_store(app);
+    if label == "main" && store.main_fullscreen {
+        return;
+    }
+    if layout.is_some() && layout != store.layout {
+        return;
+    }
+    let WindowStateStore { file, layout, .. } = &mut *store;
+    file.remember(label, captured, layout.as_ref());
+}
+
+/// 記録してすぐ書き出す
+pub fn save(app: &AppHandle, labels: &[&str]) {
+    for label in labels {
+        remember(app, label);
+    }
+    flush(app);
+}
+
+/// 画面外だった場合に中央へ置き直す先。main 以外は main ウィンドウのモニター
+/// （`center_on_main_monitor` と同じ）、main はプライマリモニター
+fn fallback_monitor(window: &tauri::WebviewWindow) -> Option<MonitorInfo> {
+    let main = window
+        .app_handle()
+        .get_webview_window("main")
+        .filter(|_| window.label() != "main")
+        .and_then(|main| main.current_monitor().ok().flatten());
+    main.or_else(|| window.primary_monitor().ok().flatten())
+        .as_ref()
+        .map(MonitorInfo::from_monitor)
+}
+
+/// 保存した位置が今のモニター構成で見えるか確かめ、見えなければ直した位置を返す
+fn visible_position(
+    window: &tauri::WebviewWindow,
+    saved: &WindowState,
+    x: i32,
+    y: i32,
+) -> (i32, i32) {
+    let monitors: Vec<MonitorInfo> = match window.available_monitors() {
+        Ok(monitors) => monitors.iter().map(MonitorInfo::from_monitor).collect(),
+        Err(e) => {
+            println!("DEBUG: Failed to list monitors: {}", e);
+            return (x, y);
+        }
+    };
+    // 論理サイズを保存時のモニターの倍率で物理サイズに戻す
+    let scale = saved
+        .monitor
+        .as_ref()
+        .map(|m| m.scale_factor)
+        .or_else(|| window.scale_factor().ok())
+        .unwrap_or(1.0);
+    let physical = |logical: Option<u32>, current: u32| {
+        logical.map_or(current, |v| (f64::from(v) * scale).round() as u32)
+    };
+    let current = window.outer_size().unwrap_or_default();
+    let rect = Rect {
+        x,
+        y,
+        width: physical(saved.width, current.width),
+        height: physical(saved.height, current.height),
+    };
+    let fallback = fallback_monitor(window);
+    match place_window(rect, saved.monitor.as_ref(), &monitors, fallback.as_ref()) {
+        Placement::Visible => (x, y),
+        placement @ (Placement::Clamped { x, y } | Placement::Centered { x, y }) => {
+            println!(
+                "DEBUG: {} window was off-screen at ({}, {}): {:?}",
+                window.label(),
+                rect.x,
+                rect.y,
+                placement
+            );
+            (x, y)
+        }
+    }
+}
+
+/// 保存済みの位置へ動かす。画面外なら見える位置に直す
+fn move_to_saved(window: &tauri::WebviewWindow, saved: &WindowState) {
+    if let (Some(x), Some(y)) = (saved.x, saved.y) {
+        let (x, y) = visible_position(window, saved, x, y);
+        if let Err(e) = window.set_position(tauri::PhysicalPosition { x, y }) {
+            println!(
+                "DEBUG: Failed to restore {} position: {}",
+                window.label(),
+                e
+            );
+        }
+    }
+}
+
+/// 作成したウィンドウに、今のモニター構成で保存済みの位置・サイズを反映する。画面外なら見える位置に直す
+pub fn restore(window: &tauri::WebviewWindow) {
+    let saved = {
+        let store = lock_store(window.app_handle());
+        store
+            .file
+            .saved_state(store.layout.as_ref(), window.label())
+            .cloned()
+    };
+    let Some(saved) = saved else {
+        return;
+    };
+    if let (Some(width), Some(height)) = (saved.width, saved.height) {
+        if let Err(e) = window.set_size(tauri::LogicalSize { width, height }) {
+            println!("DEBUG: Failed to restore {} size: {}", window.label(), e);
+        }
+    }
+    move_to_saved(window, &saved);
+    println!(
+        "DEBUG: {} window state restored: {:?}",
+        window.label(),
+        saved
+    );
+}
+
+/// モニター構成が変わったとき、開いているウィンドウにその構成の配置を反映する。
+/// 初めての構成なら OS が動かした位置のまま、画面外のものだけ寄せて新しい配置として記録する。
+/// サイズは表示モードやフォントで決まるので位置だけ動かす
+fn apply_layout(app: &AppHandle, layout: MonitorLayout) {
+    println!("DEBUG: Monitor layout changed: {}", layout.description());
+    let profile: BTreeMap<String, WindowState> = {
+        let mut store = lock_store(app);
+        let profile = store
+            .file
+            .profiles
+            .get(&layout.fingerprint())
+            .map(|profile| profile.windows.clone())
+            .unwrap_or_default();
+        store.layout = Some(layout);
+        profile
+    };
+    for label in TRACKED_WINDOWS {
+        let Some(window) = app.get_webview_window(label) else {
+            continue;
+        };
+        let saved = match profile.get(label) {
+            Some(saved) => saved.clone(),
+            None => match capture(&window) {
+                Ok(current) => current,
+                Err(_) => continue,
+            },
+        };
+        move_to_saved(&window, &saved);
+    }
+    crate::sync_layer_to_ctrl(app);
+    save(app, &TRACKED_WINDOWS);
+}
+
+/// モニターの抜き差し・配置変更を検出して配置を切り替えるスレッドを起動する
+pub fn spawn_monitor_watch(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(MONITOR_POLL);
+        let Some(layout) = current_layout(&app) else {
+            continue;
+        };
+        let changed = lock_store(&app).layout.as_ref() != Some(&layout);
+        if changed {
+            apply_layout(&app, layout);
+        }
+    });
+}
+
+/// main ウィンドウの表示モードを記録する。フルスクリーン中は位置・サイズを記録しない
+#[tauri::command]
+pub async fn window_set_display_mode(
+    app: AppHandle,
+    state: State<'_, WindowStatesState>,
+    display_mode: DisplayMode,
+    fullscreen: bool,
+) -> Result<(), String> {
+    {
+        let mut store = state
+            .0
+            .lock()
+            .map_err(|e| format!("Window state lock poisoned: {}", e))?;
+        store.main_fullscreen = fullscreen;
+        store
+            .file
+            .windows
+            .entry("main".to_string())
+            .or_default()
+            .display_mode = Some(display_mode);
+    }
+    save(&app, &["main"]);
+    Ok(())
+}
+
+/// 前回終了時の main ウィンドウの表示モード
+#[tauri::command]
+pub async fn window_saved_display_mode(
+    state: State<'_, WindowStatesState>,
+) -> Result<Option<DisplayMode>, String> {
+    let store = state
+        .0
+        .lock()
+        .map_err(|e| format!("Window state lock poisoned: {}", e))?;
+    Ok(store
+        .file
+        .windows
+        .get("main")
+        .and_then(|main| main.display_mode))
+}
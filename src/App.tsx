@@ -7,7 +7,7 @@ import TimerDisplay from "./components/TimerDisplay";
 import TimerControls from "./components/TimerControls";
 import Help from "./components/Help";
 import AboutInfo from "./components/AboutInfo";
-import { TimerState, Settings as SettingsType } from "./types";
+import { TimerState, TimerSnapshot, TimerRestoreReport, WallClockAlarm, LaunchOptions, Settings as SettingsType, DEFAULT_TIMER_ID, DEFAULT_TIME_UP_SCREEN, normalizeTimeUpScreen, clampAlertDelay, clampSnoozeMinutes } from "./types";
 import { useTranslation } from "./i18n/useTranslation";
 import { initLanguage } from "./i18n";
 import type { LanguageSetting } from "./i18n";
@@ -28,6 +28,7 @@ const App: React.FC = () => {
   }, []);
 
   const [timerState, setTimerState] = useState<TimerState>({
+    hours: 0,
     minutes: 0,
     seconds: 0,
     isRunning: false,
@@ -40,8 +41,16 @@ const App: React.FC = () => {
     darkMode: false,
     alarmSound: "alarm.mp3",
     alarmVolume: 0.8,
-    displayMode: "normal", // 常に通常モードで起動
+    displayMode: "normal", // 前回の表示モードはウィンドウ状態から復元する
     showTimeUpWindow: true, // デフォルトでTime Up画面を表示
+    timeUpScreen: DEFAULT_TIME_UP_SCREEN,
+    alertLayerFlash: true,
+    alertNotification: false,
+    alertNotificationDelaySecs: 30,
+    alertTimeUpDelaySecs: 0,
+    snoozeMinutes: 5,
+    overtime: false,
+    missedAlarmPolicy: "fireLate",
     layerTextColor: "#00ff66",
     layerShadowStyle: "dark",
     layerFontSize: 6,
@@ -51,6 +60,12 @@ const App: React.FC = () => {
   // TimeUP表示の状態管理
   const [showTimeUp, setShowTimeUp] = useState(false);
 
+  // 終了した名前付きタイマー（既定タイマーなら null）
+  const [firedTimerName, setFiredTimerName] = useState<string | null>(null);
+
+  // 既定タイマーが 0 到達後の超過時間を計測中か
+  const [isOvertime, setIsOvertime] = useState(false);
+
   // フルスクリーン状態管理
   const [isFullscreen, setIsFullscreen] = useState(false);
 
@@ -104,6 +119,14 @@ const App: React.FC = () => {
           alarmVolume: savedSettings.alarmVolume ?? 0.8,
           displayMode: (savedSettings.displayMode === "compact" || savedSettings.displayMode === "minimal" ? savedSettings.displayMode : (savedSettings.compactMode ? "compact" : "normal")),
           showTimeUpWindow: savedSettings.showTimeUpWindow ?? true,
+          timeUpScreen: normalizeTimeUpScreen(savedSettings.timeUpScreen),
+          alertLayerFlash: savedSettings.alertLayerFlash ?? true,
+          alertNotification: savedSettings.alertNotification ?? false,
+          alertNotificationDelaySecs: clampAlertDelay(savedSettings.alertNotificationDelaySecs, 30),
+          alertTimeUpDelaySecs: clampAlertDelay(savedSettings.alertTimeUpDelaySecs, 0),
+          snoozeMinutes: clampSnoozeMinutes(savedSettings.snoozeMinutes),
+          overtime: savedSettings.overtime ?? false,
+          missedAlarmPolicy: savedSettings.missedAlarmPolicy === "drop" ? "drop" : "fireLate",
           layerTextColor: savedSettings.layerTextColor ?? "#00ff66",
           layerShadowStyle: savedSettings.layerShadowStyle ?? "dark",
           layerFontSize: typeof savedSettings.layerFontSize === "number" && savedSettings.layerFontSize > 0
@@ -113,7 +136,8 @@ const App: React.FC = () => {
             ? savedSettings.language
             : "auto",
         };
-        setSettings(convertedSettings);
+        // 表示モードはウィンドウ状態から復元するので、読み込み前後の値を保つ
+        setSettings((prev) => ({ ...convertedSettings, displayMode: prev.displayMode }));
       }
     } catch (error) {
       console.error("Failed to load settings:", error);
@@ -149,7 +173,6 @@ const App: React.FC = () => {
     minutes: number;
     seconds: number;
   } | null>(null);
-  const timeUpWindowShownRef = useRef(false);
 
   const loadTimerState = useCallback(async () => {
     if (!isTauri()) return;
@@ -157,33 +180,44 @@ const App: React.FC = () => {
     try {
       const settings = await Store.load("settings.json");
       const savedTimerData = await settings.get<{
-        minutes: number;
-        seconds: number;
         lastSetTime: { minutes: number; seconds: number } | null;
       }>("timer");
-
       if (savedTimerData) {
-        setTimerState((prev) => ({
-          ...prev,
-          minutes: savedTimerData.minutes,
-          seconds: savedTimerData.seconds,
-          timeRemaining: savedTimerData.minutes * 60 + savedTimerData.seconds,
-        }));
         setLastSetTime(savedTimerData.lastSetTime);
       }
+
+      // 残り時間・実行状態はバックエンドが timer_state.json から復元済み
+      const snapshot = await invoke<TimerSnapshot>("timer_get_state");
+      setTimerState({
+        hours: snapshot.hours,
+        minutes: snapshot.minutes,
+        seconds: snapshot.seconds,
+        isRunning: snapshot.status === "running",
+        isPaused: snapshot.status === "paused",
+        timeRemaining: Math.ceil(snapshot.remainingMs / 1000),
+      });
+      setIsOvertime(snapshot.status === "overtime");
+
+      // アプリ終了中に時間切れになっていた場合は TIME UP 表示にする
+      const reports = await invoke<TimerRestoreReport[]>("timer_restore_report");
+      const expired = reports.find((report) => report.expiredWhileClosed);
+      if (expired) {
+        setFiredTimerName(expired.id === DEFAULT_TIMER_ID ? null : expired.name);
+        setShowTimeUp(true);
+      }
     } catch (error) {
       console.error("Failed to load timer state:", error);
     }
   }, []);
 
 
-  // タイマー状態の保存・読み込み
+  // バックエンドのタイマーエンジンから配信される tick / finished を反映
   useEffect(() => {
-    let interval: number | null = null;
+    if (!isTauri()) return;
 
     // playAlarm関数をuseEffect内で定義（最新のsettingsを参照）
-    const playAlarmInEffect = async () => {
-      const audio = new Audio(`/sounds/${settings.alarmSound}`);
+    const playAlarmInEffect = async (sound: string) => {
+      const audio = new Audio(`/sounds/${sound}`);
       audio.volume = settings.alarmVolume;
       alarmAudioRef.current = audio; // 参照を保持
 
@@ -208,85 +242,86 @@ const App: React.FC = () => {
       }
     };
 
-    if (timerState.isRunning && timerState.timeRemaining > 0) {
-      interval = setInterval(() => {
-        setTimerState((prev) => {
-          const newTimeRemaining = prev.timeRemaining - 1;
-          if (newTimeRemaining <= 0) {
-            // タイマー終了
-            // TimeUP表示を有効化
-            setShowTimeUp(true);
-
-            // 音声再生を開始（重複再生を防ぐ）
-            if (!alarmPlayedRef.current) {
-              alarmPlayedRef.current = true;
-              playAlarmInEffect().catch((error) => {
-                console.error("Failed to start alarm sound:", error);
-              });
-            }
+    const unlistenTick = listen<TimerSnapshot>("timer-tick", (event) => {
+      // main ウィンドウには既定タイマーのみ表示する
+      if (event.payload.id !== DEFAULT_TIMER_ID) return;
+      const { status, hours, minutes, seconds, remainingMs } = event.payload;
+      setTimerState({
+        hours,
+        minutes,
+        seconds,
+        isRunning: status === "running",
+        isPaused: status === "paused",
+        timeRemaining: Math.ceil(remainingMs / 1000),
+      });
+      setIsOvertime(status === "overtime");
+      // トレイなど main ウィンドウ以外から開始・リセットされた場合も TimeUP 表示を片付ける
+      if (status === "running" || status === "idle") {
+        setShowTimeUp(false);
+        stopAlarm();
+      }
+    });
 
-            // 音声再生開始後、設定に応じてウィンドウを表示（重複実行を防ぐ）
-            if (!timeUpWindowShownRef.current && settings.showTimeUpWindow) {
-              timeUpWindowShownRef.current = true;
-              setTimeout(() => {
-                if (isTauri()) {
-                  invoke("show_timeup_window").catch((error) => {
-                    console.error("Failed to show Time Up window:", error);
-                  });
-                }
-              }, 100); // 100ms遅延で音声再生を優先
-            }
+    const unlistenFinished = listen<TimerSnapshot>("timer-finished", (event) => {
+      const { id, name, alarm } = event.payload;
+      const isDefaultTimer = id === DEFAULT_TIMER_ID;
 
-            // 最後に設定した時間があれば復元
-            if (lastSetTime) {
-              const restoredTotalSeconds =
-                lastSetTime.minutes * 60 + lastSetTime.seconds;
-              return {
-                ...prev,
-                isRunning: false,
-                timeRemaining: restoredTotalSeconds,
-                minutes: lastSetTime.minutes,
-                seconds: lastSetTime.seconds,
-              };
-            } else {
-              return {
-                ...prev,
-                isRunning: false,
-                timeRemaining: 0,
-                minutes: 0,
-                seconds: 0,
-              };
-            }
-          }
-          return {
-            ...prev,
-            timeRemaining: newTimeRemaining,
-            minutes: Math.floor(newTimeRemaining / 60),
-            seconds: newTimeRemaining % 60,
-          };
+      // TimeUP表示を有効化（既定以外のタイマーは名前も表示する）
+      setFiredTimerName(isDefaultTimer ? null : name);
+      setShowTimeUp(true);
+
+      // 音声再生を開始（重複再生を防ぐ）
+      if (!alarmPlayedRef.current) {
+        alarmPlayedRef.current = true;
+        playAlarmInEffect(alarm.sound ?? settings.alarmSound).catch((error) => {
+          console.error("Failed to start alarm sound:", error);
         });
-      }, 1000);
-    }
+      }
+
+      // 通知と Time Up ウィンドウはバックエンドが段階的に表示する（escalation_set_policy）
+    });
+
+    // 時刻指定アラーム（Time Up ウィンドウはバックエンドが表示する）
+    const unlistenAlarm = listen<WallClockAlarm>("alarm-fired", (event) => {
+      const { alarm } = event.payload;
+      if (!alarmPlayedRef.current) {
+        alarmPlayedRef.current = true;
+        playAlarmInEffect(alarm.sound ?? settings.alarmSound).catch((error) => {
+          console.error("Failed to start alarm sound:", error);
+        });
+      }
+    });
 
     return () => {
-      if (interval) clearInterval(interval);
+      unlistenTick.then((unlisten) => unlisten()).catch(() => {});
+      unlistenFinished.then((unlisten) => unlisten()).catch(() => {});
+      unlistenAlarm.then((unlisten) => unlisten()).catch(() => {});
     };
-  }, [timerState.isRunning, timerState.timeRemaining, lastSetTime, settings.alarmSound, settings.alarmVolume, settings.showTimeUpWindow, stopAlarm]);
+  }, [settings.alarmSound, settings.alarmVolume, stopAlarm]);
 
-  const updateTimer = useCallback((minutes: number, seconds: number) => {
+  const updateTimer = useCallback((minutes: number, seconds: number, hours = 0) => {
     // アラーム音を停止
     stopAlarm();
 
-    const totalSeconds = minutes * 60 + seconds;
+    // 桁あふれ（0:90 など）はバックエンドが正規化して timer-tick で返す
+    const totalSeconds = hours * 3600 + minutes * 60 + seconds;
     setTimerState((prev) => {
       const newState = {
         ...prev,
+        hours,
         minutes,
         seconds,
         timeRemaining: totalSeconds,
       };
       return newState;
     });
+
+    // カウントダウン本体はバックエンドのタイマーエンジンが管理する
+    if (isTauri()) {
+      invoke("timer_set", { hours, minutes, seconds }).catch((error) => {
+        console.error("Failed to set timer:", error);
+      });
+    }
   }, [stopAlarm]);
 
   const updateTimerBoth = useCallback(
@@ -302,6 +337,7 @@ const App: React.FC = () => {
       setTimerState((prev) => {
         const newState = {
           ...prev,
+          hours: 0,
           minutes,
           seconds,
           timeRemaining: totalSeconds,
@@ -309,6 +345,12 @@ const App: React.FC = () => {
         return newState;
       });
 
+      if (isTauri()) {
+        invoke("timer_set", { minutes, seconds }).catch((error) => {
+          console.error("Failed to set timer:", error);
+        });
+      }
+
       // タイマー状態を即座に保存（新しい値を使用）
       if (isTauri()) {
         try {
@@ -348,6 +390,12 @@ const App: React.FC = () => {
       isRunning: true,
       isPaused: false,
     }));
+
+    if (isTauri()) {
+      invoke("timer_start").catch((error) => {
+        console.error("Failed to start timer:", error);
+      });
+    }
   }, [timerState.minutes, timerState.seconds, timerState.isPaused, stopAlarm]);
 
   const pauseTimer = useCallback(() => {
@@ -356,6 +404,12 @@ const App: React.FC = () => {
       isRunning: false,
       isPaused: true,
     }));
+
+    if (isTauri()) {
+      invoke("timer_pause").catch((error) => {
+        console.error("Failed to pause timer:", error);
+      });
+    }
   }, []);
 
   const resetTimer = useCallback(() => {
@@ -372,10 +426,17 @@ const App: React.FC = () => {
       ...prev,
       isRunning: false,
       isPaused: false,
+      hours: 0,
       minutes: 0,
       seconds: 0,
       timeRemaining: 0,
     }));
+
+    if (isTauri()) {
+      invoke("timer_reset").catch((error) => {
+        console.error("Failed to reset timer:", error);
+      });
+    }
   }, [stopAlarm]);
 
   // F12キーでデベロッパーツールを開く
@@ -400,6 +461,54 @@ const App: React.FC = () => {
     };
   }, []);
 
+  // 超過計測の設定を既定タイマーのアラームへ反映
+  useEffect(() => {
+    if (!isTauri()) return;
+    invoke("timer_set_overtime", { enabled: settings.overtime }).catch((error) => {
+      console.error("Failed to update overtime setting:", error);
+    });
+  }, [settings.overtime]);
+
+  // Time Up ウィンドウを出すモニターをバックエンドへ反映
+  useEffect(() => {
+    if (!isTauri()) return;
+    invoke("timeup_set_screen", { screen: settings.timeUpScreen }).catch((error) => {
+      console.error("Failed to update Time Up screen:", error);
+    });
+  }, [settings.timeUpScreen]);
+
+  // 時間切れの知らせ方（点滅 → 通知 → Time Up ウィンドウ）とスヌーズの長さをバックエンドへ反映
+  useEffect(() => {
+    if (!isTauri()) return;
+    invoke("escalation_set_policy", {
+      policy: {
+        layerFlash: settings.alertLayerFlash,
+        notification: settings.alertNotification,
+        notificationDelaySecs: settings.alertNotificationDelaySecs,
+        timeupWindow: settings.showTimeUpWindow,
+        timeupWindowDelaySecs: settings.alertTimeUpDelaySecs,
+        snoozeSecs: settings.snoozeMinutes * 60,
+      },
+    }).catch((error) => {
+      console.error("Failed to update alert escalation:", error);
+    });
+  }, [
+    settings.alertLayerFlash,
+    settings.alertNotification,
+    settings.alertNotificationDelaySecs,
+    settings.showTimeUpWindow,
+    settings.alertTimeUpDelaySecs,
+    settings.snoozeMinutes,
+  ]);
+
+  // スリープ中に過ぎたタイマー・アラームの扱いをバックエンドへ反映
+  useEffect(() => {
+    if (!isTauri()) return;
+    invoke("resync_set_policy", { policy: settings.missedAlarmPolicy }).catch((error) => {
+      console.error("Failed to update missed alarm policy:", error);
+    });
+  }, [settings.missedAlarmPolicy]);
+
   // アプリ起動時に設定とタイマー状態を読み込み
   useEffect(() => {
     const initializeApp = async () => {
@@ -450,14 +559,17 @@ const App: React.FC = () => {
     [setI18nLanguage]
   );
 
+  // --always-on-top で起動した場合はこのセッションだけ最前面にする（設定には保存しない）
+  const [alwaysOnTopOverride, setAlwaysOnTopOverride] = useState(false);
+
   // alwaysOnTop はストア起動読み込み時にも外部変更時にも自動で OS 側へ反映
   useEffect(() => {
     if (!isTauri()) return;
     const currentWindow = getCurrentWindow();
-    currentWindow.setAlwaysOnTop(settings.alwaysOnTop).catch((error) => {
+    currentWindow.setAlwaysOnTop(settings.alwaysOnTop || alwaysOnTopOverride).catch((error) => {
       console.error("Failed to apply alwaysOnTop:", error);
     });
-  }, [settings.alwaysOnTop]);
+  }, [settings.alwaysOnTop, alwaysOnTopOverride]);
 
   const handlePowerButtonClick = async () => {
     if (isTauri()) {
@@ -512,7 +624,7 @@ const App: React.FC = () => {
 
       const newSettings = { ...settings, displayMode: nextMode };
       setSettings(newSettings);
-      // 表示モードの状態は保存しない
+      // 表示モードは設定ではなくウィンドウ状態（window_states.json）に保存する
 
       if (!isTauri()) return;
 
@@ -549,6 +661,7 @@ const App: React.FC = () => {
         const size = new LogicalSize(width, height);
         await currentWindow.setMinSize(size);
         await currentWindow.setMaxSize(size);
+        await invoke("window_set_display_mode", { displayMode: nextMode, fullscreen: false });
       } catch (error) {
         console.error("Failed to transition display mode:", error);
       }
@@ -556,6 +669,22 @@ const App: React.FC = () => {
     [settings, isFullscreen]
   );
 
+  // 前回終了時の表示モードを復元（位置・サイズはバックエンドが復元済み）
+  const [savedDisplayMode, setSavedDisplayMode] = useState<SettingsType["displayMode"] | null>(null);
+  useEffect(() => {
+    if (!isTauri()) return;
+    invoke<SettingsType["displayMode"] | null>("window_saved_display_mode")
+      .then((mode) => setSavedDisplayMode(mode))
+      .catch((error) => {
+        console.error("Failed to load saved display mode:", error);
+      });
+  }, []);
+  useEffect(() => {
+    if (!savedDisplayMode) return;
+    setSavedDisplayMode(null);
+    transitionToMode(savedDisplayMode);
+  }, [savedDisplayMode, transitionToMode]);
+
   const handleCompactModeToggle = useCallback(async () => {
     // 通常 → 簡易 → ミニマム → 通常の順で循環
     const nextMode: "normal" | "compact" | "minimal" =
@@ -598,6 +727,8 @@ const App: React.FC = () => {
         // フルスクリーン前のウィンドウ位置を保存
         const position = await currentWindow.outerPosition();
         savedWindowGeometryRef.current = { x: position.x, y: position.y };
+        // フルスクリーン中の位置・サイズはウィンドウ状態に記録しない
+        await invoke("window_set_display_mode", { displayMode: settings.displayMode, fullscreen: true });
 
         // 現在のモニター情報を取得
         const monitor = await currentMonitor();
@@ -649,6 +780,7 @@ const App: React.FC = () => {
         const size = new LogicalSize(width, height);
         await currentWindow.setMinSize(size);
         await currentWindow.setMaxSize(size);
+        await invoke("window_set_display_mode", { displayMode: settings.displayMode, fullscreen: false });
       }
 
       setIsFullscreen(newFullscreen);
@@ -766,7 +898,6 @@ const App: React.FC = () => {
   useEffect(() => {
     const handleMessage = (event: MessageEvent) => {
       if (event.data?.action === 'closeTimeUpWindow') {
-        timeUpWindowShownRef.current = false; // フラグをリセット
         // アラーム音を停止
         stopAlarm();
         if (isTauri()) {
@@ -781,7 +912,6 @@ const App: React.FC = () => {
     // localStorage監視
     const handleStorageChange = (event: StorageEvent) => {
       if (event.key === 'closeTimeUpWindow') {
-        timeUpWindowShownRef.current = false; // フラグをリセット
         // アラーム音を停止
         stopAlarm();
         if (isTauri()) {
@@ -808,17 +938,23 @@ const App: React.FC = () => {
     if (!layerEnabled) return;
 
     invoke("update_layer_timer", {
+      hours: timerState.hours,
       minutes: timerState.minutes,
       seconds: timerState.seconds,
       showTimeUp,
+      overtime: isOvertime,
+      label: showTimeUp ? firedTimerName : null,
     }).catch((error) => {
       console.error("Failed to update layer timer:", error);
     });
   }, [
     layerEnabled,
+    timerState.hours,
     timerState.minutes,
     timerState.seconds,
     showTimeUp,
+    isOvertime,
+    firedTimerName,
   ]);
 
   // レイヤー表示中、文字色・影スタイルの変更を反映
@@ -844,6 +980,51 @@ const App: React.FC = () => {
       invoke("hide_layer_window").catch(() => {});
       setLayerEnabled(false);
     });
+    // グローバルショートカットで Time Up を閉じた場合（ウィンドウはバックエンドが非表示済み）
+    const unlistenDismiss = listen("timeup-dismissed", () => {
+      setShowTimeUp(false);
+      stopAlarm();
+    });
+    // トレイからレイヤー表示を有効にした場合（ウィンドウはバックエンドが表示済み）
+    const unlistenEnter = listen("layer-enter-requested", () => {
+      setLayerEnabled(true);
+    });
+
+    return () => {
+      unlistenPromise.then((unlisten) => unlisten()).catch(() => {});
+      unlistenEnter.then((unlisten) => unlisten()).catch(() => {});
+      unlistenDismiss.then((unlisten) => unlisten()).catch(() => {});
+    };
+  }, [stopAlarm]);
+
+  // コマンドラインの --layer / --always-on-top を反映（起動時は取得、2 つ目の起動からはイベント）
+  useEffect(() => {
+    if (!isTauri()) return;
+
+    const applyLaunchOptions = async (options: LaunchOptions) => {
+      if (options.alwaysOnTop) {
+        setAlwaysOnTopOverride(true);
+      }
+      if (options.layer) {
+        try {
+          await invoke("show_layer_window");
+          setLayerEnabled(true);
+        } catch (error) {
+          console.error("Failed to show layer from launch options:", error);
+        }
+      }
+    };
+
+    invoke<LaunchOptions | null>("launch_take_options")
+      .then((options) => {
+        if (options) applyLaunchOptions(options);
+      })
+      .catch((error) => {
+        console.error("Failed to load launch options:", error);
+      });
+    const unlistenPromise = listen<LaunchOptions>("launch-options", (event) => {
+      applyLaunchOptions(event.payload);
+    });
 
     return () => {
       unlistenPromise.then((unlisten) => unlisten()).catch(() => {});
@@ -876,9 +1057,12 @@ const App: React.FC = () => {
         console.error("Failed to update layer style on ready:", error);
       });
       invoke("update_layer_timer", {
+        hours: timerState.hours,
         minutes: timerState.minutes,
         seconds: timerState.seconds,
         showTimeUp,
+        overtime: isOvertime,
+        label: showTimeUp ? firedTimerName : null,
       }).catch((error) => {
         console.error("Failed to update layer timer on ready:", error);
       });
@@ -888,10 +1072,13 @@ const App: React.FC = () => {
       unlistenPromise.then((unlisten) => unlisten()).catch(() => {});
     };
   }, [
+    timerState.hours,
     timerState.minutes,
     timerState.seconds,
     timerState.isRunning,
     showTimeUp,
+    isOvertime,
+    firedTimerName,
     settings.layerTextColor,
     settings.layerShadowStyle,
     settings.layerFontSize,
@@ -989,10 +1176,12 @@ const App: React.FC = () => {
 
       <div className="timer-container">
         <TimerDisplay
+          hours={timerState.hours}
           minutes={timerState.minutes}
           seconds={timerState.seconds}
           isRunning={timerState.isRunning}
           showTimeUp={showTimeUp}
+          overtime={isOvertime}
         />
 
         {settings.displayMode === "normal" && (
@@ -1006,10 +1195,10 @@ const App: React.FC = () => {
             onSettings={openSettings}
             onHelp={() => setShowHelp(true)}
             onMinutesChange={(minutes) =>
-              updateTimer(minutes, timerState.seconds)
+              updateTimer(minutes, timerState.seconds, timerState.hours)
             }
             onSecondsChange={(seconds) =>
-              updateTimer(timerState.minutes, seconds)
+              updateTimer(timerState.minutes, seconds, timerState.hours)
             }
             onBothChange={updateTimerBoth}
             alarmVolume={settings.alarmVolume}
@@ -2,11 +2,33 @@ import React, { useCallback, useEffect, useRef, useState } from "react";
 import { invoke, isTauri } from "@tauri-apps/api/core";
 import { emit, listen } from "@tauri-apps/api/event";
 import { Store } from "@tauri-apps/plugin-store";
-import { Settings as SettingsType } from "./types";
+import {
+  LocalServerConfig,
+  LocalServerStatus,
+  Settings as SettingsType,
+  ShortcutAction,
+  ShortcutConfig,
+  ShortcutConflict,
+  TimeUpScreen,
+  DEFAULT_TIME_UP_SCREEN,
+  normalizeTimeUpScreen,
+  clampAlertDelay,
+  clampSnoozeMinutes,
+  MAX_ALERT_DELAY_SECS,
+  MAX_SNOOZE_MINUTES,
+} from "./types";
 import { useTranslation } from "./i18n/useTranslation";
 import { initLanguage } from "./i18n";
 import type { LanguageSetting } from "./i18n";
 
+const SHORTCUT_ACTIONS: ShortcutAction[] = [
+  "startPause",
+  "reset",
+  "addMinute",
+  "toggleLayer",
+  "dismissTimeup",
+];
+
 const DEFAULT_SETTINGS: SettingsType = {
   alwaysOnTop: false,
   darkMode: false,
@@ -14,6 +36,14 @@ const DEFAULT_SETTINGS: SettingsType = {
   alarmVolume: 0.8,
   displayMode: "normal",
   showTimeUpWindow: true,
+  timeUpScreen: DEFAULT_TIME_UP_SCREEN,
+  alertLayerFlash: true,
+  alertNotification: false,
+  alertNotificationDelaySecs: 30,
+  alertTimeUpDelaySecs: 0,
+  snoozeMinutes: 5,
+  overtime: false,
+  missedAlarmPolicy: "fireLate",
   layerTextColor: "#00ff66",
   layerShadowStyle: "dark",
   layerFontSize: 6,
@@ -59,6 +89,17 @@ function normalizeSettings(
           ? "compact"
           : "normal",
     showTimeUpWindow: saved.showTimeUpWindow ?? DEFAULT_SETTINGS.showTimeUpWindow,
+    timeUpScreen: normalizeTimeUpScreen(saved.timeUpScreen),
+    alertLayerFlash: saved.alertLayerFlash ?? DEFAULT_SETTINGS.alertLayerFlash,
+    alertNotification: saved.alertNotification ?? DEFAULT_SETTINGS.alertNotification,
+    alertNotificationDelaySecs: clampAlertDelay(
+      saved.alertNotificationDelaySecs,
+      DEFAULT_SETTINGS.alertNotificationDelaySecs
+    ),
+    alertTimeUpDelaySecs: clampAlertDelay(saved.alertTimeUpDelaySecs, DEFAULT_SETTINGS.alertTimeUpDelaySecs),
+    snoozeMinutes: clampSnoozeMinutes(saved.snoozeMinutes),
+    overtime: saved.overtime ?? DEFAULT_SETTINGS.overtime,
+    missedAlarmPolicy: saved.missedAlarmPolicy === "drop" ? "drop" : DEFAULT_SETTINGS.missedAlarmPolicy,
     layerTextColor: saved.layerTextColor ?? DEFAULT_SETTINGS.layerTextColor,
     layerShadowStyle: saved.layerShadowStyle ?? DEFAULT_SETTINGS.layerShadowStyle,
     layerFontSize:
@@ -72,12 +113,151 @@ function normalizeSettings(
   };
 }
 
+type LocalServer = ReturnType<typeof useLocalServer>;
+
+// ローカル HTTP API・WebSocket 配信の設定と状態
+function useLocalServer(getCommand: string, setCommand: string) {
+  const [status, setStatus] = useState<LocalServerStatus | null>(null);
+  const [port, setPort] = useState("");
+
+  useEffect(() => {
+    if (!isTauri()) return;
+    invoke<LocalServerStatus>(getCommand)
+      .then((loaded) => {
+        setStatus(loaded);
+        setPort(String(loaded.config.port));
+      })
+      .catch((err) => console.error(`Failed to run ${getCommand}:`, err));
+  }, [getCommand]);
+
+  const save = useCallback(
+    async (config: LocalServerConfig) => {
+      if (!isTauri()) return;
+      try {
+        const saved = await invoke<LocalServerStatus>(setCommand, { config });
+        setStatus(saved);
+        setPort(String(saved.config.port));
+      } catch (err) {
+        console.error(`Failed to run ${setCommand}:`, err);
+      }
+    },
+    [setCommand]
+  );
+
+  // 範囲外の入力は保存済みの値に戻す
+  const commitPort = () => {
+    if (!status) return;
+    const value = parseInt(port, 10);
+    if (!Number.isInteger(value) || value < 1 || value > 65535) {
+      setPort(String(status.config.port));
+      return;
+    }
+    if (value !== status.config.port) void save({ ...status.config, port: value });
+  };
+
+  return { status, port, setPort, save, commitPort };
+}
+
+const LocalServerRows: React.FC<{
+  server: LocalServer;
+  label: string;
+  hint: string;
+  scheme: "http" | "ws";
+}> = ({ server, label, hint, scheme }) => {
+  const { t } = useTranslation();
+  const { status } = server;
+  if (!status) return null;
+  return (
+    <>
+      <div className="settings-row">
+        <label className="settings-toggle">
+          <input
+            type="checkbox"
+            checked={status.config.enabled}
+            onChange={(e) => void server.save({ ...status.config, enabled: e.target.checked })}
+          />
+          <span>{label}</span>
+        </label>
+        <p className="settings-row-hint">{hint}</p>
+      </div>
+      <div className="settings-row settings-row-inline">
+        <span className="settings-row-label">{t("settings.localServer.port")}</span>
+        <input
+          type="number"
+          className="settings-port-input"
+          min={1}
+          max={65535}
+          value={server.port}
+          onChange={(e) => server.setPort(e.target.value)}
+          onBlur={server.commitPort}
+          onKeyDown={(e) => {
+            if (e.key === "Enter") server.commitPort();
+          }}
+        />
+      </div>
+      {status.port !== null && (
+        <p className="settings-row-hint">
+          {t("settings.localServer.listening")} {scheme}://127.0.0.1:{status.port}
+        </p>
+      )}
+      {status.error && <p className="settings-row-error">{status.error}</p>}
+    </>
+  );
+};
+
 const SettingsApp: React.FC = () => {
   const { t, setLanguage: setI18nLanguage } = useTranslation();
   const [settings, setSettings] = useState<SettingsType>(DEFAULT_SETTINGS);
   const [loaded, setLoaded] = useState(false);
   const [isPlaying, setIsPlaying] = useState(false);
   const audioRef = useRef<HTMLAudioElement | null>(null);
+  const [shortcuts, setShortcuts] = useState<ShortcutConfig["bindings"] | null>(null);
+  const [shortcutConflicts, setShortcutConflicts] = useState<ShortcutConflict[]>([]);
+
+  // グローバルショートカットはバックエンドが保存・登録する
+  const loadShortcuts = useCallback(async () => {
+    if (!isTauri()) return;
+    try {
+      const config = await invoke<ShortcutConfig>("shortcut_get_config");
+      setShortcuts(config.bindings);
+      setShortcutConflicts(await invoke<ShortcutConflict[]>("shortcut_conflicts"));
+    } catch (err) {
+      console.error("Failed to load shortcuts:", err);
+    }
+  }, []);
+
+  useEffect(() => {
+    void loadShortcuts();
+  }, [loadShortcuts]);
+
+  const saveShortcuts = useCallback(
+    async (bindings: Partial<ShortcutConfig["bindings"]>) => {
+      if (!isTauri()) return;
+      try {
+        const conflicts = await invoke<ShortcutConflict[]>("shortcut_set_config", {
+          config: { bindings },
+        });
+        setShortcutConflicts(conflicts);
+        await loadShortcuts();
+      } catch (err) {
+        console.error("Failed to save shortcuts:", err);
+      }
+    },
+    [loadShortcuts]
+  );
+
+  // Time Up ウィンドウの表示先に選べるモニター名
+  const [monitorNames, setMonitorNames] = useState<string[]>([]);
+  useEffect(() => {
+    if (!isTauri()) return;
+    invoke<string[]>("timeup_list_monitors")
+      .then(setMonitorNames)
+      .catch((err) => console.error("Failed to list monitors:", err));
+  }, []);
+
+  // HTTP API と WebSocket 配信はバックエンドが保存・起動する
+  const httpApi = useLocalServer("http_api_get_status", "http_api_set_config");
+  const eventStream = useLocalServer("event_stream_get_status", "event_stream_set_config");
 
   // 起動時にストアから読み込み
   useEffect(() => {
@@ -145,6 +325,19 @@ const SettingsApp: React.FC = () => {
     [persist, settings]
   );
 
+  // セレクトの値: "all" / "main" / "named:<モニター名>"
+  const timeUpScreenValue =
+    settings.timeUpScreen.kind === "named" ? `named:${settings.timeUpScreen.name}` : settings.timeUpScreen.kind;
+  const handleTimeUpScreenChange = useCallback(
+    (value: string) => {
+      const screen: TimeUpScreen = value.startsWith("named:")
+        ? { kind: "named", name: value.slice("named:".length) }
+        : normalizeTimeUpScreen({ kind: value });
+      update("timeUpScreen", screen);
+    },
+    [update]
+  );
+
   const handleLanguageChange = useCallback(
     (value: string) => {
       const lang = value as LanguageSetting;
@@ -275,6 +468,38 @@ const SettingsApp: React.FC = () => {
             </label>
             <p className="settings-row-hint">{t("settings.window.showTimeUpWindowHint")}</p>
           </div>
+          <div className="settings-row settings-row-inline">
+            <span className="settings-row-label">{t("settings.window.timeUpScreen")}</span>
+            <div className="settings-row-control">
+              <select
+                className="settings-select"
+                value={timeUpScreenValue}
+                onChange={(e) => handleTimeUpScreenChange(e.target.value)}
+              >
+                <option value="main">{t("settings.window.timeUpScreenMain")}</option>
+                <option value="all">{t("settings.window.timeUpScreenAll")}</option>
+                {settings.timeUpScreen.kind === "named" && !monitorNames.includes(settings.timeUpScreen.name) && (
+                  <option value={timeUpScreenValue}>{settings.timeUpScreen.name}</option>
+                )}
+                {monitorNames.map((name) => (
+                  <option key={name} value={`named:${name}`}>
+                    {name}
+                  </option>
+                ))}
+              </select>
+            </div>
+          </div>
+          <div className="settings-row">
+            <label className="settings-toggle">
+              <input
+                type="checkbox"
+                checked={settings.overtime}
+                onChange={(e) => update("overtime", e.target.checked)}
+              />
+              <span>{t("settings.window.overtime")}</span>
+            </label>
+            <p className="settings-row-hint">{t("settings.window.overtimeHint")}</p>
+          </div>
         </section>
 
         <section className="settings-section">
@@ -302,6 +527,89 @@ const SettingsApp: React.FC = () => {
               </button>
             </div>
           </div>
+          <div className="settings-row settings-row-inline">
+            <span className="settings-row-label">{t("settings.alarm.missed")}</span>
+            <div className="settings-row-control">
+              <select
+                className="settings-select"
+                value={settings.missedAlarmPolicy}
+                onChange={(e) => update("missedAlarmPolicy", e.target.value as SettingsType["missedAlarmPolicy"])}
+              >
+                <option value="fireLate">{t("settings.alarm.missedFireLate")}</option>
+                <option value="drop">{t("settings.alarm.missedDrop")}</option>
+              </select>
+            </div>
+          </div>
+          <p className="settings-row-hint">{t("settings.alarm.missedHint")}</p>
+          <p className="settings-row-hint">{t("settings.alarm.escalationHint")}</p>
+          <div className="settings-row">
+            <label className="settings-toggle">
+              <input
+                type="checkbox"
+                checked={settings.alertLayerFlash}
+                onChange={(e) => update("alertLayerFlash", e.target.checked)}
+              />
+              <span>{t("settings.alarm.layerFlash")}</span>
+            </label>
+          </div>
+          <div className="settings-row settings-row-inline">
+            <label className="settings-toggle">
+              <input
+                type="checkbox"
+                checked={settings.alertNotification}
+                onChange={(e) => update("alertNotification", e.target.checked)}
+              />
+              <span>{t("settings.alarm.notification")}</span>
+            </label>
+            <div className="settings-row-control">
+              <input
+                type="number"
+                className="settings-port-input"
+                min={0}
+                max={MAX_ALERT_DELAY_SECS}
+                value={settings.alertNotificationDelaySecs}
+                disabled={!settings.alertNotification}
+                onChange={(e) =>
+                  update(
+                    "alertNotificationDelaySecs",
+                    clampAlertDelay(e.target.valueAsNumber, settings.alertNotificationDelaySecs)
+                  )
+                }
+              />
+              <span>{t("settings.alarm.secondsLater")}</span>
+            </div>
+          </div>
+          <div className="settings-row settings-row-inline">
+            <span className="settings-row-label">{t("settings.alarm.timeUpDelay")}</span>
+            <div className="settings-row-control">
+              <input
+                type="number"
+                className="settings-port-input"
+                min={0}
+                max={MAX_ALERT_DELAY_SECS}
+                value={settings.alertTimeUpDelaySecs}
+                disabled={!settings.showTimeUpWindow}
+                onChange={(e) =>
+                  update("alertTimeUpDelaySecs", clampAlertDelay(e.target.valueAsNumber, settings.alertTimeUpDelaySecs))
+                }
+              />
+              <span>{t("settings.alarm.secondsLater")}</span>
+            </div>
+          </div>
+          <div className="settings-row settings-row-inline">
+            <span className="settings-row-label">{t("settings.alarm.snooze")}</span>
+            <div className="settings-row-control">
+              <input
+                type="number"
+                className="settings-port-input"
+                min={1}
+                max={MAX_SNOOZE_MINUTES}
+                value={settings.snoozeMinutes}
+                onChange={(e) => update("snoozeMinutes", clampSnoozeMinutes(e.target.valueAsNumber))}
+              />
+              <span>{t("settings.alarm.minutes")}</span>
+            </div>
+          </div>
         </section>
 
         <section className="settings-section">
@@ -407,6 +715,71 @@ const SettingsApp: React.FC = () => {
             <p className="settings-row-hint">{t("settings.layer.preview")}</p>
           </div>
         </section>
+
+        {shortcuts && (
+          <section className="settings-section">
+            <h2 className="settings-section-title">{t("settings.shortcuts.title")}</h2>
+            {SHORTCUT_ACTIONS.map((action) => {
+              const conflict = shortcutConflicts.find((c) => c.action === action);
+              return (
+                <div className="settings-row" key={action}>
+                  <div className="settings-row settings-row-inline">
+                    <span className="settings-row-label">{t(`settings.shortcuts.${action}`)}</span>
+                    <input
+                      type="text"
+                      className="settings-shortcut-input"
+                      value={shortcuts[action]}
+                      placeholder={t("settings.shortcuts.disabled")}
+                      spellCheck={false}
+                      onChange={(e) => setShortcuts({ ...shortcuts, [action]: e.target.value })}
+                      onBlur={() => void saveShortcuts(shortcuts)}
+                      onKeyDown={(e) => {
+                        if (e.key === "Enter") void saveShortcuts(shortcuts);
+                      }}
+                    />
+                  </div>
+                  {conflict && <p className="settings-row-error">{conflict.reason}</p>}
+                </div>
+              );
+            })}
+            {shortcutConflicts
+              .filter((c) => c.action === "launchPreset")
+              .map((c) => (
+                <p className="settings-row-error" key={c.presetId}>
+                  {t("settings.shortcuts.launchPreset")} {c.presetId} ({c.accelerator}): {c.reason}
+                </p>
+              ))}
+            <div className="settings-row settings-row-inline">
+              <p className="settings-row-hint">{t("settings.shortcuts.hint")}</p>
+              <button
+                type="button"
+                className="settings-text-button"
+                onClick={() => void saveShortcuts({})}
+                title={t("settings.layer.resetToDefaultTitle")}
+              >
+                {t("settings.layer.resetToDefault")}
+              </button>
+            </div>
+          </section>
+        )}
+
+        {httpApi.status && eventStream.status && (
+          <section className="settings-section">
+            <h2 className="settings-section-title">{t("settings.localServer.title")}</h2>
+            <LocalServerRows
+              server={httpApi}
+              label={t("settings.localServer.httpApi")}
+              hint={t("settings.localServer.httpApiHint")}
+              scheme="http"
+            />
+            <LocalServerRows
+              server={eventStream}
+              label={t("settings.localServer.eventStream")}
+              hint={t("settings.localServer.eventStreamHint")}
+              scheme="ws"
+            />
+          </section>
+        )}
       </main>
     </div>
   );
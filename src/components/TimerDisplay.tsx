@@ -1,18 +1,33 @@
 import React from 'react';
 import { TimerDisplayProps } from '../types';
 
-const TimerDisplay: React.FC<TimerDisplayProps> = ({ minutes, seconds, isRunning, showTimeUp }) => {
+const TimerDisplay: React.FC<TimerDisplayProps> = ({ hours = 0, minutes, seconds, isRunning, showTimeUp, overtime }) => {
   const formatTime = (value: number): string => {
     return value.toString().padStart(2, '0');
   };
 
+  // 1 時間以上は H:MM:SS、未満は MM:SS
+  const clock = hours > 0
+    ? `${hours}:${formatTime(minutes)}:${formatTime(seconds)}`
+    : `${formatTime(minutes)}:${formatTime(seconds)}`;
+
   return (
     <div className={`timer-display ${isRunning ? 'running' : ''}`}>
       <div className="time">
-        {showTimeUp ? (
+        {overtime ? (
+          <span className="overtime-text">
+            -{clock}
+          </span>
+        ) : showTimeUp ? (
           <span className="timeup-text">Time UP!</span>
         ) : (
           <>
+            {hours > 0 && (
+              <>
+                <span className="hours">{hours}</span>
+                <span className="separator">:</span>
+              </>
+            )}
             <span className="minutes">{formatTime(minutes)}</span>
             <span className="separator">:</span>
             <span className="seconds">{formatTime(seconds)}</span>
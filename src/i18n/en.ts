@@ -10,12 +10,29 @@ const en = {
       darkModeHint: "Toggle UI theme",
       showTimeUpWindow: 'Show "Time Up" window',
       showTimeUpWindowHint: 'Show full-screen "Time Up" screen when timer ends',
+      timeUpScreen: '"Time Up" on',
+      timeUpScreenMain: "Monitor with the timer window",
+      timeUpScreenAll: "All monitors",
+      overtime: "Count overtime",
+      overtimeHint: 'Keep counting up as negative time (e.g. "-01:23") after the timer ends',
     },
     alarm: {
       title: "Alarm",
       sound: "Sound",
       testPlay: "Play",
       testStop: "Stop",
+      missed: "Missed while asleep",
+      missedFireLate: "Ring late",
+      missedDrop: "Skip silently",
+      missedHint: "What to do with timers and alarms that ended while the computer was asleep",
+      escalationHint:
+        'When time is up: flash the layer overlay, then a notification, then the full-screen "Time Up" window. Delays count from when time is up.',
+      layerFlash: "Flash the layer overlay",
+      notification: "Desktop notification",
+      timeUpDelay: '"Time Up" window',
+      secondsLater: "seconds later",
+      snooze: "Snooze",
+      minutes: "minutes",
     },
     layer: {
       title: "Layer overlay",
@@ -32,6 +49,28 @@ const en = {
       resetToDefaultTitle: "Reset to default",
       preview: "Preview",
     },
+    shortcuts: {
+      title: "Global shortcuts",
+      startPause: "Start / Pause",
+      reset: "Reset",
+      addMinute: "Add one minute",
+      toggleLayer: "Toggle layer overlay",
+      dismissTimeup: 'Dismiss "Time Up"',
+      launchPreset: "Preset",
+      disabled: "Disabled",
+      hint: 'Work from any application, e.g. "CommandOrControl+Alt+Space". Leave empty to disable.',
+    },
+    localServer: {
+      title: "External control",
+      httpApi: "Enable local HTTP API",
+      httpApiHint:
+        "Control the timer from scripts or Stream Deck via 127.0.0.1. The port in use is written to http_api.port in the app data folder.",
+      eventStream: "Enable WebSocket event stream",
+      eventStreamHint:
+        "Push timer updates to wall displays or OBS browser sources. The port in use is written to event_stream.port in the app data folder.",
+      port: "Preferred port",
+      listening: "Listening on",
+    },
     language: {
       title: "Language",
       label: "Display language",
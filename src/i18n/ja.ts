@@ -13,12 +13,29 @@ const ja: Translations = {
       showTimeUpWindow: '"Time Up" 画面を表示',
       showTimeUpWindowHint:
         'タイマー終了時に全画面の "Time Up" 画面を表示します',
+      timeUpScreen: '"Time Up" を表示するモニター',
+      timeUpScreenMain: "タイマーウィンドウのモニター",
+      timeUpScreenAll: "すべてのモニター",
+      overtime: "超過時間を計測",
+      overtimeHint: 'タイマー終了後も "-01:23" のようにマイナス表示で超過時間を数えます',
     },
     alarm: {
       title: "アラーム",
       sound: "サウンド",
       testPlay: "試聴",
       testStop: "停止",
+      missed: "スリープ中に過ぎた場合",
+      missedFireLate: "遅れて鳴らす",
+      missedDrop: "鳴らさない",
+      missedHint: "コンピューターのスリープ中に終了したタイマーやアラームの扱い",
+      escalationHint:
+        '時間切れになるとレイヤーオーバーレイの点滅、通知、全画面の "Time Up" 画面の順に知らせます。待ち時間は時間切れから数えます',
+      layerFlash: "レイヤーオーバーレイを点滅",
+      notification: "デスクトップ通知",
+      timeUpDelay: '"Time Up" 画面',
+      secondsLater: "秒後",
+      snooze: "スヌーズ",
+      minutes: "分",
     },
     layer: {
       title: "レイヤーオーバーレイ",
@@ -35,6 +52,28 @@ const ja: Translations = {
       resetToDefaultTitle: "既定値に戻す",
       preview: "プレビュー",
     },
+    shortcuts: {
+      title: "グローバルショートカット",
+      startPause: "開始 / 一時停止",
+      reset: "リセット",
+      addMinute: "1 分追加",
+      toggleLayer: "レイヤー表示の切り替え",
+      dismissTimeup: "「Time Up」を閉じる",
+      launchPreset: "プリセット",
+      disabled: "無効",
+      hint: '他のアプリを使用中でも有効です（例: "CommandOrControl+Alt+Space"）。空欄にすると無効になります。',
+    },
+    localServer: {
+      title: "外部連携",
+      httpApi: "ローカル HTTP API を有効にする",
+      httpApiHint:
+        "スクリプトや Stream Deck から 127.0.0.1 経由でタイマーを操作できます。使用中のポートはアプリデータフォルダの http_api.port に書き出されます。",
+      eventStream: "WebSocket 配信を有効にする",
+      eventStreamHint:
+        "壁掛けディスプレイや OBS のブラウザソースにタイマーの変化を送ります。使用中のポートはアプリデータフォルダの event_stream.port に書き出されます。",
+      port: "優先ポート",
+      listening: "待ち受け中:",
+    },
     language: {
       title: "言語",
       label: "表示言語",
@@ -1,4 +1,6 @@
 export interface TimerState {
+  // 1 時間以上のときのみ 0 以外（minutes / seconds は 0〜59）
+  hours: number;
   minutes: number;
   seconds: number;
   isRunning: boolean;
@@ -6,6 +8,207 @@ export interface TimerState {
   timeRemaining: number;
 }
 
+// main ウィンドウが操作する既定タイマーの ID
+export const DEFAULT_TIMER_ID = "default";
+
+// タイマーごとのアラーム設定
+export interface TimerAlarm {
+  sound: string | null;
+  showTimeUpWindow: boolean;
+  overtime: boolean;
+}
+
+// バックエンドのタイマーエンジンが emit する状態
+export interface TimerSnapshot {
+  id: string;
+  name: string;
+  alarm: TimerAlarm;
+  status: "idle" | "running" | "paused" | "finished" | "overtime";
+  durationMs: number;
+  remainingMs: number;
+  // 0 到達からの超過時間（超過計測中のみ）
+  overrunMs: number;
+  hours: number;
+  minutes: number;
+  seconds: number;
+}
+
+// 起動時の復元結果
+export interface TimerRestoreReport {
+  id: string;
+  name: string;
+  status: TimerSnapshot["status"];
+  expiredWhileClosed: boolean;
+  overdueMs: number;
+}
+
+export interface PomodoroConfig {
+  workSecs: number;
+  shortBreakSecs: number;
+  longBreakSecs: number;
+  roundsBeforeLongBreak: number;
+  autoAdvance: boolean;
+  skipTimeupOnBreakEnd: boolean;
+}
+
+export type PomodoroPhase = "work" | "shortBreak" | "longBreak";
+
+// pomodoro-phase-changed イベントのペイロード（停止時は null）
+export interface PomodoroCycle {
+  config: PomodoroConfig;
+  phase: PomodoroPhase;
+  round: number;
+}
+
+export interface SequenceSegment {
+  label: string;
+  durationSecs: number;
+  alarm?: TimerAlarm;
+}
+
+export interface Sequence {
+  id: string;
+  name: string;
+  segments: SequenceSegment[];
+}
+
+// sequence-changed イベントのペイロード（終了時は null）
+export interface SequenceRun {
+  sequence: Sequence;
+  index: number;
+}
+
+// session-recorded イベントのペイロード / session_list の要素
+export interface SessionRecord {
+  timerId: string;
+  timerName: string;
+  durationMs: number;
+  finishedAtUnixMs: number;
+  overrunMs: number;
+}
+
+export type Weekday = "Mon" | "Tue" | "Wed" | "Thu" | "Fri" | "Sat" | "Sun";
+
+// 名前付きプリセット（preset_list の要素 / presets-changed イベントのペイロード）
+export interface Preset {
+  id: string;
+  name: string;
+  durationSecs: number;
+  // null なら既定タイマーの現在のアラーム設定を使う
+  alarm: TimerAlarm | null;
+  // 起動するグローバルショートカット（null・空文字列なら割り当てない）
+  shortcut: string | null;
+}
+
+// 時刻指定アラーム（alarm-fired / alarms-changed イベントのペイロード）
+export interface WallClockAlarm {
+  id: string;
+  label: string;
+  hour: number;
+  minute: number;
+  // 空なら 1 回だけ鳴る
+  weekdays: Weekday[];
+  enabled: boolean;
+  alarm: TimerAlarm;
+  lastFiredUnixMs: number | null;
+  // 保存した時刻。起動時はここから過ぎた回を確認する
+  armedUnixMs?: number | null;
+}
+
+export interface StopwatchLap {
+  number: number;
+  lapMs: number;
+  splitMs: number;
+}
+
+// stopwatch-tick イベントのペイロード
+export interface StopwatchSnapshot {
+  running: boolean;
+  elapsedMs: number;
+  laps: StopwatchLap[];
+  showOnLayer: boolean;
+}
+
+export type ShortcutAction = "startPause" | "reset" | "addMinute" | "toggleLayer" | "dismissTimeup";
+
+// グローバルショートカットの割り当て（空文字列は無効）
+export interface ShortcutConfig {
+  bindings: Record<ShortcutAction, string>;
+}
+
+// 登録できなかったショートカット（shortcut-conflicts イベントのペイロードの要素）
+export interface ShortcutConflict {
+  // プリセットの割り当ては "launchPreset" と presetId
+  action: ShortcutAction | "launchPreset";
+  presetId?: string;
+  accelerator: string;
+  reason: string;
+}
+
+// ローカル HTTP API・WebSocket 配信の設定（http_api.json / event_stream.json）
+export interface LocalServerConfig {
+  enabled: boolean;
+  port: number;
+}
+
+// ローカル HTTP API・WebSocket 配信の状態。port は実際に待ち受けているポート
+export interface LocalServerStatus {
+  config: LocalServerConfig;
+  port: number | null;
+  error: string | null;
+}
+
+// GUI の起動オプションのうちフロントエンドで反映する分（launch_take_options / launch-options）
+export interface LaunchOptions {
+  layer: boolean;
+  alwaysOnTop: boolean;
+}
+
+// スリープ中に過ぎたタイマー・アラームの扱い
+export type MissedAlarmPolicy = "fireLate" | "drop";
+
+// timer-resynced イベントのペイロード
+export interface ResyncReport {
+  reason: "suspended" | "stalled" | "wallClockMovedForward" | "wallClockMovedBack";
+  jumpMs: number;
+  missedTimers: string[];
+  policy: MissedAlarmPolicy;
+}
+
+// Time Up ウィンドウを出すモニター（timeup_set_screen）
+export type TimeUpScreen =
+  | { kind: "all" }
+  | { kind: "main" }
+  | { kind: "named"; name: string };
+
+export const DEFAULT_TIME_UP_SCREEN: TimeUpScreen = { kind: "main" };
+
+// 保存済みの値を検証する。不正なら main ウィンドウのモニター
+export function normalizeTimeUpScreen(saved: unknown): TimeUpScreen {
+  const value = saved as Partial<{ kind: string; name: unknown }> | null | undefined;
+  if (value?.kind === "all") return { kind: "all" };
+  if (value?.kind === "named" && typeof value.name === "string" && value.name !== "") {
+    return { kind: "named", name: value.name };
+  }
+  return DEFAULT_TIME_UP_SCREEN;
+}
+
+// 時間切れから通知・Time Up ウィンドウまでの待ち時間（秒）と、スヌーズの長さ（分）の範囲
+export const MAX_ALERT_DELAY_SECS = 3600;
+export const MAX_SNOOZE_MINUTES = 24 * 60;
+
+export function clampAlertDelay(saved: unknown, fallback: number): number {
+  return typeof saved === "number" && Number.isFinite(saved)
+    ? Math.min(Math.max(Math.round(saved), 0), MAX_ALERT_DELAY_SECS)
+    : fallback;
+}
+
+export function clampSnoozeMinutes(saved: unknown): number {
+  return typeof saved === "number" && Number.isFinite(saved)
+    ? Math.min(Math.max(Math.round(saved), 1), MAX_SNOOZE_MINUTES)
+    : 5;
+}
+
 export interface Settings {
   alwaysOnTop: boolean;
   darkMode: boolean;
@@ -13,6 +216,15 @@ export interface Settings {
   alarmVolume: number;
   displayMode: "normal" | "compact" | "minimal";
   showTimeUpWindow: boolean;
+  timeUpScreen: TimeUpScreen;
+  // 時間切れの知らせ方。Time Up ウィンドウ自体は showTimeUpWindow
+  alertLayerFlash: boolean;
+  alertNotification: boolean;
+  alertNotificationDelaySecs: number;
+  alertTimeUpDelaySecs: number;
+  snoozeMinutes: number;
+  overtime: boolean;
+  missedAlarmPolicy: MissedAlarmPolicy;
   layerTextColor: string;
   layerShadowStyle: "dark" | "light";
   layerFontSize: number;
@@ -20,10 +232,12 @@ export interface Settings {
 }
 
 export interface TimerDisplayProps {
+  hours?: number;
   minutes: number;
   seconds: number;
   isRunning: boolean;
   showTimeUp?: boolean;
+  overtime?: boolean;
 }
 
 export interface TimerControlsProps {